@@ -12,8 +12,17 @@ use std::sync::{Arc, Weak};
 #[derive(Debug)]
 pub enum CallbackResult {
     AuthSessionTicketResponse(AuthSessionTicketResponse),
+    AvatarImageLoaded(AvatarImageLoaded),
     DownloadItemResult(DownloadItemResult),
+    DurationControl(DurationControl),
+    EquippedProfileItems(EquippedProfileItems),
+    EquippedProfileItemsChanged(EquippedProfileItemsChanged),
     FloatingGamepadTextInputDismissed(FloatingGamepadTextInputDismissed),
+    FriendRichPresenceUpdate(FriendRichPresenceUpdate),
+    GameConnectedChatJoin(GameConnectedChatJoin),
+    GameConnectedChatLeave(GameConnectedChatLeave),
+    GameConnectedClanChatMsg(GameConnectedClanChatMsg),
+    GameConnectedFriendChatMsg(GameConnectedFriendChatMsg),
     GameLobbyJoinRequested(GameLobbyJoinRequested),
     GameOverlayActivated(GameOverlayActivated),
     GamepadTextInputDismissed(GamepadTextInputDismissed),
@@ -43,6 +52,7 @@ pub enum CallbackResult {
     UserAchievementIconFetched(UserAchievementIconFetched),
     UserStatsReceived(UserStatsReceived),
     UserStatsStored(UserStatsStored),
+    UserStatsUnloaded(UserStatsUnloaded),
     ValidateAuthTicketResponse(ValidateAuthTicketResponse),
     GSClientApprove(GSClientApprove),
     GSClientDeny(GSClientDeny),
@@ -60,10 +70,33 @@ impl CallbackResult {
             AuthSessionTicketResponse::ID => {
                 Self::AuthSessionTicketResponse(AuthSessionTicketResponse::from_raw(data))
             }
+            AvatarImageLoaded::ID => Self::AvatarImageLoaded(AvatarImageLoaded::from_raw(data)),
             DownloadItemResult::ID => Self::DownloadItemResult(DownloadItemResult::from_raw(data)),
+            DurationControl::ID => Self::DurationControl(DurationControl::from_raw(data)),
+            EquippedProfileItems::ID => {
+                Self::EquippedProfileItems(EquippedProfileItems::from_raw(data))
+            }
+            EquippedProfileItemsChanged::ID => {
+                Self::EquippedProfileItemsChanged(EquippedProfileItemsChanged::from_raw(data))
+            }
             FloatingGamepadTextInputDismissed::ID => Self::FloatingGamepadTextInputDismissed(
                 FloatingGamepadTextInputDismissed::from_raw(data),
             ),
+            FriendRichPresenceUpdate::ID => {
+                Self::FriendRichPresenceUpdate(FriendRichPresenceUpdate::from_raw(data))
+            }
+            GameConnectedChatJoin::ID => {
+                Self::GameConnectedChatJoin(GameConnectedChatJoin::from_raw(data))
+            }
+            GameConnectedChatLeave::ID => {
+                Self::GameConnectedChatLeave(GameConnectedChatLeave::from_raw(data))
+            }
+            GameConnectedClanChatMsg::ID => {
+                Self::GameConnectedClanChatMsg(GameConnectedClanChatMsg::from_raw(data))
+            }
+            GameConnectedFriendChatMsg::ID => {
+                Self::GameConnectedFriendChatMsg(GameConnectedFriendChatMsg::from_raw(data))
+            }
             GameLobbyJoinRequested::ID => {
                 Self::GameLobbyJoinRequested(GameLobbyJoinRequested::from_raw(data))
             }
@@ -128,6 +161,7 @@ impl CallbackResult {
             }
             UserStatsReceived::ID => Self::UserStatsReceived(UserStatsReceived::from_raw(data)),
             UserStatsStored::ID => Self::UserStatsStored(UserStatsStored::from_raw(data)),
+            UserStatsUnloaded::ID => Self::UserStatsUnloaded(UserStatsUnloaded::from_raw(data)),
             ValidateAuthTicketResponse::ID => {
                 Self::ValidateAuthTicketResponse(ValidateAuthTicketResponse::from_raw(data))
             }
@@ -174,6 +208,38 @@ impl Drop for CallbackHandle {
     }
 }
 
+/// A state machine that is driven by callbacks instead of registering its
+/// own.
+///
+/// Registering a callback replaces any other callback for the same ID, so
+/// types that track state across several callbacks leave registration to
+/// the caller. Forward every callback to
+/// [`handle_callback`](Self::handle_callback), e.g. from
+/// [`Client::process_callbacks`], and call [`poll`](Self::poll) afterwards
+/// to apply finished async calls and timeouts.
+///
+/// ```no_run
+/// # use steamworks::*;
+/// fn run<M: CallbackStateMachine>(client: &Client, machine: &mut M) -> Vec<M::Event> {
+///     client.process_callbacks(|callback| machine.handle_callback(&callback));
+///     machine.poll()
+/// }
+/// ```
+pub trait CallbackStateMachine {
+    /// What [`poll`](Self::poll) reports
+    type Event;
+
+    /// Applies any of the callbacks the machine handles, ignoring all
+    /// others. By default all callbacks are ignored, for machines that are
+    /// only driven by async calls.
+    fn handle_callback(&mut self, callback: &CallbackResult) {
+        let _ = callback;
+    }
+
+    /// Advances the machine and returns all events since the last call
+    fn poll(&mut self) -> Vec<Self::Event>;
+}
+
 macro_rules! impl_callback {
     ($fn_arg_name:ident: $sys_ty:ident => $callback_ty:ident $from_raw_impl:tt) => {
         paste::item! {
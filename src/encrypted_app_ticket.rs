@@ -0,0 +1,483 @@
+//! Decryption of encrypted app tickets without Valve's
+//! `sdkencryptedappticket` library.
+//!
+//! Tickets are requested by the client with
+//! [`User::request_encrypted_app_ticket`] and decrypted on the server with
+//! the app's encrypted ticket key from the Steamworks partner site.
+
+use super::*;
+use aes::cipher::{generic_array::GenericArray, BlockDecrypt, KeyInit};
+use aes::Aes256;
+use sha1::{Digest, Sha1};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The length of an app's encrypted ticket key
+pub const ENCRYPTED_APP_TICKET_KEY_LEN: usize = 32;
+
+/// Errors from [`DecryptedAppTicket::decrypt`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Error)]
+pub enum AppTicketError {
+    /// The data is not an encrypted app ticket
+    #[error("malformed ticket")]
+    Malformed,
+    /// The ticket could not be decrypted, usually because the key is
+    /// not the key of the app the ticket was issued for
+    #[error("failed to decrypt the ticket")]
+    Decryption,
+    /// The decrypted ticket does not match its checksum
+    #[error("ticket checksum mismatch")]
+    ChecksumMismatch,
+    /// The contents of the ticket have been modified
+    #[error("ticket hash mismatch")]
+    HashMismatch,
+}
+
+/// A DLC listed in a [`DecryptedAppTicket`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AppTicketDlc {
+    pub app_id: AppId,
+    /// The packages granting the DLC
+    pub licenses: Vec<u32>,
+}
+
+/// The contents of an encrypted app ticket
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DecryptedAppTicket {
+    /// The user the ticket was issued to
+    pub steam_id: SteamId,
+    /// The app the ticket was issued for
+    pub app_id: AppId,
+    /// When the ticket was issued, in seconds since the unix epoch
+    pub issue_time: u32,
+    /// When the ticket expires, in seconds since the unix epoch
+    pub expire_time: u32,
+    /// The data passed to [`User::request_encrypted_app_ticket`]
+    pub user_data: Vec<u8>,
+    /// The packages granting the app
+    pub licenses: Vec<u32>,
+    /// The DLC of the app the user owns
+    pub dlc: Vec<AppTicketDlc>,
+    /// Undocumented by Valve. Read from where community decoders place the
+    /// VAC flag, which has not been checked against a real ticket.
+    pub vac_banned: bool,
+    /// The app is borrowed through Steam Family Sharing. Undocumented by
+    /// Valve, like [`license_temporary`](Self::license_temporary), and
+    /// read from the flag bit community decoders use.
+    pub license_borrowed: bool,
+    /// The license is temporary, e.g. a free weekend. Undocumented by
+    /// Valve.
+    pub license_temporary: bool,
+    /// Whether the ticket carried a salted hash of its contents that was
+    /// checked. Tickets without one are only protected by their CRC.
+    pub hash_verified: bool,
+    /// Valve's 128 byte RSA signature of the ownership ticket, if the
+    /// ticket is signed. The signature is not verified.
+    pub signature: Option<Vec<u8>>,
+}
+
+impl DecryptedAppTicket {
+    /// Decrypts and verifies a ticket with the app's encrypted ticket key
+    pub fn decrypt(
+        ticket: &[u8],
+        key: &[u8; ENCRYPTED_APP_TICKET_KEY_LEN],
+    ) -> Result<DecryptedAppTicket, AppTicketError> {
+        let outer = OuterTicket::parse(ticket)?;
+        let plain = symmetric_decrypt(outer.encrypted_ticket, key)?;
+        if crc32fast::hash(&plain) != outer.crc {
+            return Err(AppTicketError::ChecksumMismatch);
+        }
+        let user_data_len = outer.user_data_len as usize;
+        let user_data = plain
+            .get(..user_data_len)
+            .ok_or(AppTicketError::Malformed)?;
+        let mut reader = Reader(&plain[user_data_len..]);
+        // The length at the start of the ownership ticket does not include
+        // the signature, the length in the outer message does
+        let ownership_len = reader.clone().u32()? as usize;
+        let ownership = reader.take(ownership_len)?;
+        let signature = match outer.ownership_len.map(|len| len as usize) {
+            None => None,
+            Some(len) if len == ownership_len => None,
+            Some(len) if len == ownership_len + SIGNATURE_LEN => {
+                Some(reader.take(SIGNATURE_LEN)?.to_vec())
+            }
+            Some(_) => return Err(AppTicketError::Malformed),
+        };
+        let hash_verified = verify_hash(&plain[..plain.len() - reader.0.len()], reader.0)?;
+
+        let mut ticket = parse_ownership_ticket(ownership)?;
+        ticket.user_data = user_data.to_vec();
+        ticket.signature = signature;
+        ticket.hash_verified = hash_verified;
+        Ok(ticket)
+    }
+
+    /// Returns whether the ticket carries Valve's signature
+    pub fn is_signed(&self) -> bool {
+        self.signature.is_some()
+    }
+
+    /// Returns whether the ticket was issued for the given app
+    pub fn is_for_app(&self, app_id: AppId) -> bool {
+        self.app_id == app_id
+    }
+
+    /// Returns whether the user owns the given app or DLC
+    pub fn owns_app(&self, app_id: AppId) -> bool {
+        self.is_for_app(app_id) || self.dlc.iter().any(|dlc| dlc.app_id == app_id)
+    }
+
+    /// Returns when the ticket was issued
+    pub fn issued_at(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(self.issue_time.into())
+    }
+}
+
+/// The length of the RSA signature following a signed ownership ticket
+const SIGNATURE_LEN: usize = 128;
+
+/// The `EncryptedAppTicket` protobuf message the ticket is wrapped in
+struct OuterTicket<'a> {
+    crc: u32,
+    user_data_len: u32,
+    /// The length of the ownership ticket including its signature
+    ownership_len: Option<u32>,
+    encrypted_ticket: &'a [u8],
+}
+
+impl<'a> OuterTicket<'a> {
+    fn parse(data: &'a [u8]) -> Result<Self, AppTicketError> {
+        let mut reader = Reader(data);
+        let mut version = None;
+        let mut crc = None;
+        let mut user_data_len = None;
+        let mut ownership_len = None;
+        let mut encrypted_ticket = None;
+        while !reader.0.is_empty() {
+            let key = reader.varint()?;
+            match (key >> 3, key & 7) {
+                (1, 0) => version = Some(reader.varint()? as u32),
+                (2, 0) => crc = Some(reader.varint()? as u32),
+                (3, 0) => user_data_len = Some(reader.varint()? as u32),
+                (4, 0) => ownership_len = Some(reader.varint()? as u32),
+                (5, 2) => {
+                    let len = reader.varint()? as usize;
+                    encrypted_ticket = Some(reader.take(len)?);
+                }
+                (_, 0) => {
+                    reader.varint()?;
+                }
+                (_, 1) => {
+                    reader.take(8)?;
+                }
+                (_, 2) => {
+                    let len = reader.varint()? as usize;
+                    reader.take(len)?;
+                }
+                (_, 5) => {
+                    reader.take(4)?;
+                }
+                _ => return Err(AppTicketError::Malformed),
+            }
+        }
+        match (version, crc, user_data_len, encrypted_ticket) {
+            (Some(version), Some(crc), Some(user_data_len), Some(encrypted_ticket))
+                if version != 0 =>
+            {
+                Ok(OuterTicket {
+                    crc,
+                    user_data_len,
+                    ownership_len,
+                    encrypted_ticket,
+                })
+            }
+            _ => Err(AppTicketError::Malformed),
+        }
+    }
+}
+
+/// Decrypts data encrypted with Steam's symmetric encryption: an AES-ECB
+/// encrypted IV followed by the AES-CBC encrypted, PKCS#7 padded data.
+fn symmetric_decrypt(
+    data: &[u8],
+    key: &[u8; ENCRYPTED_APP_TICKET_KEY_LEN],
+) -> Result<Vec<u8>, AppTicketError> {
+    if data.len() < 32 || data.len() % 16 != 0 {
+        return Err(AppTicketError::Decryption);
+    }
+    let cipher = Aes256::new(GenericArray::from_slice(key));
+    let mut prev = GenericArray::clone_from_slice(&data[..16]);
+    cipher.decrypt_block(&mut prev);
+
+    let mut plain = Vec::with_capacity(data.len() - 16);
+    for chunk in data[16..].chunks_exact(16) {
+        let mut block = GenericArray::clone_from_slice(chunk);
+        cipher.decrypt_block(&mut block);
+        plain.extend(block.iter().zip(prev.iter()).map(|(b, p)| b ^ p));
+        prev = GenericArray::clone_from_slice(chunk);
+    }
+
+    let padding = *plain.last().unwrap() as usize;
+    if padding == 0
+        || padding > 16
+        || !plain[plain.len() - padding..]
+            .iter()
+            .all(|&b| b as usize == padding)
+    {
+        return Err(AppTicketError::Decryption);
+    }
+    plain.truncate(plain.len() - padding);
+    Ok(plain)
+}
+
+/// Verifies the salted SHA-1 hash of the user data and ownership ticket
+/// that follows them, returning whether there was a hash. Anything after
+/// the hash is ignored.
+fn verify_hash(hashed: &[u8], remainder: &[u8]) -> Result<bool, AppTicketError> {
+    const SALT_LEN: usize = 8;
+    const HASH_LEN: usize = 20;
+
+    if remainder.len() < SALT_LEN + HASH_LEN {
+        return Ok(false);
+    }
+    let (salt, hash) = (
+        &remainder[..SALT_LEN],
+        &remainder[SALT_LEN..SALT_LEN + HASH_LEN],
+    );
+    let mut hasher = Sha1::new();
+    hasher.update(hashed);
+    hasher.update(salt);
+    if hasher.finalize().as_slice() != hash {
+        return Err(AppTicketError::HashMismatch);
+    }
+    Ok(true)
+}
+
+fn parse_ownership_ticket(data: &[u8]) -> Result<DecryptedAppTicket, AppTicketError> {
+    const FLAG_BORROWED: u32 = 1 << 1;
+    const FLAG_TEMPORARY: u32 = 1 << 2;
+
+    let mut reader = Reader(data);
+    let _len = reader.u32()?;
+    let version = reader.u32()?;
+    if version < 2 {
+        return Err(AppTicketError::Malformed);
+    }
+    let steam_id = SteamId(reader.u64()?);
+    let app_id = AppId(reader.u32()?);
+    let _external_ip = reader.u32()?;
+    let _internal_ip = reader.u32()?;
+    let flags = reader.u32()?;
+    let issue_time = reader.u32()?;
+    let expire_time = reader.u32()?;
+
+    let licenses = reader.licenses()?;
+    let dlc = (0..reader.u16()?)
+        .map(|_| {
+            Ok(AppTicketDlc {
+                app_id: AppId(reader.u32()?),
+                licenses: reader.licenses()?,
+            })
+        })
+        .collect::<Result<_, AppTicketError>>()?;
+    let vac_banned = reader.u16()? != 0;
+
+    Ok(DecryptedAppTicket {
+        steam_id,
+        app_id,
+        issue_time,
+        expire_time,
+        user_data: Vec::new(),
+        licenses,
+        dlc,
+        vac_banned,
+        license_borrowed: flags & FLAG_BORROWED != 0,
+        license_temporary: flags & FLAG_TEMPORARY != 0,
+        signature: None,
+        hash_verified: false,
+    })
+}
+
+#[derive(Clone)]
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], AppTicketError> {
+        if len > self.0.len() {
+            return Err(AppTicketError::Malformed);
+        }
+        let (head, tail) = self.0.split_at(len);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    fn u16(&mut self) -> Result<u16, AppTicketError> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, AppTicketError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, AppTicketError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn varint(&mut self) -> Result<u64, AppTicketError> {
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.take(1)?[0];
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(AppTicketError::Malformed)
+    }
+
+    fn licenses(&mut self) -> Result<Vec<u32>, AppTicketError> {
+        (0..self.u16()?).map(|_| self.u32()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: [u8; ENCRYPTED_APP_TICKET_KEY_LEN] = [
+        0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24,
+        25, 26, 27, 28, 29, 30, 31,
+    ];
+
+    // The fixtures are built by encrypting tickets laid out like Valve's,
+    // as no real ticket has been published together with its app's key.
+    // They check the parser against that layout, not against Steam.
+
+    // A ticket with user data and DLC, ending in a salted hash
+    const SALTED_TICKET: &str = "080410d59c82c205180820462a80015a6e045708fb7196f02e553d02c3a6927c\
+        3a85b12eabfbbda2e420e22bbd95571ba54376de3eb4aa7609e99bcbabfc4d5be7d048091f4508b74504017b\
+        12b2cc3a06fcd78e5f5cee2a159700dbb8aa3cf21e66f157015c8e40d9f5abb9b8ac7a195e4399938b1fdacf\
+        c02cbd833d6c12698a754af5328b058ecc3db6ae837466";
+
+    // A signed ticket without user data, with unknown data after the hash
+    const SIGNED_TICKET: &str = "0804108eaddec801180020b2012a8002e9c3ef8ab23453e6f0749cd636e7a88e\
+        440d78c0ae46bfa742bff283e7659cae77408b773d462d5a48953b17eb5e7f06cc2f27c6b4071b31dc42bff4\
+        eee52495d9914eb91a2b42dc278aa67180d6bc7cd8ecb690bc9d59183bbd1d0f69bcb670e0d36314feba2b68\
+        75ddd1e241f5dccc6e192c293978ecba11697bfa9610b783b77be815ae5ad147b3f8ee51b364d40ad1c4f26b\
+        be67c4c85a44eb3c3e68c23851991ae6655e543132cf03c54f2c29d6f74567fa2531bd1c9ecbc1c9a1596d14\
+        24586824d8197bea23eb16a85f2f30a8ff3796fa1e283ba2e044b42b0c9a8e953694a928339c6b0aa9e98e93\
+        8315a284f0d541212d2e626295e0aaaa97187b12";
+
+    // A ticket without a hash after the ownership ticket
+    const UNHASHED_TICKET: &str =
+        "080410f0f5e3c701180020322a5061a6936e4e8f101c1cc1f993b542a0d411b9\
+        b2bf48656a9d2166c516a4dd1b9f78e7a018810f9c04cb1f8749e088714431f927f0b3ea12546f8284b523cc\
+        b76c79328f32f414eec590fda4c2aa13dc2a";
+
+    fn unhex(hex: &str) -> Vec<u8> {
+        let hex: String = hex.split_whitespace().collect();
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn decrypt_salted_ticket() {
+        let ticket = DecryptedAppTicket::decrypt(&unhex(SALTED_TICKET), &KEY).unwrap();
+        assert_eq!(ticket.steam_id, SteamId(76561197960287930));
+        assert_eq!(ticket.app_id, AppId(480));
+        assert_eq!(ticket.issue_time, 1700000000);
+        assert_eq!(ticket.expire_time, 1700086400);
+        assert_eq!(ticket.user_data, b"match:42");
+        assert_eq!(ticket.licenses, vec![0, 12345]);
+        assert_eq!(
+            ticket.dlc,
+            vec![
+                AppTicketDlc {
+                    app_id: AppId(1000),
+                    licenses: vec![777],
+                },
+                AppTicketDlc {
+                    app_id: AppId(1001),
+                    licenses: vec![],
+                },
+            ]
+        );
+        assert!(ticket.vac_banned);
+        assert!(ticket.license_borrowed);
+        assert!(!ticket.license_temporary);
+        assert!(!ticket.is_signed());
+        assert!(ticket.hash_verified);
+        assert!(ticket.is_for_app(AppId(480)));
+        assert!(ticket.owns_app(AppId(1001)));
+        assert!(!ticket.owns_app(AppId(9)));
+    }
+
+    #[test]
+    fn decrypt_signed_ticket() {
+        let ticket = DecryptedAppTicket::decrypt(&unhex(SIGNED_TICKET), &KEY).unwrap();
+        assert_eq!(ticket.steam_id, SteamId(76561198000000000));
+        assert_eq!(ticket.app_id, AppId(730));
+        assert_eq!(ticket.issue_time, 1710000000);
+        assert!(ticket.user_data.is_empty());
+        assert_eq!(ticket.licenses, vec![54321]);
+        assert!(ticket.dlc.is_empty());
+        assert!(!ticket.vac_banned);
+        assert!(!ticket.license_borrowed);
+        assert!(ticket.license_temporary);
+        assert!(ticket.hash_verified);
+        let signature = ticket.signature.unwrap();
+        assert_eq!(signature.len(), SIGNATURE_LEN);
+        assert_eq!(signature[1], 7);
+    }
+
+    #[test]
+    fn decrypt_unhashed_ticket() {
+        let ticket = DecryptedAppTicket::decrypt(&unhex(UNHASHED_TICKET), &KEY).unwrap();
+        assert_eq!(ticket.app_id, AppId(480));
+        assert_eq!(ticket.licenses, vec![7]);
+        assert!(!ticket.hash_verified);
+    }
+
+    #[test]
+    fn rejects_wrong_key() {
+        let err = DecryptedAppTicket::decrypt(&unhex(SALTED_TICKET), &[0; 32]).unwrap_err();
+        assert!(matches!(
+            err,
+            AppTicketError::Decryption | AppTicketError::ChecksumMismatch
+        ));
+    }
+
+    #[test]
+    fn rejects_modified_ticket() {
+        let mut ticket = unhex(SALTED_TICKET);
+        // Changing the checksum in the outer message
+        ticket[3] ^= 1;
+        assert_eq!(
+            DecryptedAppTicket::decrypt(&ticket, &KEY),
+            Err(AppTicketError::ChecksumMismatch)
+        );
+
+        // The ownership ticket length in the outer message must match
+        let mut ticket = unhex(SALTED_TICKET);
+        assert_eq!(ticket[10..12], [0x20, 70]);
+        ticket[11] = 71;
+        assert_eq!(
+            DecryptedAppTicket::decrypt(&ticket, &KEY),
+            Err(AppTicketError::Malformed)
+        );
+
+        let ticket = unhex(SALTED_TICKET);
+        assert_eq!(
+            DecryptedAppTicket::decrypt(&ticket[..ticket.len() - 16], &KEY),
+            Err(AppTicketError::Malformed)
+        );
+        assert_eq!(
+            DecryptedAppTicket::decrypt(b"not a ticket", &KEY),
+            Err(AppTicketError::Malformed)
+        );
+    }
+}
@@ -1,3 +1,14 @@
+mod avatar;
+mod overlay;
+mod profile_items;
+mod rich_presence;
+mod tracker;
+
+pub use self::avatar::*;
+pub use self::overlay::*;
+pub use self::profile_items::*;
+pub use self::rich_presence::*;
+pub use self::tracker::*;
 use super::*;
 use std::net::Ipv4Addr;
 
@@ -127,14 +138,28 @@ impl Friends {
         }
     }
 
+    /// Opens one of the overlay dialogs
+    pub fn activate_game_overlay_dialog(&self, dialog: OverlayDialog) {
+        self.activate_game_overlay(&dialog.as_str());
+    }
+
     // I don't know why these are part of friends either
     pub fn activate_game_overlay_to_web_page(&self, url: &str) {
+        self.activate_game_overlay_to_web_page_with_mode(url, OverlayWebPageMode::Default);
+    }
+
+    /// Opens the overlay web browser to the specified URL.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `url` str contains a null byte.
+    pub fn activate_game_overlay_to_web_page_with_mode(&self, url: &str, mode: OverlayWebPageMode) {
         unsafe {
             let url = CString::new(url).unwrap();
             sys::SteamAPI_ISteamFriends_ActivateGameOverlayToWebPage(
                 self.friends,
                 url.as_ptr(),
-                sys::EActivateGameOverlayToWebPageMode::k_EActivateGameOverlayToWebPageMode_Default,
+                mode.into(),
             );
         }
     }
@@ -180,6 +205,16 @@ impl Friends {
         }
     }
 
+    /// Opens up a Remote Play Together invite dialog for the given lobby
+    pub fn activate_remote_play_together_invite_dialog(&self, lobby: LobbyId) {
+        unsafe {
+            sys::SteamAPI_ISteamFriends_ActivateGameOverlayRemotePlayTogetherInviteDialog(
+                self.friends,
+                lobby.0,
+            );
+        }
+    }
+
     /// Opens up an invite dialog that will send Rich Presence connect string to friends
     ///
     /// # Panics
@@ -219,10 +254,334 @@ impl Friends {
             sys::SteamAPI_ISteamFriends_ClearRichPresence(self.friends);
         }
     }
+
+    /// Returns a [`RichPresenceUpdater`] that sets typed rich presence,
+    /// only sending the keys that changed.
+    pub fn rich_presence_updater(&self) -> RichPresenceUpdater {
+        RichPresenceUpdater::new(self)
+    }
+
+    /// Returns a [`FriendsTracker`] that keeps a snapshot of the friends
+    /// matching `flags` up to date.
+    pub fn tracker(&self, flags: FriendFlags) -> FriendsTracker {
+        FriendsTracker::new(self, flags)
+    }
+
+    /// Returns an [`OverlayState`] that tracks whether the overlay is open.
+    pub fn overlay_state(&self) -> OverlayState {
+        OverlayState::new()
+    }
+
+    /// Returns an [`AvatarLoader`] that notifies when user avatars have
+    /// been downloaded.
+    pub fn avatar_loader(&self) -> AvatarLoader {
+        AvatarLoader::new(self)
+    }
+
+    /// Gets the number of users following the specified user.
+    pub fn get_follower_count<F>(&self, user: SteamId, cb: F)
+    where
+        F: FnOnce(SResult<u32>) + 'static + Send,
+    {
+        unsafe {
+            let api_call = sys::SteamAPI_ISteamFriends_GetFollowerCount(self.friends, user.0);
+            register_call_result::<sys::FriendsGetFollowerCount_t, _>(
+                &self.inner,
+                api_call,
+                move |v, io_error| {
+                    cb(if io_error {
+                        Err(SteamError::IOFailure)
+                    } else {
+                        crate::to_steam_result(v.m_eResult).map(|_| v.m_nCount as u32)
+                    })
+                },
+            );
+        }
+    }
+
+    /// Checks if the current user is following the specified user.
+    pub fn is_following<F>(&self, user: SteamId, cb: F)
+    where
+        F: FnOnce(SResult<bool>) + 'static + Send,
+    {
+        unsafe {
+            let api_call = sys::SteamAPI_ISteamFriends_IsFollowing(self.friends, user.0);
+            register_call_result::<sys::FriendsIsFollowing_t, _>(
+                &self.inner,
+                api_call,
+                move |v, io_error| {
+                    cb(if io_error {
+                        Err(SteamError::IOFailure)
+                    } else {
+                        crate::to_steam_result(v.m_eResult).map(|_| v.m_bIsFollowing)
+                    })
+                },
+            );
+        }
+    }
+
+    /// Gets the list of users that the current user is following.
+    ///
+    /// At most 50 users are returned per call, use `start_index` to page
+    /// through the rest of the list.
+    pub fn enumerate_following_list<F>(&self, start_index: u32, cb: F)
+    where
+        F: FnOnce(SResult<FollowingList>) + 'static + Send,
+    {
+        unsafe {
+            let api_call =
+                sys::SteamAPI_ISteamFriends_EnumerateFollowingList(self.friends, start_index);
+            register_call_result::<sys::FriendsEnumerateFollowingList_t, _>(
+                &self.inner,
+                api_call,
+                move |v, io_error| {
+                    cb(if io_error {
+                        Err(SteamError::IOFailure)
+                    } else {
+                        crate::to_steam_result(v.m_eResult).map(|_| {
+                            let count =
+                                (v.m_nResultsReturned.max(0) as usize).min(v.m_rgSteamID.len());
+                            FollowingList {
+                                users: v.m_rgSteamID[..count]
+                                    .iter()
+                                    .map(|id| SteamId(id.m_steamid.m_unAll64Bits))
+                                    .collect(),
+                                total: v.m_nTotalResultCount as u32,
+                            }
+                        })
+                    })
+                },
+            );
+        }
+    }
+
+    /// Returns the friends groups (tags) the current user has created in
+    /// the Steam friends list.
+    pub fn get_friends_groups(&self) -> Vec<FriendsGroup> {
+        unsafe {
+            let count = sys::SteamAPI_ISteamFriends_GetFriendsGroupCount(self.friends);
+            (0..count)
+                .map(|idx| sys::SteamAPI_ISteamFriends_GetFriendsGroupIDByIndex(self.friends, idx))
+                .filter(|id| *id != sys::k_FriendsGroupID_Invalid)
+                .map(|id| self.get_friends_group(FriendsGroupId(id)))
+                .collect()
+        }
+    }
+
+    /// Returns the name and members of a friends group
+    pub fn get_friends_group(&self, id: FriendsGroupId) -> FriendsGroup {
+        unsafe {
+            let name = sys::SteamAPI_ISteamFriends_GetFriendsGroupName(self.friends, id.0);
+            let name = if name.is_null() {
+                String::new()
+            } else {
+                CStr::from_ptr(name).to_string_lossy().into_owned()
+            };
+            let count = sys::SteamAPI_ISteamFriends_GetFriendsGroupMembersCount(self.friends, id.0);
+            let mut members = vec![
+                sys::CSteamID {
+                    m_steamid: sys::CSteamID_SteamID_t { m_unAll64Bits: 0 }
+                };
+                count.max(0) as usize
+            ];
+            sys::SteamAPI_ISteamFriends_GetFriendsGroupMembersList(
+                self.friends,
+                id.0,
+                members.as_mut_ptr(),
+                members.len() as _,
+            );
+            FriendsGroup {
+                id,
+                name,
+                members: members
+                    .iter()
+                    .map(|id| SteamId(id.m_steamid.m_unAll64Bits))
+                    .collect(),
+            }
+        }
+    }
+
+    /// Returns the Steam groups (clans) the current user is a member of
+    pub fn get_clans(&self) -> Vec<Clan> {
+        unsafe {
+            let count = sys::SteamAPI_ISteamFriends_GetClanCount(self.friends);
+            if count == -1 {
+                return Vec::new();
+            }
+            let mut clans = Vec::with_capacity(count as usize);
+            for idx in 0..count {
+                let clan = SteamId(sys::SteamAPI_ISteamFriends_GetClanByIndex(
+                    self.friends,
+                    idx,
+                ));
+                clans.push(self.get_clan(clan));
+            }
+            clans
+        }
+    }
+
+    pub fn get_clan(&self, clan: SteamId) -> Clan {
+        Clan {
+            id: clan,
+            friends: self.friends,
+            inner: self.inner.clone(),
+        }
+    }
+
+    /// Refreshes the activity counts of the given Steam groups.
+    ///
+    /// Once the callback reports success the counts can be read
+    /// with [`Clan::activity_counts`].
+    pub fn download_clan_activity_counts<F>(&self, clans: &[SteamId], cb: F)
+    where
+        F: FnOnce(SResult<()>) + 'static + Send,
+    {
+        unsafe {
+            let mut clans: Vec<sys::CSteamID> = clans
+                .iter()
+                .map(|clan| sys::CSteamID {
+                    m_steamid: sys::CSteamID_SteamID_t {
+                        m_unAll64Bits: clan.0,
+                    },
+                })
+                .collect();
+            let api_call = sys::SteamAPI_ISteamFriends_DownloadClanActivityCounts(
+                self.friends,
+                clans.as_mut_ptr(),
+                clans.len() as _,
+            );
+            register_call_result::<sys::DownloadClanActivityCountsResult_t, _>(
+                &self.inner,
+                api_call,
+                move |v, io_error| {
+                    cb(if io_error {
+                        Err(SteamError::IOFailure)
+                    } else if !v.m_bSuccess {
+                        Err(SteamError::Generic)
+                    } else {
+                        Ok(())
+                    })
+                },
+            );
+        }
+    }
+
+    /// Reads a message from a Steam group chat room after receiving
+    /// a [`GameConnectedClanChatMsg`] callback.
+    pub fn get_clan_chat_message(&self, chat: SteamId, message_id: i32) -> Option<ChatMessage> {
+        unsafe {
+            let mut buffer = vec![0u8; 2048];
+            let mut chat_type = sys::EChatEntryType::k_EChatEntryTypeInvalid;
+            let mut sender = sys::CSteamID {
+                m_steamid: sys::CSteamID_SteamID_t { m_unAll64Bits: 0 },
+            };
+            let len = sys::SteamAPI_ISteamFriends_GetClanChatMessage(
+                self.friends,
+                chat.0,
+                message_id,
+                buffer.as_mut_ptr().cast(),
+                buffer.len() as _,
+                &mut chat_type,
+                &mut sender,
+            );
+            if len <= 0 {
+                return None;
+            }
+            buffer.truncate(len as usize);
+            Some(ChatMessage {
+                sender: SteamId(sender.m_steamid.m_unAll64Bits),
+                entry_type: (chat_type as u8).into(),
+                text: chat_message_text(&buffer),
+            })
+        }
+    }
+
+    /// Sets whether the game handles in-game chat messages from friends.
+    ///
+    /// While enabled, messages friends send to the current user are
+    /// delivered to the game as [`GameConnectedFriendChatMsg`] callbacks
+    /// instead of being shown in the Steam overlay. The game is then
+    /// responsible for displaying them and replying with
+    /// [`Friend::reply_to_friend_message`].
+    pub fn set_listen_for_friends_messages(&self, intercept: bool) -> bool {
+        unsafe { sys::SteamAPI_ISteamFriends_SetListenForFriendsMessages(self.friends, intercept) }
+    }
+
+    /// Reads a chat message from a friend after receiving a
+    /// [`GameConnectedFriendChatMsg`] callback.
+    ///
+    /// Requires [`set_listen_for_friends_messages`](Self::set_listen_for_friends_messages)
+    /// to be enabled.
+    pub fn get_friend_message(&self, friend: SteamId, message_id: i32) -> Option<ChatMessage> {
+        unsafe { friend_message(self.friends, friend, message_id) }
+    }
+
+    /// Reads the chat message announced by a [`GameConnectedFriendChatMsg`]
+    /// callback.
+    ///
+    /// Requires [`set_listen_for_friends_messages`](Self::set_listen_for_friends_messages)
+    /// to be enabled.
+    ///
+    /// ```no_run
+    /// # use steamworks::*;
+    /// # let client = Client::init().unwrap();
+    /// let friends = client.friends();
+    /// friends.set_listen_for_friends_messages(true);
+    /// client.process_callbacks(|callback| {
+    ///     if let CallbackResult::GameConnectedFriendChatMsg(msg) = callback {
+    ///         if let Some(message) = friends.read_friend_message(&msg) {
+    ///             println!("{:?}", message);
+    ///         }
+    ///     }
+    /// });
+    /// ```
+    pub fn read_friend_message(&self, msg: &GameConnectedFriendChatMsg) -> Option<ChatMessage> {
+        self.get_friend_message(msg.user, msg.message_id)
+    }
+}
+
+unsafe fn friend_message(
+    friends: *mut sys::ISteamFriends,
+    friend: SteamId,
+    message_id: i32,
+) -> Option<ChatMessage> {
+    let mut buffer = vec![0u8; 2048];
+    let mut chat_type = sys::EChatEntryType::k_EChatEntryTypeInvalid;
+    let len = sys::SteamAPI_ISteamFriends_GetFriendMessage(
+        friends,
+        friend.0,
+        message_id,
+        buffer.as_mut_ptr().cast(),
+        buffer.len() as _,
+        &mut chat_type,
+    );
+    if len <= 0 {
+        return None;
+    }
+    buffer.truncate(len as usize);
+    Some(ChatMessage {
+        sender: friend,
+        entry_type: (chat_type as u8).into(),
+        text: chat_message_text(&buffer),
+    })
+}
+
+fn chat_message_text(buffer: &[u8]) -> String {
+    CStr::from_bytes_until_nul(buffer)
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| String::from_utf8_lossy(buffer).into_owned())
+}
+
+unsafe fn avatar_handle(friends: *mut sys::ISteamFriends, user: SteamId, size: AvatarSize) -> i32 {
+    match size {
+        AvatarSize::Small => sys::SteamAPI_ISteamFriends_GetSmallFriendAvatar(friends, user.0),
+        AvatarSize::Medium => sys::SteamAPI_ISteamFriends_GetMediumFriendAvatar(friends, user.0),
+        AvatarSize::Large => sys::SteamAPI_ISteamFriends_GetLargeFriendAvatar(friends, user.0),
+    }
 }
 
 /// Information about a friend's current state in a game
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct FriendGame {
     /// The id of the game that the friend is
@@ -252,15 +611,61 @@ impl_callback!(cb: PersonaStateChange_t => PersonaStateChange {
     }
 });
 
+/// A large avatar that was previously requested has been downloaded
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AvatarImageLoaded {
+    /// The user whose avatar was loaded
+    pub user: SteamId,
+    /// The image handle of the avatar
+    pub image: i32,
+    /// The width of the avatar in pixels
+    pub width: u32,
+    /// The height of the avatar in pixels
+    pub height: u32,
+}
+
+impl_callback!(cb: AvatarImageLoaded_t => AvatarImageLoaded {
+    Self {
+        user: SteamId(cb.m_steamID.m_steamid.m_unAll64Bits),
+        image: cb.m_iImage,
+        width: cb.m_iWide as u32,
+        height: cb.m_iTall as u32,
+    }
+});
+
+/// Rich Presence data of a friend has been updated
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FriendRichPresenceUpdate {
+    /// The friend whose Rich Presence was updated
+    pub friend: SteamId,
+    /// The app the Rich Presence belongs to
+    pub app_id: AppId,
+}
+
+impl_callback!(cb: FriendRichPresenceUpdate_t => FriendRichPresenceUpdate {
+    Self {
+        friend: SteamId(cb.m_steamIDFriend.m_steamid.m_unAll64Bits),
+        app_id: AppId(cb.m_nAppID),
+    }
+});
+
 #[derive(Clone, Debug)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct GameOverlayActivated {
     pub active: bool,
+    /// Whether the user opened the overlay, rather than the game
+    pub user_initiated: bool,
+    /// The app the overlay was activated for
+    pub app_id: AppId,
 }
 
 impl_callback!(cb: GameOverlayActivated_t => GameOverlayActivated {
     Self {
         active: cb.m_bActive == 1,
+        user_initiated: cb.m_bUserInitiated,
+        app_id: AppId(cb.m_nAppID),
     }
 });
 
@@ -349,6 +754,147 @@ impl Friend {
         }
     }
 
+    /// Opens one of the overlay dialogs for this user
+    pub fn activate_game_overlay_dialog(&self, dialog: OverlayUserDialog) {
+        let dialog = CString::new(dialog.as_str()).unwrap();
+        unsafe {
+            sys::SteamAPI_ISteamFriends_ActivateGameOverlayToUser(
+                self.friends,
+                dialog.as_ptr(),
+                self.id.0,
+            );
+        }
+    }
+
+    /// Requests the items the user has equipped on their community profile.
+    ///
+    /// The profile item properties of the user can be read once this
+    /// completes.
+    pub fn request_equipped_profile_items<F>(&self, cb: F)
+    where
+        F: FnOnce(SResult<EquippedProfileItems>) + 'static + Send,
+    {
+        unsafe {
+            let api_call =
+                sys::SteamAPI_ISteamFriends_RequestEquippedProfileItems(self.friends, self.id.0);
+            register_call_result::<sys::EquippedProfileItems_t, _>(
+                &self._inner,
+                api_call,
+                move |v, io_error| {
+                    cb(if io_error {
+                        Err(SteamError::IOFailure)
+                    } else {
+                        crate::to_steam_result(v.m_eResult)
+                            .map(|_| EquippedProfileItems::from_sys(v))
+                    })
+                },
+            );
+        }
+    }
+
+    /// Returns whether the user has an item of the given type equipped.
+    ///
+    /// Requires [`request_equipped_profile_items`](Self::request_equipped_profile_items)
+    /// to have completed.
+    pub fn has_equipped_profile_item(&self, item: CommunityProfileItemType) -> bool {
+        unsafe {
+            sys::SteamAPI_ISteamFriends_BHasEquippedProfileItem(
+                self.friends,
+                self.id.0,
+                item.into(),
+            )
+        }
+    }
+
+    /// Returns a string property of an equipped profile item, or `None` if
+    /// the item is not equipped or the property is empty.
+    pub fn profile_item_property_string(
+        &self,
+        item: CommunityProfileItemType,
+        prop: ProfileItemStringProperty,
+    ) -> Option<String> {
+        unsafe {
+            let value = sys::SteamAPI_ISteamFriends_GetProfileItemPropertyString(
+                self.friends,
+                self.id.0,
+                item.into(),
+                prop.into(),
+            );
+            if value.is_null() {
+                return None;
+            }
+            let value = CStr::from_ptr(value).to_string_lossy().into_owned();
+            (!value.is_empty()).then_some(value)
+        }
+    }
+
+    /// Returns a numeric property of an equipped profile item
+    pub fn profile_item_property_uint(
+        &self,
+        item: CommunityProfileItemType,
+        prop: ProfileItemUintProperty,
+    ) -> u32 {
+        unsafe {
+            sys::SteamAPI_ISteamFriends_GetProfileItemPropertyUint(
+                self.friends,
+                self.id.0,
+                item.into(),
+                prop.into(),
+            )
+        }
+    }
+
+    /// Returns all properties of an equipped profile item, or `None` if no
+    /// item of this type is equipped.
+    pub fn profile_item(&self, item: CommunityProfileItemType) -> Option<ProfileItem> {
+        if !self.has_equipped_profile_item(item) {
+            return None;
+        }
+        let string = |prop| self.profile_item_property_string(item, prop);
+        let uint = |prop| self.profile_item_property_uint(item, prop);
+        Some(ProfileItem {
+            title: string(ProfileItemStringProperty::Title),
+            description: string(ProfileItemStringProperty::Description),
+            internal_name: string(ProfileItemStringProperty::InternalName),
+            image_small: string(ProfileItemStringProperty::ImageSmall),
+            image_large: string(ProfileItemStringProperty::ImageLarge),
+            movie_webm: string(ProfileItemStringProperty::MovieWebM),
+            movie_mp4: string(ProfileItemStringProperty::MovieMp4),
+            movie_webm_small: string(ProfileItemStringProperty::MovieWebMSmall),
+            movie_mp4_small: string(ProfileItemStringProperty::MovieMp4Small),
+            app_id: AppId(uint(ProfileItemUintProperty::AppId)),
+            type_id: uint(ProfileItemUintProperty::TypeId),
+            class: uint(ProfileItemUintProperty::Class),
+        })
+    }
+
+    /// Returns the relationship between the current user and this user
+    pub fn relationship(&self) -> FriendRelationship {
+        unsafe {
+            let relationship =
+                sys::SteamAPI_ISteamFriends_GetFriendRelationship(self.friends, self.id.0);
+            match relationship {
+                sys::EFriendRelationship::k_EFriendRelationshipBlocked => {
+                    FriendRelationship::Blocked
+                }
+                sys::EFriendRelationship::k_EFriendRelationshipRequestRecipient => {
+                    FriendRelationship::RequestRecipient
+                }
+                sys::EFriendRelationship::k_EFriendRelationshipFriend => FriendRelationship::Friend,
+                sys::EFriendRelationship::k_EFriendRelationshipRequestInitiator => {
+                    FriendRelationship::RequestInitiator
+                }
+                sys::EFriendRelationship::k_EFriendRelationshipIgnored => {
+                    FriendRelationship::Ignored
+                }
+                sys::EFriendRelationship::k_EFriendRelationshipIgnoredFriend => {
+                    FriendRelationship::IgnoredFriend
+                }
+                _ => FriendRelationship::None,
+            }
+        }
+    }
+
     pub fn state(&self) -> FriendState {
         unsafe {
             let state = sys::SteamAPI_ISteamFriends_GetFriendPersonaState(self.friends, self.id.0);
@@ -398,65 +944,28 @@ impl Friend {
 
     /// Returns a small (32x32) avatar for the user in RGBA format
     pub fn small_avatar(&self) -> Option<Vec<u8>> {
-        unsafe {
-            let utils = sys::SteamAPI_SteamUtils_v010();
-            let img = sys::SteamAPI_ISteamFriends_GetSmallFriendAvatar(self.friends, self.id.0);
-            if img == 0 {
-                return None;
-            }
-            let mut width = 0;
-            let mut height = 0;
-            if !sys::SteamAPI_ISteamUtils_GetImageSize(utils, img, &mut width, &mut height) {
-                return None;
-            }
-            let mut dest = vec![0; 32 * 32 * 4];
-            if !sys::SteamAPI_ISteamUtils_GetImageRGBA(utils, img, dest.as_mut_ptr(), 32 * 32 * 4) {
-                return None;
-            }
-            Some(dest)
-        }
+        self.avatar(AvatarSize::Small).map(SteamImage::into_rgba)
     }
 
     /// Returns a medium (64x64) avatar for the user in RGBA format
     pub fn medium_avatar(&self) -> Option<Vec<u8>> {
-        unsafe {
-            let utils = sys::SteamAPI_SteamUtils_v010();
-            let img = sys::SteamAPI_ISteamFriends_GetMediumFriendAvatar(self.friends, self.id.0);
-            if img == 0 {
-                return None;
-            }
-            let mut width = 0;
-            let mut height = 0;
-            if !sys::SteamAPI_ISteamUtils_GetImageSize(utils, img, &mut width, &mut height) {
-                return None;
-            }
-            let mut dest = vec![0; 64 * 64 * 4];
-            if !sys::SteamAPI_ISteamUtils_GetImageRGBA(utils, img, dest.as_mut_ptr(), 64 * 64 * 4) {
-                return None;
-            }
-            Some(dest)
-        }
+        self.avatar(AvatarSize::Medium).map(SteamImage::into_rgba)
     }
 
     /// Returns a large (184x184) avatar for the user in RGBA format
     pub fn large_avatar(&self) -> Option<Vec<u8>> {
+        self.avatar(AvatarSize::Large).map(SteamImage::into_rgba)
+    }
+
+    /// Returns the avatar of the user in the given size.
+    ///
+    /// Returns `None` if the user has no avatar or if it has not been
+    /// downloaded yet. Use an [`AvatarLoader`] to get notified once the
+    /// avatar is available.
+    pub fn avatar(&self, size: AvatarSize) -> Option<SteamImage> {
         unsafe {
-            let utils = sys::SteamAPI_SteamUtils_v010();
-            let img = sys::SteamAPI_ISteamFriends_GetLargeFriendAvatar(self.friends, self.id.0);
-            if img == 0 {
-                return None;
-            }
-            let mut width = 0;
-            let mut height = 0;
-            if !sys::SteamAPI_ISteamUtils_GetImageSize(utils, img, &mut width, &mut height) {
-                return None;
-            }
-            let mut dest = vec![0; 184 * 184 * 4];
-            if !sys::SteamAPI_ISteamUtils_GetImageRGBA(utils, img, dest.as_mut_ptr(), 184 * 184 * 4)
-            {
-                return None;
-            }
-            Some(dest)
+            let handle = avatar_handle(self.friends, self.id, size);
+            SteamImage::from_handle(sys::SteamAPI_SteamUtils_v010(), handle)
         }
     }
 
@@ -487,6 +996,35 @@ impl Friend {
         }
     }
 
+    /// Gets the Steam level of the friend.
+    ///
+    /// Returns 0 if the level is not known yet. Use
+    /// [`Friends::request_user_information`] to request it, a
+    /// [`PersonaStateChange`] callback with [`PersonaChange::STEAM_LEVEL`]
+    /// is posted once it is available.
+    pub fn steam_level(&self) -> u32 {
+        unsafe { sys::SteamAPI_ISteamFriends_GetFriendSteamLevel(self.friends, self.id.0) as u32 }
+    }
+
+    /// Sends a chat message to the friend.
+    ///
+    /// Used to reply to messages received while
+    /// [`Friends::set_listen_for_friends_messages`] is enabled.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `message` contains a null byte.
+    pub fn reply_to_friend_message(&self, message: &str) -> bool {
+        let message = CString::new(message).unwrap();
+        unsafe {
+            sys::SteamAPI_ISteamFriends_ReplyToFriendMessage(
+                self.friends,
+                self.id.0,
+                message.as_ptr(),
+            )
+        }
+    }
+
     /// Get a Rich Presence value from a specified friend.
     pub fn rich_presence(&self, key: &str) -> Option<String> {
         let key = CString::new(key).unwrap();
@@ -502,6 +1040,413 @@ impl Friend {
         }
         Some(cstr.to_string_lossy().into_owned())
     }
+
+    /// Get all Rich Presence key/values of the friend for the current game.
+    ///
+    /// Rich Presence is only available for friends playing the same game
+    /// unless requested with [`request_rich_presence`](Self::request_rich_presence).
+    pub fn all_rich_presence(&self) -> HashMap<String, String> {
+        unsafe {
+            let count =
+                sys::SteamAPI_ISteamFriends_GetFriendRichPresenceKeyCount(self.friends, self.id.0);
+            let mut values = HashMap::with_capacity(count.max(0) as usize);
+            for idx in 0..count {
+                let key = sys::SteamAPI_ISteamFriends_GetFriendRichPresenceKeyByIndex(
+                    self.friends,
+                    self.id.0,
+                    idx,
+                );
+                if key.is_null() {
+                    continue;
+                }
+                let key = CStr::from_ptr(key).to_string_lossy().into_owned();
+                if let Some(value) = self.rich_presence(&key) {
+                    values.insert(key, value);
+                }
+            }
+            values
+        }
+    }
+
+    /// Requests the Rich Presence data of the friend for the current game.
+    ///
+    /// A [`FriendRichPresenceUpdate`] callback is posted once the data
+    /// has been received.
+    pub fn request_rich_presence(&self) {
+        unsafe {
+            sys::SteamAPI_ISteamFriends_RequestFriendRichPresence(self.friends, self.id.0);
+        }
+    }
+}
+
+/// A Steam group (clan)
+pub struct Clan {
+    id: SteamId,
+    friends: *mut sys::ISteamFriends,
+    inner: Arc<Inner>,
+}
+
+impl Debug for Clan {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Clan({:?})", self.id)
+    }
+}
+
+/// Activity counts of a Steam group
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ClanActivityCounts {
+    /// Number of members that are online
+    pub online: i32,
+    /// Number of members that are in game
+    pub in_game: i32,
+    /// Number of members that are chatting in the group chat room
+    pub chatting: i32,
+}
+
+impl Clan {
+    pub fn id(&self) -> SteamId {
+        self.id
+    }
+
+    /// Returns the display name of the group.
+    ///
+    /// The name may be empty if the current user is not a member of
+    /// the group and its information has not been requested yet.
+    pub fn name(&self) -> String {
+        unsafe {
+            let name = sys::SteamAPI_ISteamFriends_GetClanName(self.friends, self.id.0);
+            let name = CStr::from_ptr(name);
+            name.to_string_lossy().into_owned()
+        }
+    }
+
+    /// Returns the unique tag (abbreviation) of the group
+    pub fn tag(&self) -> String {
+        unsafe {
+            let tag = sys::SteamAPI_ISteamFriends_GetClanTag(self.friends, self.id.0);
+            let tag = CStr::from_ptr(tag);
+            tag.to_string_lossy().into_owned()
+        }
+    }
+
+    /// Returns the most recently downloaded activity counts of the group.
+    ///
+    /// Use [`Friends::download_clan_activity_counts`] to refresh them.
+    pub fn activity_counts(&self) -> Option<ClanActivityCounts> {
+        unsafe {
+            let mut online = 0;
+            let mut in_game = 0;
+            let mut chatting = 0;
+            sys::SteamAPI_ISteamFriends_GetClanActivityCounts(
+                self.friends,
+                self.id.0,
+                &mut online,
+                &mut in_game,
+                &mut chatting,
+            )
+            .then_some(ClanActivityCounts {
+                online,
+                in_game,
+                chatting,
+            })
+        }
+    }
+
+    /// Returns whether the group is public or private
+    pub fn is_public(&self) -> bool {
+        unsafe { sys::SteamAPI_ISteamFriends_IsClanPublic(self.friends, self.id.0) }
+    }
+
+    /// Returns whether the group is an official game group or community group
+    pub fn is_official_game_group(&self) -> bool {
+        unsafe { sys::SteamAPI_ISteamFriends_IsClanOfficialGameGroup(self.friends, self.id.0) }
+    }
+
+    /// Requests the list of officers of the group.
+    ///
+    /// The callback receives the officers on success. After that
+    /// [`owner`](Self::owner) and [`officers`](Self::officers) can be used
+    /// as well. The request can only be made for groups the current user
+    /// is a member of.
+    pub fn request_officer_list<F>(&self, cb: F)
+    where
+        F: FnOnce(SResult<Vec<SteamId>>) + 'static + Send,
+    {
+        unsafe {
+            let api_call =
+                sys::SteamAPI_ISteamFriends_RequestClanOfficerList(self.friends, self.id.0);
+            let friends = self.friends as isize;
+            let clan = self.id;
+            register_call_result::<sys::ClanOfficerListResponse_t, _>(
+                &self.inner,
+                api_call,
+                move |v, io_error| {
+                    cb(if io_error {
+                        Err(SteamError::IOFailure)
+                    } else if v.m_bSuccess == 0 {
+                        Err(SteamError::Generic)
+                    } else {
+                        Ok(clan_officers(friends as *mut _, clan))
+                    })
+                },
+            );
+        }
+    }
+
+    /// Returns the owner of the group.
+    ///
+    /// Requires [`request_officer_list`](Self::request_officer_list) to
+    /// have completed.
+    pub fn owner(&self) -> SteamId {
+        unsafe {
+            SteamId(sys::SteamAPI_ISteamFriends_GetClanOwner(
+                self.friends,
+                self.id.0,
+            ))
+        }
+    }
+
+    /// Returns the officers of the group, including the owner.
+    ///
+    /// Requires [`request_officer_list`](Self::request_officer_list) to
+    /// have completed.
+    pub fn officers(&self) -> Vec<SteamId> {
+        unsafe { clan_officers(self.friends, self.id) }
+    }
+
+    /// Joins the chat room of the group.
+    ///
+    /// The callback receives the id of the chat room on success. While in
+    /// the room [`GameConnectedClanChatMsg`], [`GameConnectedChatJoin`] and
+    /// [`GameConnectedChatLeave`] callbacks are posted.
+    pub fn join_chat_room<F>(&self, cb: F)
+    where
+        F: FnOnce(Result<SteamId, ChatRoomEnterResponse>) + 'static + Send,
+    {
+        unsafe {
+            let api_call = sys::SteamAPI_ISteamFriends_JoinClanChatRoom(self.friends, self.id.0);
+            register_call_result::<sys::JoinClanChatRoomCompletionResult_t, _>(
+                &self.inner,
+                api_call,
+                move |v, io_error| {
+                    cb(if io_error {
+                        Err(ChatRoomEnterResponse::Error)
+                    } else {
+                        match ChatRoomEnterResponse::from(v.m_eChatRoomEnterResponse as u32) {
+                            ChatRoomEnterResponse::Success => {
+                                Ok(SteamId(v.m_steamIDClanChat.m_steamid.m_unAll64Bits))
+                            }
+                            err => Err(err),
+                        }
+                    })
+                },
+            );
+        }
+    }
+
+    /// Leaves the chat room of the group.
+    pub fn leave_chat_room(&self) -> bool {
+        unsafe { sys::SteamAPI_ISteamFriends_LeaveClanChatRoom(self.friends, self.id.0) }
+    }
+
+    /// Returns the users in the chat room of the group.
+    ///
+    /// Only available while the current user is in the chat room.
+    pub fn chat_members(&self) -> Vec<SteamId> {
+        unsafe {
+            let count = sys::SteamAPI_ISteamFriends_GetClanChatMemberCount(self.friends, self.id.0);
+            let mut members = Vec::with_capacity(count.max(0) as usize);
+            for idx in 0..count {
+                members.push(SteamId(sys::SteamAPI_ISteamFriends_GetChatMemberByIndex(
+                    self.friends,
+                    self.id.0,
+                    idx,
+                )));
+            }
+            members
+        }
+    }
+
+    /// Sends a message to the group chat room `chat` returned by
+    /// [`join_chat_room`](Self::join_chat_room).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `text` contains a null byte.
+    pub fn send_chat_message(&self, chat: SteamId, text: &str) -> bool {
+        let text = CString::new(text).unwrap();
+        unsafe {
+            sys::SteamAPI_ISteamFriends_SendClanChatMessage(self.friends, chat.0, text.as_ptr())
+        }
+    }
+
+    /// Returns whether `user` is an admin of the group chat room `chat`
+    pub fn is_chat_admin(&self, chat: SteamId, user: SteamId) -> bool {
+        unsafe { sys::SteamAPI_ISteamFriends_IsClanChatAdmin(self.friends, chat.0, user.0) }
+    }
+}
+
+unsafe fn clan_officers(friends: *mut sys::ISteamFriends, clan: SteamId) -> Vec<SteamId> {
+    let count = sys::SteamAPI_ISteamFriends_GetClanOfficerCount(friends, clan.0);
+    let mut officers = Vec::with_capacity(count.max(0) as usize);
+    for idx in 0..count {
+        officers.push(SteamId(sys::SteamAPI_ISteamFriends_GetClanOfficerByIndex(
+            friends, clan.0, idx,
+        )));
+    }
+    officers
+}
+
+/// A chat message received from a Steam group or friend chat
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ChatMessage {
+    /// The user that sent the message. For friend chats this is the
+    /// friend the conversation is with.
+    pub sender: SteamId,
+    /// The type of the message
+    pub entry_type: ChatEntryType,
+    /// The text of the message
+    pub text: String,
+}
+
+/// A message was posted in a Steam group chat room the current user is in.
+///
+/// Use [`Friends::get_clan_chat_message`] to read the message.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GameConnectedClanChatMsg {
+    /// The chat room the message was posted in
+    pub chat: SteamId,
+    /// The user that posted the message
+    pub user: SteamId,
+    /// The index of the message to pass to [`Friends::get_clan_chat_message`]
+    pub message_id: i32,
+}
+
+impl_callback!(cb: GameConnectedClanChatMsg_t => GameConnectedClanChatMsg {
+    Self {
+        chat: SteamId(cb.m_steamIDClanChat.m_steamid.m_unAll64Bits),
+        user: SteamId(cb.m_steamIDUser.m_steamid.m_unAll64Bits),
+        message_id: cb.m_iMessageID,
+    }
+});
+
+/// A friend sent a chat message to the current user.
+///
+/// Only posted while [`Friends::set_listen_for_friends_messages`] is
+/// enabled. Use [`Friends::get_friend_message`] to read the message.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GameConnectedFriendChatMsg {
+    /// The friend the conversation is with
+    pub user: SteamId,
+    /// The index of the message to pass to [`Friends::get_friend_message`]
+    pub message_id: i32,
+}
+
+impl_callback!(cb: GameConnectedFriendChatMsg_t => GameConnectedFriendChatMsg {
+    Self {
+        user: SteamId(cb.m_steamIDUser.m_steamid.m_unAll64Bits),
+        message_id: cb.m_iMessageID,
+    }
+});
+
+/// A user joined a Steam group chat room the current user is in
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GameConnectedChatJoin {
+    /// The chat room that was joined
+    pub chat: SteamId,
+    /// The user that joined the chat room
+    pub user: SteamId,
+}
+
+impl_callback!(cb: GameConnectedChatJoin_t => GameConnectedChatJoin {
+    Self {
+        chat: SteamId(cb.m_steamIDClanChat.m_steamid.m_unAll64Bits),
+        user: SteamId(cb.m_steamIDUser.m_steamid.m_unAll64Bits),
+    }
+});
+
+/// A user left a Steam group chat room the current user is in
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GameConnectedChatLeave {
+    /// The chat room that was left
+    pub chat: SteamId,
+    /// The user that left the chat room
+    pub user: SteamId,
+    /// Whether the user was kicked by an officer
+    pub kicked: bool,
+    /// Whether the user's connection was dropped
+    pub dropped: bool,
+}
+
+impl_callback!(cb: GameConnectedChatLeave_t => GameConnectedChatLeave {
+    Self {
+        chat: SteamId(cb.m_steamIDClanChat.m_steamid.m_unAll64Bits),
+        user: SteamId(cb.m_steamIDUser.m_steamid.m_unAll64Bits),
+        kicked: cb.m_bKicked,
+        dropped: cb.m_bDropped,
+    }
+});
+
+/// The relationship between the current user and another user
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum FriendRelationship {
+    /// The users have no relationship
+    None,
+    /// The user has been blocked
+    Blocked,
+    /// The user has sent a friend request to the current user
+    RequestRecipient,
+    /// The users are friends
+    Friend,
+    /// The current user has sent a friend request to the user
+    RequestInitiator,
+    /// The user has been ignored
+    Ignored,
+    /// The user is a friend that has been ignored
+    IgnoredFriend,
+}
+
+/// The id of a friends group (tag) in the Steam friends list
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FriendsGroupId(pub(crate) i16);
+
+impl FriendsGroupId {
+    /// Creates a `FriendsGroupId` from a raw 16 bit value.
+    pub fn from_raw(id: i16) -> FriendsGroupId {
+        FriendsGroupId(id)
+    }
+
+    /// Returns the raw 16 bit value of the friends group id
+    pub fn raw(&self) -> i16 {
+        self.0
+    }
+}
+
+/// A friends group (tag) created by the current user
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FriendsGroup {
+    pub id: FriendsGroupId,
+    pub name: String,
+    pub members: Vec<SteamId>,
+}
+
+/// A page of the users the current user is following
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FollowingList {
+    /// The users on this page
+    pub users: Vec<SteamId>,
+    /// The total number of users the current user is following
+    pub total: u32,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -0,0 +1,274 @@
+use super::*;
+use std::collections::VecDeque;
+
+type AvatarCallback = Box<dyn FnOnce(Option<SteamImage>) + Send>;
+type AvatarKey = (SteamId, AvatarSize);
+
+/// A least recently used cache of avatars
+struct AvatarCache {
+    capacity: usize,
+    images: HashMap<AvatarKey, SteamImage>,
+    order: VecDeque<AvatarKey>,
+}
+
+impl AvatarCache {
+    fn new(capacity: usize) -> AvatarCache {
+        AvatarCache {
+            capacity,
+            images: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    fn touch(&mut self, key: AvatarKey) {
+        if let Some(pos) = self.order.iter().position(|k| *k == key) {
+            self.order.remove(pos);
+        }
+        self.order.push_back(key);
+    }
+
+    fn get(&mut self, key: AvatarKey) -> Option<SteamImage> {
+        let image = self.images.get(&key)?.clone();
+        self.touch(key);
+        Some(image)
+    }
+
+    fn insert(&mut self, key: AvatarKey, image: SteamImage) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.images.insert(key, image);
+        self.touch(key);
+        while self.order.len() > self.capacity {
+            if let Some(old) = self.order.pop_front() {
+                self.images.remove(&old);
+            }
+        }
+    }
+
+    fn remove_user(&mut self, user: SteamId) {
+        self.images.retain(|k, _| k.0 != user);
+        self.order.retain(|k| k.0 != user);
+    }
+}
+
+struct LoaderState {
+    cache: Option<AvatarCache>,
+    pending: HashMap<AvatarKey, Vec<AvatarCallback>>,
+    loaded: Vec<AvatarKey>,
+}
+
+impl LoaderState {
+    /// Tries to load all pending avatars of `user`, returning the callbacks
+    /// that can be completed
+    fn resolve(
+        &mut self,
+        friends: *mut sys::ISteamFriends,
+        user: SteamId,
+    ) -> Vec<(AvatarCallback, Option<SteamImage>)> {
+        let keys: Vec<_> = self
+            .pending
+            .keys()
+            .filter(|k| k.0 == user)
+            .copied()
+            .collect();
+        let mut ready = Vec::new();
+        for key in keys {
+            let handle = unsafe { avatar_handle(friends, key.0, key.1) };
+            if handle == -1 {
+                continue;
+            }
+            let image = unsafe { SteamImage::from_handle(sys::SteamAPI_SteamUtils_v010(), handle) };
+            if let (Some(cache), Some(image)) = (self.cache.as_mut(), image.as_ref()) {
+                cache.insert(key, image.clone());
+            }
+            self.loaded.push(key);
+            for cb in self.pending.remove(&key).unwrap_or_default() {
+                ready.push((cb, image.clone()));
+            }
+        }
+        ready
+    }
+}
+
+/// Loads user avatars, calling back once they have been downloaded.
+///
+/// Steam only hands out avatars it has already downloaded; other avatars
+/// are requested and become available after an [`AvatarImageLoaded`]
+/// callback, or a [`PersonaStateChange`] for small and medium avatars of
+/// users Steam knew nothing about. The loader keeps track of these pending
+/// requests and can optionally cache loaded avatars.
+///
+/// The loader is a [`CallbackStateMachine`] that completes the requests
+/// when both callbacks are forwarded to it.
+/// [`poll`](CallbackStateMachine::poll) returns the users and sizes whose
+/// requests completed, e.g. to redraw them.
+pub struct AvatarLoader {
+    friends: *mut sys::ISteamFriends,
+    state: LoaderState,
+}
+
+impl AvatarLoader {
+    pub(crate) fn new(friends: &Friends) -> AvatarLoader {
+        AvatarLoader {
+            friends: friends.friends,
+            state: LoaderState {
+                cache: None,
+                pending: HashMap::new(),
+                loaded: Vec::new(),
+            },
+        }
+    }
+
+    /// Keeps up to `capacity` loaded avatars in a least recently used cache
+    pub fn with_cache(mut self, capacity: usize) -> Self {
+        self.state.cache = Some(AvatarCache::new(capacity));
+        self
+    }
+
+    /// Returns the avatar of the user if it is cached or has already been
+    /// downloaded by Steam.
+    pub fn get(&mut self, user: SteamId, size: AvatarSize) -> Option<SteamImage> {
+        let state = &mut self.state;
+        if let Some(image) = state.cache.as_mut().and_then(|c| c.get((user, size))) {
+            return Some(image);
+        }
+        let image = unsafe {
+            let handle = avatar_handle(self.friends, user, size);
+            SteamImage::from_handle(sys::SteamAPI_SteamUtils_v010(), handle)
+        };
+        if let (Some(cache), Some(image)) = (state.cache.as_mut(), image.as_ref()) {
+            cache.insert((user, size), image.clone());
+        }
+        image
+    }
+
+    /// Loads the avatar of the user, downloading it first if required.
+    ///
+    /// The callback is called with `None` if the user has no avatar or it
+    /// could not be read. If the avatar is already available the callback
+    /// is called immediately.
+    pub fn load<F>(&mut self, user: SteamId, size: AvatarSize, cb: F)
+    where
+        F: FnOnce(Option<SteamImage>) + Send + 'static,
+    {
+        if let Some(image) = self.get(user, size) {
+            cb(Some(image));
+            return;
+        }
+        let handle = unsafe { avatar_handle(self.friends, user, size) };
+        // A handle of -1 means the large avatar is being downloaded, 0 means
+        // that we either know nothing about the user yet or they have no
+        // avatar. Any other handle is an avatar Steam already has but
+        // failed to read, for which no callback will follow.
+        if handle > 0
+            || (handle == 0
+                && !unsafe {
+                    sys::SteamAPI_ISteamFriends_RequestUserInformation(self.friends, user.0, false)
+                })
+        {
+            cb(None);
+            return;
+        }
+        self.state
+            .pending
+            .entry((user, size))
+            .or_default()
+            .push(Box::new(cb));
+    }
+
+    /// Applies an [`AvatarImageLoaded`], completing the requests for the
+    /// user's avatar
+    pub fn on_avatar_image_loaded(&mut self, loaded: &AvatarImageLoaded) {
+        for (cb, image) in self.state.resolve(self.friends, loaded.user) {
+            cb(image);
+        }
+    }
+
+    /// Applies a [`PersonaStateChange`], completing requests for avatars
+    /// that are not announced through [`AvatarImageLoaded`]. Avatar
+    /// changes also evict the user from the cache.
+    pub fn persona_changed(&mut self, change: &PersonaStateChange) {
+        if !change
+            .flags
+            .intersects(PersonaChange::AVATAR | PersonaChange::NAME_FIRST_SET)
+        {
+            return;
+        }
+        if let Some(cache) = self.state.cache.as_mut() {
+            cache.remove_user(change.steam_id);
+        }
+        for (cb, image) in self.state.resolve(self.friends, change.steam_id) {
+            cb(image);
+        }
+    }
+
+    /// Removes all cached avatars of the user
+    pub fn invalidate(&mut self, user: SteamId) {
+        if let Some(cache) = self.state.cache.as_mut() {
+            cache.remove_user(user);
+        }
+    }
+}
+
+impl CallbackStateMachine for AvatarLoader {
+    type Event = (SteamId, AvatarSize);
+
+    fn handle_callback(&mut self, callback: &CallbackResult) {
+        match callback {
+            CallbackResult::AvatarImageLoaded(loaded) => self.on_avatar_image_loaded(loaded),
+            CallbackResult::PersonaStateChange(change) => self.persona_changed(change),
+            _ => {}
+        }
+    }
+
+    /// Returns the avatars whose requests completed since the last call
+    fn poll(&mut self) -> Vec<(SteamId, AvatarSize)> {
+        std::mem::take(&mut self.state.loaded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(value: u8) -> SteamImage {
+        SteamImage::from_rgba(1, 1, vec![value; 4]).unwrap()
+    }
+
+    #[test]
+    fn cache_evicts_least_recently_used() {
+        let mut cache = AvatarCache::new(2);
+        let a = (SteamId(1), AvatarSize::Small);
+        let b = (SteamId(2), AvatarSize::Small);
+        let c = (SteamId(3), AvatarSize::Small);
+        cache.insert(a, image(1));
+        cache.insert(b, image(2));
+        assert_eq!(cache.get(a), Some(image(1)));
+        cache.insert(c, image(3));
+        assert_eq!(cache.get(b), None);
+        assert_eq!(cache.get(a), Some(image(1)));
+        assert_eq!(cache.get(c), Some(image(3)));
+    }
+
+    #[test]
+    fn cache_keys_by_size_and_removes_users() {
+        let mut cache = AvatarCache::new(4);
+        cache.insert((SteamId(1), AvatarSize::Small), image(1));
+        cache.insert((SteamId(1), AvatarSize::Large), image(2));
+        cache.insert((SteamId(2), AvatarSize::Large), image(3));
+        assert_eq!(cache.get((SteamId(1), AvatarSize::Large)), Some(image(2)));
+        assert_eq!(cache.get((SteamId(1), AvatarSize::Medium)), None);
+        cache.remove_user(SteamId(1));
+        assert_eq!(cache.get((SteamId(1), AvatarSize::Small)), None);
+        assert_eq!(cache.get((SteamId(2), AvatarSize::Large)), Some(image(3)));
+        assert_eq!(cache.order.len(), 1);
+    }
+
+    #[test]
+    fn zero_capacity_cache_stores_nothing() {
+        let mut cache = AvatarCache::new(0);
+        cache.insert((SteamId(1), AvatarSize::Small), image(1));
+        assert_eq!(cache.get((SteamId(1), AvatarSize::Small)), None);
+    }
+}
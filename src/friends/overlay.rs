@@ -0,0 +1,268 @@
+use super::*;
+use std::borrow::Cow;
+use std::time::{Duration, Instant};
+
+/// The dialogs that can be opened with
+/// [`Friends::activate_game_overlay_dialog`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OverlayDialog {
+    Friends,
+    Community,
+    Players,
+    Settings,
+    OfficialGameGroup,
+    Stats,
+    Achievements,
+    /// The chat room of a group chat
+    ChatRoomGroup(u64),
+}
+
+impl OverlayDialog {
+    /// Returns the dialog string as expected by the Steam API
+    pub fn as_str(&self) -> Cow<'static, str> {
+        match self {
+            OverlayDialog::Friends => "friends".into(),
+            OverlayDialog::Community => "community".into(),
+            OverlayDialog::Players => "players".into(),
+            OverlayDialog::Settings => "settings".into(),
+            OverlayDialog::OfficialGameGroup => "officialgamegroup".into(),
+            OverlayDialog::Stats => "stats".into(),
+            OverlayDialog::Achievements => "achievements".into(),
+            OverlayDialog::ChatRoomGroup(id) => format!("chatroomgroup/{}", id).into(),
+        }
+    }
+}
+
+/// The dialogs that can be opened for a user with
+/// [`Friend::activate_game_overlay_dialog`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OverlayUserDialog {
+    /// The Steam community profile of the user
+    SteamId,
+    /// A chat with the user
+    Chat,
+    /// A trade offer with the user
+    JoinTrade,
+    /// The stats of the user
+    Stats,
+    /// The achievements of the user
+    Achievements,
+    /// Prompts to add the user as a friend
+    FriendAdd,
+    /// Prompts to remove the user as a friend
+    FriendRemove,
+    /// Accepts a friend request from the user
+    FriendRequestAccept,
+    /// Ignores a friend request from the user
+    FriendRequestIgnore,
+}
+
+impl OverlayUserDialog {
+    /// Returns the dialog string as expected by the Steam API
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OverlayUserDialog::SteamId => "steamid",
+            OverlayUserDialog::Chat => "chat",
+            OverlayUserDialog::JoinTrade => "jointrade",
+            OverlayUserDialog::Stats => "stats",
+            OverlayUserDialog::Achievements => "achievements",
+            OverlayUserDialog::FriendAdd => "friendadd",
+            OverlayUserDialog::FriendRemove => "friendremove",
+            OverlayUserDialog::FriendRequestAccept => "friendrequestaccept",
+            OverlayUserDialog::FriendRequestIgnore => "friendrequestignore",
+        }
+    }
+}
+
+/// How a web page opened in the overlay is displayed
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OverlayWebPageMode {
+    /// The page is opened in the overlay browser next to any other pages
+    /// the user has open
+    Default,
+    /// The page is opened in a new overlay window that hides all other
+    /// windows. The overlay closes when the window is closed.
+    Modal,
+}
+
+impl From<OverlayWebPageMode> for sys::EActivateGameOverlayToWebPageMode {
+    fn from(mode: OverlayWebPageMode) -> Self {
+        match mode {
+            OverlayWebPageMode::Default => {
+                sys::EActivateGameOverlayToWebPageMode::k_EActivateGameOverlayToWebPageMode_Default
+            }
+            OverlayWebPageMode::Modal => {
+                sys::EActivateGameOverlayToWebPageMode::k_EActivateGameOverlayToWebPageMode_Modal
+            }
+        }
+    }
+}
+
+/// A change of the overlay reported by [`OverlayState`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverlayEvent {
+    /// The overlay was opened, by the user or by the game through the API
+    Opened { user_initiated: bool },
+    /// The overlay was closed after being open for `duration`
+    Closed { duration: Duration },
+}
+
+#[derive(Debug, Default)]
+struct OverlayStatus {
+    opened_at: Option<Instant>,
+    user_initiated: bool,
+}
+
+impl OverlayStatus {
+    fn apply(&mut self, event: &GameOverlayActivated, now: Instant) -> Option<OverlayEvent> {
+        if event.active {
+            self.user_initiated = event.user_initiated;
+            if self.opened_at.is_some() {
+                return None;
+            }
+            self.opened_at = Some(now);
+            Some(OverlayEvent::Opened {
+                user_initiated: event.user_initiated,
+            })
+        } else {
+            self.user_initiated = false;
+            let opened_at = self.opened_at.take()?;
+            Some(OverlayEvent::Closed {
+                duration: now.saturating_duration_since(opened_at),
+            })
+        }
+    }
+}
+
+/// Tracks whether the Steam overlay is currently open.
+///
+/// The tracker is a [`CallbackStateMachine`] that is updated from
+/// [`GameOverlayActivated`], which games usually handle anyway to pause,
+/// and reports the overlay opening and closing as [`OverlayEvent`]s.
+pub struct OverlayState {
+    status: OverlayStatus,
+    events: Vec<OverlayEvent>,
+}
+
+impl OverlayState {
+    pub(crate) fn new() -> OverlayState {
+        OverlayState {
+            status: OverlayStatus::default(),
+            events: Vec::new(),
+        }
+    }
+
+    /// Applies a [`GameOverlayActivated`]
+    pub fn on_overlay_activated(&mut self, event: &GameOverlayActivated) {
+        if let Some(event) = self.status.apply(event, Instant::now()) {
+            self.events.push(event);
+        }
+    }
+
+    /// Returns whether the overlay is currently open
+    pub fn is_open(&self) -> bool {
+        self.status.opened_at.is_some()
+    }
+
+    /// Returns whether the overlay was opened by the user, as opposed to
+    /// being opened by the game through the API
+    pub fn is_user_initiated(&self) -> bool {
+        self.status.user_initiated
+    }
+
+    /// Returns how long the overlay has been open for
+    pub fn open_duration(&self) -> Option<Duration> {
+        self.status.opened_at.map(|t| t.elapsed())
+    }
+
+    /// Returns whether the game should pause, which is the case while the
+    /// overlay is open
+    pub fn should_pause(&self) -> bool {
+        self.is_open()
+    }
+
+    /// Returns whether the game must keep presenting frames so the overlay
+    /// can be drawn, even if it would otherwise skip rendering (e.g. while
+    /// paused or in a menu without any changes).
+    pub fn should_keep_presenting(&self) -> bool {
+        self.is_open()
+            || unsafe {
+                sys::SteamAPI_ISteamUtils_BOverlayNeedsPresent(sys::SteamAPI_SteamUtils_v010())
+            }
+    }
+}
+
+impl CallbackStateMachine for OverlayState {
+    type Event = OverlayEvent;
+
+    fn handle_callback(&mut self, callback: &CallbackResult) {
+        if let CallbackResult::GameOverlayActivated(event) = callback {
+            self.on_overlay_activated(event);
+        }
+    }
+
+    /// Returns the overlay changes since the last call
+    fn poll(&mut self) -> Vec<OverlayEvent> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(active: bool, user_initiated: bool) -> GameOverlayActivated {
+        GameOverlayActivated {
+            active,
+            user_initiated,
+            app_id: AppId(480),
+        }
+    }
+
+    #[test]
+    fn status_follows_activation() {
+        let start = Instant::now();
+        let mut status = OverlayStatus::default();
+        assert_eq!(
+            status.apply(&event(true, true), start),
+            Some(OverlayEvent::Opened {
+                user_initiated: true
+            })
+        );
+        assert_eq!(status.opened_at, Some(start));
+        assert!(status.user_initiated);
+
+        // Repeated activations keep the original open time
+        assert_eq!(
+            status.apply(&event(true, false), start + Duration::from_secs(1)),
+            None
+        );
+        assert_eq!(status.opened_at, Some(start));
+        assert!(!status.user_initiated);
+
+        assert_eq!(
+            status.apply(&event(false, false), start + Duration::from_secs(2)),
+            Some(OverlayEvent::Closed {
+                duration: Duration::from_secs(2)
+            })
+        );
+        assert_eq!(status.opened_at, None);
+        assert_eq!(
+            status.apply(&event(false, false), start + Duration::from_secs(3)),
+            None
+        );
+    }
+
+    #[test]
+    fn dialog_strings() {
+        assert_eq!(OverlayDialog::Achievements.as_str(), "achievements");
+        assert_eq!(
+            OverlayDialog::ChatRoomGroup(42).as_str(),
+            "chatroomgroup/42"
+        );
+        assert_eq!(
+            OverlayUserDialog::FriendRequestAccept.as_str(),
+            "friendrequestaccept"
+        );
+    }
+}
@@ -0,0 +1,186 @@
+use super::*;
+
+/// The kinds of items a user can equip on their community profile
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CommunityProfileItemType {
+    AnimatedAvatar,
+    AvatarFrame,
+    ProfileModifier,
+    ProfileBackground,
+    MiniProfileBackground,
+}
+
+impl CommunityProfileItemType {
+    /// All item types, in the order Steam defines them
+    pub const ALL: [CommunityProfileItemType; 5] = [
+        CommunityProfileItemType::AnimatedAvatar,
+        CommunityProfileItemType::AvatarFrame,
+        CommunityProfileItemType::ProfileModifier,
+        CommunityProfileItemType::ProfileBackground,
+        CommunityProfileItemType::MiniProfileBackground,
+    ];
+}
+
+impl From<CommunityProfileItemType> for sys::ECommunityProfileItemType {
+    fn from(item: CommunityProfileItemType) -> Self {
+        match item {
+            CommunityProfileItemType::AnimatedAvatar => {
+                sys::ECommunityProfileItemType::k_ECommunityProfileItemType_AnimatedAvatar
+            }
+            CommunityProfileItemType::AvatarFrame => {
+                sys::ECommunityProfileItemType::k_ECommunityProfileItemType_AvatarFrame
+            }
+            CommunityProfileItemType::ProfileModifier => {
+                sys::ECommunityProfileItemType::k_ECommunityProfileItemType_ProfileModifier
+            }
+            CommunityProfileItemType::ProfileBackground => {
+                sys::ECommunityProfileItemType::k_ECommunityProfileItemType_ProfileBackground
+            }
+            CommunityProfileItemType::MiniProfileBackground => {
+                sys::ECommunityProfileItemType::k_ECommunityProfileItemType_MiniProfileBackground
+            }
+        }
+    }
+}
+
+/// The string properties of an equipped profile item
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ProfileItemStringProperty {
+    /// URL of the small image
+    ImageSmall,
+    /// URL of the large image, animated for animated items
+    ImageLarge,
+    /// The internal name set by the item's creator
+    InternalName,
+    /// The localized title of the item
+    Title,
+    /// The localized description of the item
+    Description,
+    /// URL of the WebM movie, for animated backgrounds
+    MovieWebM,
+    /// URL of the MP4 movie, for animated backgrounds
+    MovieMp4,
+    /// URL of the small WebM movie, for animated mini profile backgrounds
+    MovieWebMSmall,
+    /// URL of the small MP4 movie, for animated mini profile backgrounds
+    MovieMp4Small,
+}
+
+impl From<ProfileItemStringProperty> for sys::ECommunityProfileItemProperty {
+    fn from(prop: ProfileItemStringProperty) -> Self {
+        use sys::ECommunityProfileItemProperty::*;
+        match prop {
+            ProfileItemStringProperty::ImageSmall => k_ECommunityProfileItemProperty_ImageSmall,
+            ProfileItemStringProperty::ImageLarge => k_ECommunityProfileItemProperty_ImageLarge,
+            ProfileItemStringProperty::InternalName => k_ECommunityProfileItemProperty_InternalName,
+            ProfileItemStringProperty::Title => k_ECommunityProfileItemProperty_Title,
+            ProfileItemStringProperty::Description => k_ECommunityProfileItemProperty_Description,
+            ProfileItemStringProperty::MovieWebM => k_ECommunityProfileItemProperty_MovieWebM,
+            ProfileItemStringProperty::MovieMp4 => k_ECommunityProfileItemProperty_MovieMP4,
+            ProfileItemStringProperty::MovieWebMSmall => {
+                k_ECommunityProfileItemProperty_MovieWebMSmall
+            }
+            ProfileItemStringProperty::MovieMp4Small => {
+                k_ECommunityProfileItemProperty_MovieMP4Small
+            }
+        }
+    }
+}
+
+/// The numeric properties of an equipped profile item
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ProfileItemUintProperty {
+    /// The app the item belongs to
+    AppId,
+    /// The type id of the item, unique to its app
+    TypeId,
+    /// The item class, see `EItemClass` in the Steam web API
+    Class,
+}
+
+impl From<ProfileItemUintProperty> for sys::ECommunityProfileItemProperty {
+    fn from(prop: ProfileItemUintProperty) -> Self {
+        use sys::ECommunityProfileItemProperty::*;
+        match prop {
+            ProfileItemUintProperty::AppId => k_ECommunityProfileItemProperty_AppID,
+            ProfileItemUintProperty::TypeId => k_ECommunityProfileItemProperty_TypeID,
+            ProfileItemUintProperty::Class => k_ECommunityProfileItemProperty_Class,
+        }
+    }
+}
+
+/// An item equipped on a user's community profile
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProfileItem {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub internal_name: Option<String>,
+    pub image_small: Option<String>,
+    pub image_large: Option<String>,
+    pub movie_webm: Option<String>,
+    pub movie_mp4: Option<String>,
+    pub movie_webm_small: Option<String>,
+    pub movie_mp4_small: Option<String>,
+    pub app_id: AppId,
+    pub type_id: u32,
+    pub class: u32,
+}
+
+/// The profile items a user has equipped, as returned by
+/// [`Friend::request_equipped_profile_items`]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EquippedProfileItems {
+    pub user: SteamId,
+    pub has_animated_avatar: bool,
+    pub has_avatar_frame: bool,
+    pub has_profile_modifier: bool,
+    pub has_profile_background: bool,
+    pub has_mini_profile_background: bool,
+    /// Whether the data was read from the local cache
+    pub from_cache: bool,
+}
+
+impl EquippedProfileItems {
+    /// Returns whether an item of the given type is equipped
+    pub fn has(&self, item: CommunityProfileItemType) -> bool {
+        match item {
+            CommunityProfileItemType::AnimatedAvatar => self.has_animated_avatar,
+            CommunityProfileItemType::AvatarFrame => self.has_avatar_frame,
+            CommunityProfileItemType::ProfileModifier => self.has_profile_modifier,
+            CommunityProfileItemType::ProfileBackground => self.has_profile_background,
+            CommunityProfileItemType::MiniProfileBackground => self.has_mini_profile_background,
+        }
+    }
+
+    pub(crate) unsafe fn from_sys(cb: &sys::EquippedProfileItems_t) -> Self {
+        EquippedProfileItems {
+            user: SteamId(cb.m_steamID.m_steamid.m_unAll64Bits),
+            has_animated_avatar: cb.m_bHasAnimatedAvatar,
+            has_avatar_frame: cb.m_bHasAvatarFrame,
+            has_profile_modifier: cb.m_bHasProfileModifier,
+            has_profile_background: cb.m_bHasProfileBackground,
+            has_mini_profile_background: cb.m_bHasMiniProfileBackground,
+            from_cache: cb.m_bFromCache,
+        }
+    }
+}
+
+impl_callback!(cb: EquippedProfileItems_t => EquippedProfileItems {
+    Self::from_sys(&cb)
+});
+
+/// The equipped profile items of a user have changed
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EquippedProfileItemsChanged {
+    pub user: SteamId,
+}
+
+impl_callback!(cb: EquippedProfileItemsChanged_t => EquippedProfileItemsChanged {
+    Self {
+        user: SteamId(cb.m_steamID.m_steamid.m_unAll64Bits),
+    }
+});
@@ -0,0 +1,761 @@
+use super::*;
+use std::collections::BTreeMap;
+
+/// The maximum number of rich presence keys that can be set
+pub const MAX_RICH_PRESENCE_KEYS: usize = sys::k_cchMaxRichPresenceKeys as usize;
+/// The maximum length of a rich presence key
+pub const MAX_RICH_PRESENCE_KEY_LENGTH: usize = sys::k_cchMaxRichPresenceKeyLength as usize;
+/// The maximum length of a rich presence value
+pub const MAX_RICH_PRESENCE_VALUE_LENGTH: usize = sys::k_cchMaxRichPresenceValueLength as usize;
+/// How deeply `{#Token}` substitutions are followed when validating
+const MAX_TOKEN_DEPTH: usize = 8;
+
+/// The rich presence of the current user, split into the keys Steam
+/// understands and custom variables.
+///
+/// Custom variables are referenced from localization tokens as
+/// `%variable%`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RichPresenceData {
+    /// The localization token shown in the Steam friends list, e.g. `#Status_InMenu`
+    pub steam_display: Option<String>,
+    /// The status shown in the 'view game info' dialog
+    pub status: Option<String>,
+    /// The command line friends use to join the game
+    pub connect: Option<String>,
+    /// The group the user is in, used to group friends playing together
+    pub steam_player_group: Option<String>,
+    /// The total number of players in the player group
+    pub steam_player_group_size: Option<u32>,
+    /// Custom variables substituted into the `steam_display` token
+    pub variables: BTreeMap<String, String>,
+}
+
+impl RichPresenceData {
+    /// Sets a custom variable
+    pub fn variable(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.variables.insert(key.into(), value.into());
+        self
+    }
+
+    /// Returns the raw key/value pairs of this rich presence
+    pub fn to_key_values(&self) -> BTreeMap<String, String> {
+        let mut keys = self.variables.clone();
+        let known = [
+            ("steam_display", self.steam_display.clone()),
+            ("status", self.status.clone()),
+            ("connect", self.connect.clone()),
+            ("steam_player_group", self.steam_player_group.clone()),
+            (
+                "steam_player_group_size",
+                self.steam_player_group_size.map(|s| s.to_string()),
+            ),
+        ];
+        for (key, value) in known {
+            if let Some(value) = value.filter(|v| !v.is_empty()) {
+                keys.insert(key.to_owned(), value);
+            }
+        }
+        keys
+    }
+
+    /// Checks the rich presence against the limits imposed by Steam
+    pub fn check_limits(&self) -> Result<(), Vec<RichPresenceError>> {
+        let keys = self.to_key_values();
+        let mut errors = Vec::new();
+        if keys.len() > MAX_RICH_PRESENCE_KEYS {
+            errors.push(RichPresenceError::TooManyKeys(keys.len()));
+        }
+        for (key, value) in &keys {
+            if key.len() >= MAX_RICH_PRESENCE_KEY_LENGTH {
+                errors.push(RichPresenceError::KeyTooLong(key.clone()));
+            }
+            if value.len() >= MAX_RICH_PRESENCE_VALUE_LENGTH {
+                errors.push(RichPresenceError::ValueTooLong(key.clone()));
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// Types that describe the rich presence of the current user.
+///
+/// # Example
+///
+/// ```no_run
+/// # use steamworks::*;
+/// struct InMatch {
+///     map: String,
+///     lobby: LobbyId,
+///     players: u32,
+/// }
+///
+/// impl RichPresence for InMatch {
+///     fn rich_presence(&self) -> RichPresenceData {
+///         RichPresenceData {
+///             steam_display: Some("#Status_InMatch".into()),
+///             steam_player_group: Some(self.lobby.raw().to_string()),
+///             steam_player_group_size: Some(self.players),
+///             ..Default::default()
+///         }
+///         .variable("map", &self.map)
+///     }
+/// }
+/// ```
+pub trait RichPresence {
+    /// Returns the rich presence keys for this state
+    fn rich_presence(&self) -> RichPresenceData;
+}
+
+impl RichPresence for RichPresenceData {
+    fn rich_presence(&self) -> RichPresenceData {
+        self.clone()
+    }
+}
+
+/// Errors found when checking rich presence
+#[derive(Clone, Debug, Error, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RichPresenceError {
+    #[error("too many rich presence keys ({0})")]
+    TooManyKeys(usize),
+    #[error("rich presence key {0:?} is too long")]
+    KeyTooLong(String),
+    #[error("value of rich presence key {0:?} is too long")]
+    ValueTooLong(String),
+    #[error("steam_display {0:?} is not a localization token")]
+    NotAToken(String),
+    #[error("token {token:?} is missing for language {language:?}")]
+    MissingToken { language: String, token: String },
+    #[error("variable {variable:?} used by token {token:?} is not set")]
+    MissingVariable { token: String, variable: String },
+    /// The token is nested in itself or too deeply to be resolved
+    #[error("token {token:?} is nested in itself or too deeply for language {language:?}")]
+    TokenCycle { language: String, token: String },
+}
+
+/// Sets the rich presence of the current user, only sending the keys that
+/// changed since the last update.
+///
+/// Keys are tracked from the last applied state, so rich presence set
+/// through other means is not taken into account.
+pub struct RichPresenceUpdater {
+    friends: *mut sys::ISteamFriends,
+    _inner: Arc<Inner>,
+    current: BTreeMap<String, String>,
+    localization: Option<RichPresenceLocalization>,
+}
+
+impl RichPresenceUpdater {
+    pub(crate) fn new(friends: &Friends) -> RichPresenceUpdater {
+        RichPresenceUpdater {
+            friends: friends.friends,
+            _inner: friends.inner.clone(),
+            current: BTreeMap::new(),
+            localization: None,
+        }
+    }
+
+    /// Validates every update against the localization tokens before it
+    /// is sent.
+    pub fn with_localization(mut self, localization: RichPresenceLocalization) -> Self {
+        self.localization = Some(localization);
+        self
+    }
+
+    /// Applies the rich presence, returning the number of keys that were
+    /// changed.
+    ///
+    /// Nothing is sent if the rich presence exceeds the Steam limits or
+    /// fails validation against the localization.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a key or value contains a null byte.
+    pub fn apply(&mut self, presence: &impl RichPresence) -> Result<usize, Vec<RichPresenceError>> {
+        let data = presence.rich_presence();
+        data.check_limits()?;
+        if let Some(localization) = &self.localization {
+            localization.validate(&data)?;
+        }
+        let new = data.to_key_values();
+        let changes = diff_key_values(&self.current, &new);
+        for (key, value) in &changes {
+            let key = CString::new(key.as_str()).unwrap();
+            let value = value.as_ref().map(|v| CString::new(v.as_str()).unwrap());
+            let value_ptr = value
+                .as_ref()
+                .map_or(std::ptr::null(), |value| value.as_ptr());
+            unsafe {
+                sys::SteamAPI_ISteamFriends_SetRichPresence(self.friends, key.as_ptr(), value_ptr);
+            }
+        }
+        self.current = new;
+        Ok(changes.len())
+    }
+
+    /// Clears all rich presence keys of the current user
+    pub fn clear(&mut self) {
+        unsafe {
+            sys::SteamAPI_ISteamFriends_ClearRichPresence(self.friends);
+        }
+        self.current.clear();
+    }
+}
+
+/// Returns the keys that have to be set (or unset, for `None`) to go from
+/// `old` to `new`.
+fn diff_key_values(
+    old: &BTreeMap<String, String>,
+    new: &BTreeMap<String, String>,
+) -> Vec<(String, Option<String>)> {
+    let removed = old
+        .keys()
+        .filter(|k| !new.contains_key(*k))
+        .map(|k| (k.clone(), None));
+    let changed = new
+        .iter()
+        .filter(|(k, v)| old.get(*k) != Some(*v))
+        .map(|(k, v)| (k.clone(), Some(v.clone())));
+    removed.chain(changed).collect()
+}
+
+/// The rich presence localization tokens of a game, as uploaded to
+/// Steamworks.
+///
+/// Token names are case insensitive.
+#[derive(Clone, Debug, Default)]
+pub struct RichPresenceLocalization {
+    languages: BTreeMap<String, HashMap<String, String>>,
+}
+
+impl RichPresenceLocalization {
+    /// Parses a rich presence localization file.
+    ///
+    /// Both files with a single language and files containing all languages
+    /// are supported.
+    pub fn parse(vdf: &str) -> Result<RichPresenceLocalization, VdfParseError> {
+        let root = VdfParser::new(vdf).parse()?;
+        let mut localization = RichPresenceLocalization::default();
+        localization.collect("", &root);
+        Ok(localization)
+    }
+
+    fn collect(&mut self, parent: &str, entries: &[(String, VdfValue)]) {
+        let language = entries
+            .iter()
+            .find_map(|(k, v)| match v {
+                VdfValue::String(s) if k.eq_ignore_ascii_case("language") => Some(s.as_str()),
+                _ => None,
+            })
+            .unwrap_or(parent);
+        for (key, value) in entries {
+            if let VdfValue::Object(children) = value {
+                if key.eq_ignore_ascii_case("tokens") {
+                    let tokens = self.languages.entry(language.to_lowercase()).or_default();
+                    for (name, value) in children {
+                        if let VdfValue::String(text) = value {
+                            tokens.insert(name.to_lowercase(), text.clone());
+                        }
+                    }
+                } else {
+                    self.collect(key, children);
+                }
+            }
+        }
+    }
+
+    /// Returns the languages that have tokens
+    pub fn languages(&self) -> impl Iterator<Item = &str> {
+        self.languages.keys().map(|l| l.as_str())
+    }
+
+    /// Returns the text of a token in the given language
+    pub fn token(&self, language: &str, token: &str) -> Option<&str> {
+        self.languages
+            .get(&language.to_lowercase())?
+            .get(&token.to_lowercase())
+            .map(|s| s.as_str())
+    }
+
+    /// Checks that the `steam_display` token of the rich presence exists in
+    /// every language, and that all variables and nested tokens it
+    /// references can be resolved.
+    pub fn validate(&self, presence: &RichPresenceData) -> Result<(), Vec<RichPresenceError>> {
+        let display = match &presence.steam_display {
+            Some(display) => display,
+            None => return Ok(()),
+        };
+        if !display.starts_with('#') {
+            return Err(vec![RichPresenceError::NotAToken(display.clone())]);
+        }
+        let keys = presence.to_key_values();
+        let mut errors = Vec::new();
+        for (language, tokens) in &self.languages {
+            let text = match tokens.get(&display.to_lowercase()) {
+                Some(text) => text,
+                None => {
+                    errors.push(RichPresenceError::MissingToken {
+                        language: language.clone(),
+                        token: display.clone(),
+                    });
+                    continue;
+                }
+            };
+            let mut visiting = vec![display.to_lowercase()];
+            check_substitutions(
+                language,
+                tokens,
+                display,
+                text,
+                &keys,
+                &mut visiting,
+                &mut errors,
+            );
+        }
+        errors.sort();
+        errors.dedup();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// Returns whether `name` can be a rich presence key used as `%name%`
+fn is_variable_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Checks the `%variable%` and `{#Token_%variable%}` substitutions of a
+/// token. `visiting` holds the tokens currently being checked, starting
+/// with the `steam_display` token.
+fn check_substitutions(
+    language: &str,
+    tokens: &HashMap<String, String>,
+    token: &str,
+    text: &str,
+    keys: &BTreeMap<String, String>,
+    visiting: &mut Vec<String>,
+    errors: &mut Vec<RichPresenceError>,
+) {
+    let mut rest = text;
+    while let Some(start) = rest.find(['%', '{']) {
+        let (open, close) = if rest[start..].starts_with('%') {
+            ('%', '%')
+        } else {
+            ('{', '}')
+        };
+        let after = &rest[start + 1..];
+        let end = match after.find(close) {
+            Some(end) => end,
+            None => break,
+        };
+        let inner = &after[..end];
+        if open == '%' && !is_variable_name(inner) {
+            // A literal percent sign, e.g. in "50% of %map%"
+            rest = after;
+            continue;
+        }
+        rest = &after[end + 1..];
+
+        if open == '%' {
+            if !keys.contains_key(inner) {
+                errors.push(RichPresenceError::MissingVariable {
+                    token: token.to_owned(),
+                    variable: inner.to_owned(),
+                });
+            }
+            continue;
+        }
+        if !inner.starts_with('#') {
+            continue;
+        }
+        // Nested token whose name is built from variables
+        let mut name = String::new();
+        let mut parts = inner.split('%');
+        let mut resolved = true;
+        name.push_str(parts.next().unwrap_or_default());
+        while let Some(variable) = parts.next() {
+            match keys.get(variable) {
+                Some(value) => name.push_str(value),
+                None => {
+                    errors.push(RichPresenceError::MissingVariable {
+                        token: token.to_owned(),
+                        variable: variable.to_owned(),
+                    });
+                    resolved = false;
+                }
+            }
+            name.push_str(parts.next().unwrap_or_default());
+        }
+        if !resolved {
+            continue;
+        }
+        let lower = name.to_lowercase();
+        if visiting.contains(&lower) || visiting.len() >= MAX_TOKEN_DEPTH {
+            errors.push(RichPresenceError::TokenCycle {
+                language: language.to_owned(),
+                token: name,
+            });
+            continue;
+        }
+        match tokens.get(&lower) {
+            Some(nested) => {
+                visiting.push(lower);
+                check_substitutions(language, tokens, &name, nested, keys, visiting, errors);
+                visiting.pop();
+            }
+            None => errors.push(RichPresenceError::MissingToken {
+                language: language.to_owned(),
+                token: name,
+            }),
+        }
+    }
+}
+
+/// An error while parsing a KeyValues (`.vdf`) file
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum VdfParseError {
+    #[error("unexpected end of file")]
+    UnexpectedEof,
+    #[error("unexpected {0:?} on line {1}")]
+    Unexpected(char, usize),
+}
+
+#[derive(Debug)]
+enum VdfValue {
+    String(String),
+    Object(Vec<(String, VdfValue)>),
+}
+
+struct VdfParser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+}
+
+impl<'a> VdfParser<'a> {
+    fn new(input: &'a str) -> Self {
+        VdfParser {
+            chars: input.trim_start_matches('\u{feff}').chars().peekable(),
+            line: 1,
+        }
+    }
+
+    fn parse(mut self) -> Result<Vec<(String, VdfValue)>, VdfParseError> {
+        let entries = self.parse_entries()?;
+        match self.next_token()? {
+            None => Ok(entries),
+            Some(_) => Err(VdfParseError::Unexpected('}', self.line)),
+        }
+    }
+
+    fn parse_entries(&mut self) -> Result<Vec<(String, VdfValue)>, VdfParseError> {
+        let mut entries = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.chars.peek() {
+                None | Some('}') => return Ok(entries),
+                _ => {}
+            }
+            let key = match self.next_token()? {
+                Some(VdfToken::String(key)) => key,
+                Some(VdfToken::Open) => return Err(VdfParseError::Unexpected('{', self.line)),
+                Some(VdfToken::Close) | None => unreachable!(),
+            };
+            let value = match self.next_token()? {
+                Some(VdfToken::String(value)) => VdfValue::String(value),
+                Some(VdfToken::Open) => {
+                    let children = self.parse_entries()?;
+                    match self.next_token()? {
+                        Some(VdfToken::Close) => VdfValue::Object(children),
+                        _ => return Err(VdfParseError::UnexpectedEof),
+                    }
+                }
+                Some(VdfToken::Close) => return Err(VdfParseError::Unexpected('}', self.line)),
+                None => return Err(VdfParseError::UnexpectedEof),
+            };
+            // Platform conditionals such as `[$WIN32]` are ignored
+            self.skip_whitespace();
+            if self.chars.peek() == Some(&'[') {
+                for c in self.chars.by_ref() {
+                    if c == ']' {
+                        break;
+                    }
+                }
+            }
+            entries.push((key, value));
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        loop {
+            match self.chars.peek() {
+                Some('\n') => {
+                    self.line += 1;
+                    self.chars.next();
+                }
+                Some(c) if c.is_whitespace() => {
+                    self.chars.next();
+                }
+                Some('/') => {
+                    let mut lookahead = self.chars.clone();
+                    lookahead.next();
+                    if lookahead.peek() != Some(&'/') {
+                        return;
+                    }
+                    while self.chars.peek().is_some_and(|c| *c != '\n') {
+                        self.chars.next();
+                    }
+                }
+                _ => return,
+            }
+        }
+    }
+
+    fn next_token(&mut self) -> Result<Option<VdfToken>, VdfParseError> {
+        self.skip_whitespace();
+        let c = match self.chars.next() {
+            Some(c) => c,
+            None => return Ok(None),
+        };
+        match c {
+            '{' => Ok(Some(VdfToken::Open)),
+            '}' => Ok(Some(VdfToken::Close)),
+            '"' => {
+                let mut s = String::new();
+                loop {
+                    match self.chars.next() {
+                        None => return Err(VdfParseError::UnexpectedEof),
+                        Some('"') => return Ok(Some(VdfToken::String(s))),
+                        Some('\\') => match self.chars.next() {
+                            Some('n') => s.push('\n'),
+                            Some('t') => s.push('\t'),
+                            Some(c) => s.push(c),
+                            None => return Err(VdfParseError::UnexpectedEof),
+                        },
+                        Some(c) => {
+                            if c == '\n' {
+                                self.line += 1;
+                            }
+                            s.push(c);
+                        }
+                    }
+                }
+            }
+            c => {
+                let mut s = String::from(c);
+                while let Some(&c) = self.chars.peek() {
+                    if c.is_whitespace() || c == '{' || c == '}' || c == '"' {
+                        break;
+                    }
+                    s.push(c);
+                    self.chars.next();
+                }
+                Ok(Some(VdfToken::String(s)))
+            }
+        }
+    }
+}
+
+enum VdfToken {
+    String(String),
+    Open,
+    Close,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOCALIZATION: &str = r##"
+"lang"
+{
+    "Language" "english"
+    "Tokens"
+    {
+        // Shown in the friends list
+        "#Status_InMenu"    "In the main menu"
+        "#Status_InMatch"   "Playing {#Mode_%mode%} on %map%"
+        "#Mode_Coop"        "Co-op"
+        "#Mode_Versus"      "Versus"
+        "#Status_Quoted"    "Says \"hi\""
+    }
+}
+"##;
+
+    fn in_match(mode: &str) -> RichPresenceData {
+        RichPresenceData {
+            steam_display: Some("#Status_InMatch".into()),
+            ..Default::default()
+        }
+        .variable("mode", mode)
+        .variable("map", "Harbor")
+    }
+
+    #[test]
+    fn parses_single_language_file() {
+        let loc = RichPresenceLocalization::parse(LOCALIZATION).unwrap();
+        assert_eq!(loc.languages().collect::<Vec<_>>(), ["english"]);
+        assert_eq!(
+            loc.token("English", "#status_inmenu"),
+            Some("In the main menu")
+        );
+        assert_eq!(loc.token("english", "#Status_Quoted"), Some("Says \"hi\""));
+    }
+
+    #[test]
+    fn parses_multi_language_file() {
+        let vdf = r##"
+"lang"
+{
+    "english" { "tokens" { "#A" "A" } }
+    "german" { "tokens" { "#A" "Ä" } }
+}
+"##;
+        let loc = RichPresenceLocalization::parse(vdf).unwrap();
+        assert_eq!(loc.languages().collect::<Vec<_>>(), ["english", "german"]);
+        assert_eq!(loc.token("german", "#A"), Some("Ä"));
+    }
+
+    #[test]
+    fn rejects_malformed_files() {
+        assert_eq!(
+            RichPresenceLocalization::parse("\"lang\" { \"a\" \"b\"").unwrap_err(),
+            VdfParseError::UnexpectedEof
+        );
+        assert_eq!(
+            RichPresenceLocalization::parse("\"lang\"\n}").unwrap_err(),
+            VdfParseError::Unexpected('}', 2)
+        );
+    }
+
+    #[test]
+    fn validates_tokens_and_substitutions() {
+        let loc = RichPresenceLocalization::parse(LOCALIZATION).unwrap();
+        assert_eq!(loc.validate(&in_match("Coop")), Ok(()));
+        assert_eq!(
+            loc.validate(&in_match("Coopp")),
+            Err(vec![RichPresenceError::MissingToken {
+                language: "english".into(),
+                token: "#Mode_Coopp".into(),
+            }])
+        );
+
+        let mut missing_map = in_match("Versus");
+        missing_map.variables.remove("map");
+        assert_eq!(
+            loc.validate(&missing_map),
+            Err(vec![RichPresenceError::MissingVariable {
+                token: "#Status_InMatch".into(),
+                variable: "map".into(),
+            }])
+        );
+
+        let typo = RichPresenceData {
+            steam_display: Some("#Status_InMenuu".into()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            loc.validate(&typo).unwrap_err()[0],
+            RichPresenceError::MissingToken { .. }
+        ));
+
+        let plain = RichPresenceData {
+            steam_display: Some("In menu".into()),
+            ..Default::default()
+        };
+        assert_eq!(
+            loc.validate(&plain),
+            Err(vec![RichPresenceError::NotAToken("In menu".into())])
+        );
+    }
+
+    #[test]
+    fn validates_cycles_and_literal_percent_signs() {
+        let vdf = r##"
+"lang"
+{
+    "Language" "english"
+    "Tokens"
+    {
+        "#Self"     "{#Self}"
+        "#A"        "{#B}"
+        "#B"        "Loops {#A}"
+        "#Progress" "50% of %map% done, 100%"
+    }
+}
+"##;
+        let loc = RichPresenceLocalization::parse(vdf).unwrap();
+        let display = |token: &str| RichPresenceData {
+            steam_display: Some(token.into()),
+            ..Default::default()
+        };
+        assert_eq!(
+            loc.validate(&display("#Self")),
+            Err(vec![RichPresenceError::TokenCycle {
+                language: "english".into(),
+                token: "#Self".into(),
+            }])
+        );
+        assert_eq!(
+            loc.validate(&display("#A")),
+            Err(vec![RichPresenceError::TokenCycle {
+                language: "english".into(),
+                token: "#A".into(),
+            }])
+        );
+        assert_eq!(
+            loc.validate(&display("#Progress").variable("map", "Harbor")),
+            Ok(())
+        );
+        assert_eq!(
+            loc.validate(&display("#Progress")),
+            Err(vec![RichPresenceError::MissingVariable {
+                token: "#Progress".into(),
+                variable: "map".into(),
+            }])
+        );
+    }
+
+    #[test]
+    fn diff_only_sends_changes() {
+        let old = in_match("Coop").to_key_values();
+        let mut new_data = in_match("Versus");
+        new_data.variables.remove("map");
+        new_data.steam_player_group_size = Some(4);
+        let new = new_data.to_key_values();
+
+        let mut changes = diff_key_values(&old, &new);
+        changes.sort();
+        assert_eq!(
+            changes,
+            [
+                ("map".to_owned(), None),
+                ("mode".to_owned(), Some("Versus".to_owned())),
+                ("steam_player_group_size".to_owned(), Some("4".to_owned())),
+            ]
+        );
+        assert!(diff_key_values(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn checks_limits() {
+        let mut data = RichPresenceData::default();
+        for i in 0..=MAX_RICH_PRESENCE_KEYS {
+            data.variables.insert(format!("key{}", i), "value".into());
+        }
+        data.status = Some("x".repeat(MAX_RICH_PRESENCE_VALUE_LENGTH));
+        let errors = data.check_limits().unwrap_err();
+        assert!(errors.contains(&RichPresenceError::TooManyKeys(MAX_RICH_PRESENCE_KEYS + 2)));
+        assert!(errors.contains(&RichPresenceError::ValueTooLong("status".into())));
+    }
+}
@@ -0,0 +1,412 @@
+use super::*;
+
+/// A cached view of a friend's persona, kept up to date by a [`FriendsTracker`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct FriendSnapshot {
+    pub id: SteamId,
+    /// The persona name of the friend
+    pub name: String,
+    /// The nickname the current user has set for the friend
+    pub nickname: Option<String>,
+    pub state: FriendState,
+    /// The game the friend is currently playing if any
+    pub game: Option<FriendGame>,
+    /// The rich presence key/values of the friend for the current game
+    pub rich_presence: HashMap<String, String>,
+    pub steam_level: u32,
+}
+
+impl FriendSnapshot {
+    fn from_friend(friend: &Friend) -> FriendSnapshot {
+        FriendSnapshot {
+            id: friend.id(),
+            name: friend.name(),
+            nickname: friend.nick_name(),
+            state: friend.state(),
+            game: friend.game_played(),
+            rich_presence: friend.all_rich_presence(),
+            steam_level: friend.steam_level(),
+        }
+    }
+
+    /// Re-reads only the fields covered by `changed`
+    fn refresh(&mut self, friend: &Friend, changed: PersonaChange) {
+        if changed.intersects(PersonaChange::NAME | PersonaChange::NAME_FIRST_SET) {
+            self.name = friend.name();
+        }
+        if changed.intersects(
+            PersonaChange::STATUS | PersonaChange::COME_ONLINE | PersonaChange::GONE_OFFLINE,
+        ) {
+            self.state = friend.state();
+        }
+        if changed.intersects(PersonaChange::GAME_PLAYED | PersonaChange::GAME_SERVER) {
+            self.game = friend.game_played();
+        }
+        if changed.contains(PersonaChange::NICKNAME) {
+            self.nickname = friend.nick_name();
+        }
+        if changed.contains(PersonaChange::STEAM_LEVEL) {
+            self.steam_level = friend.steam_level();
+        }
+    }
+
+    /// Returns whether the friend is online in any state
+    pub fn is_online(&self) -> bool {
+        self.state != FriendState::Offline
+    }
+}
+
+/// Changes to the friends list reported by a [`FriendsTracker`]
+#[derive(Clone, Debug, PartialEq)]
+pub enum FriendEvent {
+    /// A user became a friend
+    Added(SteamId),
+    /// A user is no longer a friend
+    Removed(SteamId),
+    /// A friend went from offline to any online state
+    CameOnline(SteamId),
+    /// A friend went offline
+    WentOffline(SteamId),
+    /// A friend that was and still is online changed their state,
+    /// e.g. from online to away
+    StateChanged {
+        friend: SteamId,
+        old: FriendState,
+        new: FriendState,
+    },
+    NameChanged {
+        friend: SteamId,
+        old: String,
+        new: String,
+    },
+    NicknameChanged {
+        friend: SteamId,
+        nickname: Option<String>,
+    },
+    StartedPlaying {
+        friend: SteamId,
+        game: GameId,
+    },
+    /// A friend started playing the current game. Sent in
+    /// addition to [`FriendEvent::StartedPlaying`].
+    StartedPlayingOurGame(SteamId),
+    StoppedPlaying {
+        friend: SteamId,
+        game: GameId,
+    },
+    RichPresenceChanged(SteamId),
+    SteamLevelChanged {
+        friend: SteamId,
+        level: u32,
+    },
+    AvatarChanged(SteamId),
+}
+
+struct TrackerState {
+    app_id: AppId,
+    friends: HashMap<SteamId, FriendSnapshot>,
+    events: Vec<FriendEvent>,
+}
+
+impl TrackerState {
+    fn send(&mut self, event: FriendEvent) {
+        self.events.push(event);
+    }
+
+    fn insert(&mut self, snapshot: FriendSnapshot) {
+        self.send(FriendEvent::Added(snapshot.id));
+        self.friends.insert(snapshot.id, snapshot);
+    }
+
+    fn remove(&mut self, id: SteamId) {
+        if self.friends.remove(&id).is_some() {
+            self.send(FriendEvent::Removed(id));
+        }
+    }
+
+    /// Replaces the snapshot of a friend, reporting what changed
+    fn update(&mut self, new: FriendSnapshot) {
+        let Some(old) = self.friends.insert(new.id, new.clone()) else {
+            self.send(FriendEvent::Added(new.id));
+            return;
+        };
+        let friend = new.id;
+
+        match (old.is_online(), new.is_online()) {
+            (false, true) => self.send(FriendEvent::CameOnline(friend)),
+            (true, false) => self.send(FriendEvent::WentOffline(friend)),
+            (true, true) if old.state != new.state => self.send(FriendEvent::StateChanged {
+                friend,
+                old: old.state,
+                new: new.state,
+            }),
+            _ => {}
+        }
+        if old.name != new.name {
+            self.send(FriendEvent::NameChanged {
+                friend,
+                old: old.name,
+                new: new.name.clone(),
+            });
+        }
+        if old.nickname != new.nickname {
+            self.send(FriendEvent::NicknameChanged {
+                friend,
+                nickname: new.nickname.clone(),
+            });
+        }
+
+        let old_game = old.game.map(|g| g.game);
+        let new_game = new.game.as_ref().map(|g| g.game);
+        if old_game != new_game {
+            if let Some(game) = old_game {
+                self.send(FriendEvent::StoppedPlaying { friend, game });
+            }
+            if let Some(game) = new_game {
+                self.send(FriendEvent::StartedPlaying { friend, game });
+                if game.app_id() == self.app_id {
+                    self.send(FriendEvent::StartedPlayingOurGame(friend));
+                }
+            }
+        }
+
+        if old.rich_presence != new.rich_presence {
+            self.send(FriendEvent::RichPresenceChanged(friend));
+        }
+        if old.steam_level != new.steam_level {
+            self.send(FriendEvent::SteamLevelChanged {
+                friend,
+                level: new.steam_level,
+            });
+        }
+    }
+}
+
+/// Keeps a snapshot of the current user's friends and their personas
+/// up to date.
+///
+/// The snapshot is updated incrementally from [`PersonaStateChange`] and
+/// [`FriendRichPresenceUpdate`] callbacks, so reading it does not go
+/// through the Steam API. The tracker is a [`CallbackStateMachine`] that
+/// needs both callbacks forwarded and reports the changes as
+/// [`FriendEvent`]s from [`poll`](CallbackStateMachine::poll).
+pub struct FriendsTracker {
+    friends: *mut sys::ISteamFriends,
+    inner: Arc<Inner>,
+    flags: FriendFlags,
+    state: TrackerState,
+}
+
+impl FriendsTracker {
+    pub(crate) fn new(friends: &Friends, flags: FriendFlags) -> FriendsTracker {
+        let app_id = unsafe {
+            AppId(sys::SteamAPI_ISteamUtils_GetAppID(
+                sys::SteamAPI_SteamUtils_v010(),
+            ))
+        };
+        let snapshots = friends
+            .get_friends(flags)
+            .iter()
+            .map(|f| (f.id(), FriendSnapshot::from_friend(f)))
+            .collect();
+        FriendsTracker {
+            friends: friends.friends,
+            inner: friends.inner.clone(),
+            flags,
+            state: TrackerState {
+                app_id,
+                friends: snapshots,
+                events: Vec::new(),
+            },
+        }
+    }
+
+    fn get_friend(&self, id: SteamId) -> Friend {
+        Friend {
+            id,
+            friends: self.friends,
+            _inner: self.inner.clone(),
+        }
+    }
+
+    /// Returns the snapshots of all tracked friends
+    pub fn friends(&self) -> Vec<FriendSnapshot> {
+        self.state.friends.values().cloned().collect()
+    }
+
+    /// Returns the snapshot of a tracked friend
+    pub fn friend(&self, id: SteamId) -> Option<FriendSnapshot> {
+        self.state.friends.get(&id).cloned()
+    }
+
+    /// Applies a [`PersonaStateChange`]
+    pub fn on_persona_state_change(&mut self, change: &PersonaStateChange) {
+        let friend = self.get_friend(change.steam_id);
+        let is_friend = friend.has_friend(self.flags);
+        let state = &mut self.state;
+        match state.friends.get(&change.steam_id) {
+            None if is_friend => state.insert(FriendSnapshot::from_friend(&friend)),
+            Some(_) if !is_friend => state.remove(change.steam_id),
+            Some(old) => {
+                let mut new = old.clone();
+                new.refresh(&friend, change.flags);
+                state.update(new);
+                if change.flags.contains(PersonaChange::AVATAR) {
+                    state.send(FriendEvent::AvatarChanged(change.steam_id));
+                }
+            }
+            None => {}
+        }
+    }
+
+    /// Applies a [`FriendRichPresenceUpdate`]. Updates for other games are
+    /// ignored.
+    pub fn on_rich_presence_update(&mut self, update: &FriendRichPresenceUpdate) {
+        if update.app_id != self.state.app_id {
+            return;
+        }
+        if let Some(old) = self.state.friends.get(&update.friend) {
+            let mut new = old.clone();
+            new.rich_presence = self.get_friend(update.friend).all_rich_presence();
+            self.state.update(new);
+        }
+    }
+}
+
+impl CallbackStateMachine for FriendsTracker {
+    type Event = FriendEvent;
+
+    fn handle_callback(&mut self, callback: &CallbackResult) {
+        match callback {
+            CallbackResult::PersonaStateChange(change) => self.on_persona_state_change(change),
+            CallbackResult::FriendRichPresenceUpdate(update) => {
+                self.on_rich_presence_update(update)
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns all changes since the last call
+    fn poll(&mut self) -> Vec<FriendEvent> {
+        std::mem::take(&mut self.state.events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(id: u64) -> FriendSnapshot {
+        FriendSnapshot {
+            id: SteamId(id),
+            name: "friend".into(),
+            nickname: None,
+            state: FriendState::Offline,
+            game: None,
+            rich_presence: HashMap::new(),
+            steam_level: 10,
+        }
+    }
+
+    fn state() -> TrackerState {
+        let mut state = TrackerState {
+            app_id: AppId(480),
+            friends: HashMap::new(),
+            events: Vec::new(),
+        };
+        state.insert(snapshot(1));
+        assert_eq!(
+            std::mem::take(&mut state.events),
+            vec![FriendEvent::Added(SteamId(1))]
+        );
+        state
+    }
+
+    fn playing(app_id: u32) -> Option<FriendGame> {
+        Some(FriendGame {
+            game: GameId(app_id as u64),
+            game_address: Ipv4Addr::UNSPECIFIED,
+            game_port: 0,
+            query_port: 0,
+            lobby: LobbyId(0),
+        })
+    }
+
+    #[test]
+    fn test_online_and_state_events() {
+        let mut state = state();
+        let friend = SteamId(1);
+
+        let mut new = snapshot(1);
+        new.state = FriendState::Online;
+        state.update(new.clone());
+        new.state = FriendState::Away;
+        state.update(new.clone());
+        new.state = FriendState::Offline;
+        state.update(new);
+
+        let events = std::mem::take(&mut state.events);
+        assert_eq!(
+            events,
+            vec![
+                FriendEvent::CameOnline(friend),
+                FriendEvent::StateChanged {
+                    friend,
+                    old: FriendState::Online,
+                    new: FriendState::Away,
+                },
+                FriendEvent::WentOffline(friend),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_game_and_name_events() {
+        let mut state = state();
+        let friend = SteamId(1);
+
+        let mut new = snapshot(1);
+        new.game = playing(440);
+        state.update(new.clone());
+        new.game = playing(480);
+        new.name = "renamed".into();
+        state.update(new);
+
+        let events = std::mem::take(&mut state.events);
+        assert_eq!(
+            events,
+            vec![
+                FriendEvent::StartedPlaying {
+                    friend,
+                    game: GameId(440),
+                },
+                FriendEvent::NameChanged {
+                    friend,
+                    old: "friend".into(),
+                    new: "renamed".into(),
+                },
+                FriendEvent::StoppedPlaying {
+                    friend,
+                    game: GameId(440),
+                },
+                FriendEvent::StartedPlaying {
+                    friend,
+                    game: GameId(480),
+                },
+                FriendEvent::StartedPlayingOurGame(friend),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unchanged_snapshot_has_no_events() {
+        let mut state = state();
+        state.update(snapshot(1));
+        assert!(state.events.is_empty());
+
+        state.remove(SteamId(2));
+        assert!(state.events.is_empty());
+        state.remove(SteamId(1));
+        assert_eq!(state.events, vec![FriendEvent::Removed(SteamId(1))]);
+    }
+}
@@ -24,6 +24,8 @@ use serde::{Deserialize, Serialize};
 
 pub use crate::app::*;
 pub use crate::callback::*;
+#[cfg(feature = "encrypted-app-ticket")]
+pub use crate::encrypted_app_ticket::*;
 pub use crate::error::*;
 pub use crate::friends::*;
 pub use crate::input::*;
@@ -33,6 +35,7 @@ pub use crate::networking::*;
 pub use crate::remote_play::*;
 pub use crate::remote_storage::*;
 pub use crate::server::*;
+pub use crate::steam_image::*;
 pub use crate::timeline::*;
 pub use crate::ugc::*;
 pub use crate::user::*;
@@ -42,6 +45,8 @@ pub use crate::utils::*;
 #[macro_use]
 mod callback;
 mod app;
+#[cfg(feature = "encrypted-app-ticket")]
+mod encrypted_app_ticket;
 mod error;
 mod friends;
 mod input;
@@ -53,15 +58,19 @@ pub mod networking_sockets;
 mod networking_sockets_callback;
 pub mod networking_types;
 pub mod networking_utils;
+pub mod networking_voice;
 mod remote_play;
 mod remote_storage;
 pub mod screenshots;
 mod server;
+mod steam_image;
 pub mod timeline;
 mod ugc;
 mod user;
 mod user_stats;
 mod utils;
+#[cfg(feature = "webapi")]
+pub mod webapi;
 
 pub type SResult<T> = Result<T, SteamError>;
 
@@ -622,13 +631,7 @@ impl SteamId {
         let bits = sys::CSteamID_SteamID_t {
             m_unAll64Bits: self.0,
         };
-        match unsafe { bits.m_comp }.m_EUniverse() {
-            sys::EUniverse::k_EUniversePublic => Universe::Public,
-            sys::EUniverse::k_EUniverseBeta => Universe::Beta,
-            sys::EUniverse::k_EUniverseInternal => Universe::Internal,
-            sys::EUniverse::k_EUniverseDev => Universe::Dev,
-            _ => Universe::Invalid,
-        }
+        unsafe { bits.m_comp }.m_EUniverse().into()
     }
 
     pub fn account_type(&self) -> AccountType {
@@ -678,6 +681,18 @@ pub enum Universe {
     Dev = 4,
 }
 
+impl From<sys::EUniverse> for Universe {
+    fn from(universe: sys::EUniverse) -> Self {
+        match universe {
+            sys::EUniverse::k_EUniversePublic => Universe::Public,
+            sys::EUniverse::k_EUniverseBeta => Universe::Beta,
+            sys::EUniverse::k_EUniverseInternal => Universe::Internal,
+            sys::EUniverse::k_EUniverseDev => Universe::Dev,
+            _ => Universe::Invalid,
+        }
+    }
+}
+
 /// Steam account types.
 ///
 /// [`SteamId`]s are used to identify many different types of entities within Steam.
@@ -1152,7 +1152,8 @@ impl NetConnectionInfo {
     }
 
     pub fn end_reason(&self) -> Option<NetConnectionEnd> {
-        if self.inner.m_eEndReason == ESteamNetConnectionEnd::k_ESteamNetConnectionEnd_Invalid as _
+        if self.inner.m_eEndReason
+            == ESteamNetConnectionEnd::k_ESteamNetConnectionEnd_Invalid as i32
         {
             None
         } else {
@@ -0,0 +1,758 @@
+//! Voice chat on top of [`NetworkingMessages`].
+//!
+//! [`VoiceChat`] sends encoded voice frames, such as the compressed voice
+//! returned by [`VoiceRecorder::read_compressed`], to other players as
+//! unreliable messages with sequence numbers. Received frames are put into
+//! a jitter buffer per speaker, decoded and mixed into a single PCM stream,
+//! with lost frames concealed by fading out the previous frame.
+//!
+//! The transport and the decoder are traits, so the pipeline can be used
+//! with other codecs or tested without Steam using [`LoopbackTransport`].
+//!
+//! # Example
+//! ```no_run
+//! # use steamworks::*;
+//! # use steamworks::networking_voice::*;
+//! let client = Client::init().unwrap();
+//! let mut recorder = client.user().voice_recorder();
+//! let transport = MessagesVoiceTransport::new(client.networking_messages(), 1);
+//! let mut chat = VoiceChat::new(
+//!     transport,
+//!     client.user().voice_recorder(),
+//!     VoiceChatConfig {
+//!         sample_rate: recorder.sample_rate(),
+//!         ..Default::default()
+//!     },
+//! );
+//! let peers = [SteamId::from_raw(76561197960287930)];
+//!
+//! recorder.start();
+//! let mut output = vec![0; 480];
+//! loop {
+//!     client.run_callbacks();
+//!     if let Ok(frame) = recorder.read_compressed() {
+//!         chat.send_frame(peers, frame).ok();
+//!     }
+//!     chat.receive();
+//!     chat.mix(&mut output);
+//!     // Play `output` and show `chat.speaking()`
+//! }
+//! ```
+
+use crate::networking_messages::NetworkingMessages;
+use crate::networking_types::{NetworkingIdentity, SendFlags};
+use crate::{SteamError, SteamId, VoiceError, VoiceRecorder};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// The version of the voice packet format
+const PACKET_VERSION: u8 = 1;
+/// Version byte and sequence number
+const HEADER_LEN: usize = 3;
+
+/// Sends and receives voice packets
+pub trait VoiceTransport {
+    /// Sends a packet to a peer. Packets may be lost or arrive out of
+    /// order.
+    fn send(&self, peer: SteamId, packet: &[u8]) -> Result<(), SteamError>;
+
+    /// Appends the packets received since the last call to `packets`
+    fn receive(&self, packets: &mut Vec<(SteamId, Vec<u8>)>);
+}
+
+/// Turns encoded voice frames into PCM
+pub trait VoiceDecoder {
+    /// Decodes a frame from `peer`, appending the samples to `pcm`.
+    ///
+    /// The peer is passed so decoders that keep state between frames can
+    /// keep it per speaker.
+    fn decode(&mut self, peer: SteamId, frame: &[u8], pcm: &mut Vec<i16>)
+        -> Result<(), VoiceError>;
+}
+
+impl VoiceDecoder for VoiceRecorder {
+    fn decode(
+        &mut self,
+        _peer: SteamId,
+        frame: &[u8],
+        pcm: &mut Vec<i16>,
+    ) -> Result<(), VoiceError> {
+        pcm.extend_from_slice(self.decompress(frame)?);
+        Ok(())
+    }
+}
+
+/// A [`VoiceTransport`] sending voice as unreliable messages on a
+/// dedicated channel of [`NetworkingMessages`]
+pub struct MessagesVoiceTransport {
+    messages: NetworkingMessages,
+    channel: u32,
+}
+
+impl MessagesVoiceTransport {
+    /// Creates a transport using the given channel, which should not be
+    /// used for anything else
+    pub fn new(messages: NetworkingMessages, channel: u32) -> MessagesVoiceTransport {
+        MessagesVoiceTransport { messages, channel }
+    }
+
+    /// Returns the channel voice is sent on
+    pub fn channel(&self) -> u32 {
+        self.channel
+    }
+}
+
+impl VoiceTransport for MessagesVoiceTransport {
+    fn send(&self, peer: SteamId, packet: &[u8]) -> Result<(), SteamError> {
+        self.messages.send_message_to_user(
+            NetworkingIdentity::new_steam_id(peer),
+            SendFlags::UNRELIABLE_NO_DELAY | SendFlags::AUTO_RESTART_BROKEN_SESSION,
+            packet,
+            self.channel,
+        )
+    }
+
+    fn receive(&self, packets: &mut Vec<(SteamId, Vec<u8>)>) {
+        loop {
+            let messages = self.messages.receive_messages_on_channel(self.channel, 32);
+            let done = messages.len() < 32;
+            packets.extend(messages.iter().filter_map(|message| {
+                let peer = message.identity_peer().steam_id()?;
+                Some((peer, message.data().to_vec()))
+            }));
+            if done {
+                break;
+            }
+        }
+    }
+}
+
+type LoopbackQueues = HashMap<SteamId, VecDeque<(SteamId, Vec<u8>)>>;
+
+/// An in-memory [`VoiceTransport`] for testing, delivering packets
+/// between endpoints created from the same transport
+#[derive(Clone)]
+pub struct LoopbackTransport {
+    local: SteamId,
+    queues: Arc<Mutex<LoopbackQueues>>,
+}
+
+impl LoopbackTransport {
+    /// Creates a new network with a single endpoint
+    pub fn new(local: SteamId) -> LoopbackTransport {
+        LoopbackTransport {
+            local,
+            queues: Default::default(),
+        }
+    }
+
+    /// Creates another endpoint on the same network
+    pub fn join(&self, local: SteamId) -> LoopbackTransport {
+        LoopbackTransport {
+            local,
+            queues: self.queues.clone(),
+        }
+    }
+}
+
+impl VoiceTransport for LoopbackTransport {
+    fn send(&self, peer: SteamId, packet: &[u8]) -> Result<(), SteamError> {
+        let mut queues = self.queues.lock().unwrap();
+        queues
+            .entry(peer)
+            .or_default()
+            .push_back((self.local, packet.to_vec()));
+        Ok(())
+    }
+
+    fn receive(&self, packets: &mut Vec<(SteamId, Vec<u8>)>) {
+        let mut queues = self.queues.lock().unwrap();
+        if let Some(queue) = queues.get_mut(&self.local) {
+            packets.extend(queue.drain(..));
+        }
+    }
+}
+
+/// What a [`JitterBuffer`] plays next
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Playout {
+    /// The next frame
+    Frame(Vec<u8>),
+    /// The next frame was lost and should be concealed
+    Lost,
+}
+
+/// Reorders frames by sequence number and delays playback until enough
+/// frames are buffered to smooth out network jitter
+#[derive(Debug)]
+pub struct JitterBuffer {
+    frames: BTreeMap<u64, Vec<u8>>,
+    /// The highest sequence number received, extended to 64 bits
+    highest: Option<u64>,
+    /// The sequence number played next, if playing
+    next: Option<u64>,
+    playing: bool,
+    depth: usize,
+    max_depth: usize,
+}
+
+impl JitterBuffer {
+    /// Creates a jitter buffer that starts playing once `depth` frames
+    /// are buffered and skips ahead to reduce latency once `max_depth`
+    /// frames are buffered
+    pub fn new(depth: usize, max_depth: usize) -> JitterBuffer {
+        JitterBuffer {
+            frames: BTreeMap::new(),
+            highest: None,
+            next: None,
+            playing: false,
+            depth: depth.max(1),
+            max_depth: max_depth.max(depth.max(1)),
+        }
+    }
+
+    /// Returns the number of buffered frames
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Returns whether no frames are buffered
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Returns whether the buffer is playing, as opposed to waiting for
+    /// enough frames to be buffered
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    fn extend(&self, sequence: u16) -> u64 {
+        match self.highest {
+            // Start away from zero so sequence numbers from before the
+            // first frame don't underflow
+            None => (1 << 16) + u64::from(sequence),
+            Some(highest) => {
+                let diff = sequence.wrapping_sub(highest as u16) as i16;
+                highest.wrapping_add_signed(diff.into())
+            }
+        }
+    }
+
+    /// Adds a received frame. Duplicates and frames that arrive after
+    /// they should have been played are dropped.
+    ///
+    /// If the buffer has run dry and the frame is far from the last one,
+    /// e.g. because the sender stopped sending to this peer while the
+    /// shared sequence number kept counting, playback restarts from the
+    /// frame.
+    pub fn push(&mut self, raw_sequence: u16, frame: Vec<u8>) {
+        let mut sequence = self.extend(raw_sequence);
+        let idle = !self.playing && self.frames.is_empty();
+        if idle
+            && self
+                .next
+                .is_some_and(|next| next.abs_diff(sequence) > self.max_depth as u64)
+        {
+            self.highest = None;
+            self.next = None;
+            sequence = self.extend(raw_sequence);
+        }
+        if self.next.is_some_and(|next| sequence < next) {
+            return;
+        }
+        self.highest = Some(self.highest.map_or(sequence, |h| h.max(sequence)));
+        self.frames.entry(sequence).or_insert(frame);
+
+        while self.frames.len() > self.max_depth {
+            self.frames.pop_first();
+        }
+        if self.frames.len() == self.max_depth && self.playing {
+            // Too far behind, skip ahead to reduce the latency
+            while self.frames.len() > self.depth {
+                self.frames.pop_first();
+            }
+            self.next = self.frames.keys().next().copied();
+        }
+    }
+
+    /// Returns the next frame to play, or `None` while buffering
+    pub fn pop(&mut self) -> Option<Playout> {
+        if !self.playing {
+            if self.frames.len() < self.depth {
+                return None;
+            }
+            self.playing = true;
+            self.next = self.frames.keys().next().copied();
+        }
+        let next = self.next?;
+        if self.frames.is_empty() {
+            // Ran dry, buffer up again before resuming
+            self.playing = false;
+            return None;
+        }
+        self.next = Some(next + 1);
+        match self.frames.remove(&next) {
+            Some(frame) => Some(Playout::Frame(frame)),
+            None => Some(Playout::Lost),
+        }
+    }
+}
+
+/// The configuration of a [`VoiceChat`]
+#[derive(Clone, Debug)]
+pub struct VoiceChatConfig {
+    /// The sample rate the decoder produces
+    pub sample_rate: u32,
+    /// The number of frames buffered per speaker before playback starts
+    pub jitter_frames: usize,
+    /// The number of buffered frames at which playback skips ahead
+    pub max_jitter_frames: usize,
+    /// The number of lost frames in a row that are concealed by fading
+    /// out the previous frame, after which silence is played
+    pub concealment_frames: usize,
+    /// How long a speaker is shown as speaking after their last frame
+    pub speaking_hold: Duration,
+}
+
+impl Default for VoiceChatConfig {
+    fn default() -> Self {
+        VoiceChatConfig {
+            sample_rate: 24000,
+            jitter_frames: 3,
+            max_jitter_frames: 16,
+            concealment_frames: 3,
+            speaking_hold: Duration::from_millis(250),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Speaker {
+    jitter: JitterBuffer,
+    pcm: VecDeque<i16>,
+    last_frame: Vec<i16>,
+    decoded: Vec<i16>,
+    lost: usize,
+    gain: f32,
+    /// The number of samples the speaker is still shown as speaking for
+    speaking_left: usize,
+}
+
+impl Speaker {
+    fn new(config: &VoiceChatConfig) -> Speaker {
+        Speaker {
+            jitter: JitterBuffer::new(config.jitter_frames, config.max_jitter_frames),
+            pcm: VecDeque::new(),
+            last_frame: Vec::new(),
+            decoded: Vec::new(),
+            lost: 0,
+            gain: 1.0,
+            speaking_left: 0,
+        }
+    }
+
+    /// Decodes frames until `samples` samples are ready or the jitter
+    /// buffer runs dry
+    fn fill<D: VoiceDecoder>(
+        &mut self,
+        peer: SteamId,
+        decoder: &mut D,
+        samples: usize,
+        config: &VoiceChatConfig,
+        hold: usize,
+    ) {
+        while self.pcm.len() < samples {
+            let decoded = match self.jitter.pop() {
+                None => break,
+                Some(Playout::Frame(frame)) => {
+                    self.decoded.clear();
+                    decoder.decode(peer, &frame, &mut self.decoded).is_ok()
+                }
+                Some(Playout::Lost) => false,
+            };
+            if decoded {
+                std::mem::swap(&mut self.last_frame, &mut self.decoded);
+                self.lost = 0;
+                self.speaking_left = hold + self.last_frame.len();
+                self.pcm.extend(&self.last_frame);
+            } else if self.lost < config.concealment_frames {
+                self.lost += 1;
+                let fade = 0.5f32.powi(self.lost as i32);
+                self.pcm
+                    .extend(self.last_frame.iter().map(|&s| (s as f32 * fade) as i16));
+            } else {
+                self.pcm.resize(self.pcm.len() + self.last_frame.len(), 0);
+            }
+        }
+    }
+}
+
+/// Sends voice to other players and mixes the voice received from them,
+/// see the [module documentation](self).
+pub struct VoiceChat<T, D> {
+    transport: T,
+    decoder: D,
+    config: VoiceChatConfig,
+    sequence: u16,
+    speakers: HashMap<SteamId, Speaker>,
+    attenuation: Option<Box<dyn FnMut(SteamId) -> f32 + Send>>,
+    packet: Vec<u8>,
+    received: Vec<(SteamId, Vec<u8>)>,
+    mixed: Vec<f32>,
+}
+
+impl<T: VoiceTransport, D: VoiceDecoder> VoiceChat<T, D> {
+    pub fn new(transport: T, decoder: D, config: VoiceChatConfig) -> VoiceChat<T, D> {
+        VoiceChat {
+            transport,
+            decoder,
+            config,
+            sequence: 0,
+            speakers: HashMap::new(),
+            attenuation: None,
+            packet: Vec::new(),
+            received: Vec::new(),
+            mixed: Vec::new(),
+        }
+    }
+
+    /// Returns the transport
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    /// Returns the decoder
+    pub fn decoder_mut(&mut self) -> &mut D {
+        &mut self.decoder
+    }
+
+    /// Sends an encoded frame to the given peers.
+    ///
+    /// Every call uses the next sequence number, so calling this with no
+    /// peers skips a frame for everyone. Returns the first error, after
+    /// trying to send to all peers.
+    pub fn send_frame<I>(&mut self, peers: I, frame: &[u8]) -> Result<(), SteamError>
+    where
+        I: IntoIterator<Item = SteamId>,
+    {
+        self.packet.clear();
+        self.packet.push(PACKET_VERSION);
+        self.packet.extend_from_slice(&self.sequence.to_le_bytes());
+        self.packet.extend_from_slice(frame);
+        self.sequence = self.sequence.wrapping_add(1);
+
+        let mut result = Ok(());
+        for peer in peers {
+            let sent = self.transport.send(peer, &self.packet);
+            if result.is_ok() {
+                result = sent;
+            }
+        }
+        result
+    }
+
+    /// Receives the packets that have arrived from the transport and
+    /// buffers them for [`mix`](Self::mix)
+    pub fn receive(&mut self) {
+        self.transport.receive(&mut self.received);
+        for (peer, packet) in self.received.drain(..) {
+            if packet.len() < HEADER_LEN || packet[0] != PACKET_VERSION {
+                continue;
+            }
+            let sequence = u16::from_le_bytes([packet[1], packet[2]]);
+            self.speakers
+                .entry(peer)
+                .or_insert_with(|| Speaker::new(&self.config))
+                .jitter
+                .push(sequence, packet[HEADER_LEN..].to_vec());
+        }
+    }
+
+    /// Fills `output` with the mixed voice of all speakers, each scaled by
+    /// its gain and attenuation
+    pub fn mix(&mut self, output: &mut [i16]) {
+        let hold = (self.config.sample_rate as u128 * self.config.speaking_hold.as_millis() / 1000)
+            as usize;
+        self.mixed.clear();
+        self.mixed.resize(output.len(), 0.0);
+
+        for (&peer, speaker) in &mut self.speakers {
+            speaker.fill(peer, &mut self.decoder, output.len(), &self.config, hold);
+            speaker.speaking_left = speaker.speaking_left.saturating_sub(output.len());
+
+            let attenuation = self.attenuation.as_mut().map_or(1.0, |f| f(peer));
+            let gain = speaker.gain * attenuation;
+            let samples = output.len().min(speaker.pcm.len());
+            for (mixed, sample) in self.mixed.iter_mut().zip(speaker.pcm.drain(..samples)) {
+                *mixed += sample as f32 * gain;
+            }
+        }
+
+        for (out, mixed) in output.iter_mut().zip(&self.mixed) {
+            *out = mixed.clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+        }
+    }
+
+    /// Sets the gain of a speaker, 1.0 by default
+    pub fn set_gain(&mut self, peer: SteamId, gain: f32) {
+        self.speakers
+            .entry(peer)
+            .or_insert_with(|| Speaker::new(&self.config))
+            .gain = gain;
+    }
+
+    /// Returns the gain of a speaker
+    pub fn gain(&self, peer: SteamId) -> f32 {
+        self.speakers.get(&peer).map_or(1.0, |s| s.gain)
+    }
+
+    /// Sets a function returning the attenuation of a speaker, e.g. based
+    /// on their distance to the listener, see [`distance_attenuation`].
+    ///
+    /// The function is called once per speaker in every
+    /// [`mix`](Self::mix) and multiplied with the speaker's gain.
+    pub fn set_attenuation<F>(&mut self, attenuation: F)
+    where
+        F: FnMut(SteamId) -> f32 + Send + 'static,
+    {
+        self.attenuation = Some(Box::new(attenuation));
+    }
+
+    /// Removes the attenuation function
+    pub fn clear_attenuation(&mut self) {
+        self.attenuation = None;
+    }
+
+    /// Returns whether a speaker is currently speaking
+    pub fn is_speaking(&self, peer: SteamId) -> bool {
+        self.speakers
+            .get(&peer)
+            .is_some_and(|s| s.speaking_left > 0)
+    }
+
+    /// Returns the peers that are currently speaking
+    pub fn speaking(&self) -> impl Iterator<Item = SteamId> + '_ {
+        self.speakers
+            .iter()
+            .filter(|(_, s)| s.speaking_left > 0)
+            .map(|(&peer, _)| peer)
+    }
+
+    /// Removes a speaker and drops their buffered voice, e.g. when they
+    /// leave the game
+    pub fn remove_peer(&mut self, peer: SteamId) {
+        self.speakers.remove(&peer);
+    }
+}
+
+/// Linear distance attenuation: full volume up to `min_distance`, silent
+/// from `max_distance`
+pub fn distance_attenuation(distance: f32, min_distance: f32, max_distance: f32) -> f32 {
+    if distance <= min_distance {
+        1.0
+    } else if distance >= max_distance {
+        0.0
+    } else {
+        1.0 - (distance - min_distance) / (max_distance - min_distance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALICE: SteamId = SteamId(1);
+    const BOB: SteamId = SteamId(2);
+    const CAROL: SteamId = SteamId(3);
+
+    /// Decodes every byte of a frame into four samples of `byte * 100`
+    struct TestDecoder;
+
+    impl VoiceDecoder for TestDecoder {
+        fn decode(
+            &mut self,
+            _peer: SteamId,
+            frame: &[u8],
+            pcm: &mut Vec<i16>,
+        ) -> Result<(), VoiceError> {
+            if frame.is_empty() {
+                return Err(VoiceError::DataCorrupted);
+            }
+            pcm.extend(frame.iter().flat_map(|&b| [b as i16 * 100; 4]));
+            Ok(())
+        }
+    }
+
+    fn config() -> VoiceChatConfig {
+        VoiceChatConfig {
+            sample_rate: 1000,
+            jitter_frames: 2,
+            max_jitter_frames: 8,
+            concealment_frames: 2,
+            speaking_hold: Duration::from_millis(8),
+        }
+    }
+
+    fn pair() -> (
+        VoiceChat<LoopbackTransport, TestDecoder>,
+        VoiceChat<LoopbackTransport, TestDecoder>,
+    ) {
+        let alice = LoopbackTransport::new(ALICE);
+        let bob = alice.join(BOB);
+        (
+            VoiceChat::new(alice, TestDecoder, config()),
+            VoiceChat::new(bob, TestDecoder, config()),
+        )
+    }
+
+    #[test]
+    fn loopback() {
+        let (mut alice, mut bob) = pair();
+        for frame in 1..=3u8 {
+            alice.send_frame([BOB], &[frame]).unwrap();
+        }
+        bob.receive();
+        assert!(!bob.is_speaking(ALICE));
+
+        let mut output = [0; 8];
+        bob.mix(&mut output);
+        assert_eq!(output, [100, 100, 100, 100, 200, 200, 200, 200]);
+        assert_eq!(bob.speaking().collect::<Vec<_>>(), vec![ALICE]);
+
+        bob.mix(&mut output);
+        assert_eq!(output, [300, 300, 300, 300, 0, 0, 0, 0]);
+        assert!(bob.is_speaking(ALICE));
+
+        // The hold of 8 samples runs out
+        bob.mix(&mut output);
+        assert!(!bob.is_speaking(ALICE));
+    }
+
+    #[test]
+    fn jitter_buffer_reorders() {
+        let mut jitter = JitterBuffer::new(3, 8);
+        jitter.push(1, vec![1]);
+        jitter.push(0, vec![0]);
+        assert_eq!(jitter.pop(), None);
+        jitter.push(2, vec![2]);
+        jitter.push(2, vec![9]);
+        assert_eq!(jitter.pop(), Some(Playout::Frame(vec![0])));
+        assert_eq!(jitter.pop(), Some(Playout::Frame(vec![1])));
+        assert_eq!(jitter.pop(), Some(Playout::Frame(vec![2])));
+        assert_eq!(jitter.pop(), None);
+        assert!(!jitter.is_playing());
+
+        // Late frames are dropped
+        jitter.push(1, vec![1]);
+        assert!(jitter.is_empty());
+    }
+
+    #[test]
+    fn jitter_buffer_wraps() {
+        let mut jitter = JitterBuffer::new(2, 8);
+        jitter.push(u16::MAX, vec![0]);
+        jitter.push(0, vec![1]);
+        jitter.push(1, vec![2]);
+        assert_eq!(jitter.pop(), Some(Playout::Frame(vec![0])));
+        assert_eq!(jitter.pop(), Some(Playout::Frame(vec![1])));
+        assert_eq!(jitter.pop(), Some(Playout::Frame(vec![2])));
+    }
+
+    #[test]
+    fn jitter_buffer_skips_ahead() {
+        let mut jitter = JitterBuffer::new(2, 4);
+        jitter.push(0, vec![0]);
+        jitter.push(1, vec![1]);
+        assert_eq!(jitter.pop(), Some(Playout::Frame(vec![0])));
+        for sequence in 2..6 {
+            jitter.push(sequence, vec![sequence as u8]);
+        }
+        // Frames 1 and 2 were skipped when the buffer filled up
+        assert_eq!(jitter.len(), 3);
+        assert_eq!(jitter.pop(), Some(Playout::Frame(vec![3])));
+    }
+
+    #[test]
+    fn jitter_buffer_resyncs_after_gap() {
+        let mut jitter = JitterBuffer::new(2, 8);
+        jitter.push(0, vec![0]);
+        jitter.push(1, vec![1]);
+        assert_eq!(jitter.pop(), Some(Playout::Frame(vec![0])));
+        assert_eq!(jitter.pop(), Some(Playout::Frame(vec![1])));
+        assert_eq!(jitter.pop(), None);
+
+        // 40000 frames were sent to other peers in the meantime, which
+        // looks like going back in time with 16 bit sequence numbers
+        jitter.push(40002, vec![2]);
+        jitter.push(40003, vec![3]);
+        assert_eq!(jitter.pop(), Some(Playout::Frame(vec![2])));
+        assert_eq!(jitter.pop(), Some(Playout::Frame(vec![3])));
+
+        // Late frames are still dropped while playing
+        jitter.push(40004, vec![4]);
+        jitter.push(40001, vec![1]);
+        assert_eq!(jitter.pop(), Some(Playout::Frame(vec![4])));
+        assert_eq!(jitter.pop(), None);
+    }
+
+    #[test]
+    fn conceals_lost_frames() {
+        let (mut alice, mut bob) = pair();
+        alice.send_frame([BOB], &[4]).unwrap();
+        // Lost on the way
+        alice.send_frame([], &[5]).unwrap();
+        alice.send_frame([], &[6]).unwrap();
+        alice.send_frame([], &[7]).unwrap();
+        alice.send_frame([BOB], &[8]).unwrap();
+        bob.receive();
+
+        let mut output = [0; 20];
+        bob.mix(&mut output);
+        assert_eq!(
+            output,
+            [
+                400, 400, 400, 400, 200, 200, 200, 200, 100, 100, 100, 100, 0, 0, 0, 0, 800, 800,
+                800, 800
+            ]
+        );
+    }
+
+    #[test]
+    fn gain_and_attenuation() {
+        let alice = LoopbackTransport::new(ALICE);
+        let mut bob = VoiceChat::new(alice.join(BOB), TestDecoder, config());
+        let mut carol = VoiceChat::new(alice.join(CAROL), TestDecoder, config());
+        let mut alice = VoiceChat::new(alice, TestDecoder, config());
+        for _ in 0..2 {
+            bob.send_frame([ALICE], &[10]).unwrap();
+            carol.send_frame([ALICE], &[20]).unwrap();
+        }
+        alice.receive();
+        alice.set_gain(BOB, 0.5);
+        alice.set_attenuation(|peer| if peer == CAROL { 0.25 } else { 1.0 });
+
+        let mut output = [0; 4];
+        alice.mix(&mut output);
+        assert_eq!(output, [1000; 4]);
+        assert_eq!(alice.gain(BOB), 0.5);
+        assert_eq!(alice.gain(CAROL), 1.0);
+    }
+
+    #[test]
+    fn ignores_unknown_packets() {
+        let (alice, mut bob) = pair();
+        alice.transport().send(BOB, &[0, 0, 0, 1]).unwrap();
+        alice.transport().send(BOB, &[1]).unwrap();
+        bob.receive();
+        assert!(bob.speakers.is_empty());
+    }
+
+    #[test]
+    fn distance() {
+        assert_eq!(distance_attenuation(1.0, 2.0, 10.0), 1.0);
+        assert_eq!(distance_attenuation(6.0, 2.0, 10.0), 0.5);
+        assert_eq!(distance_attenuation(12.0, 2.0, 10.0), 0.0);
+    }
+}
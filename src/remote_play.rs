@@ -143,6 +143,28 @@ impl RemotePlaySession {
         }
     }
 
+    /// Gets the avatar of the user associated with this session.
+    ///
+    /// Returns `None` if the session has expired or the avatar has not
+    /// been downloaded yet.
+    pub fn avatar(&self, size: AvatarSize) -> Option<SteamImage> {
+        unsafe {
+            let session = self.session.raw();
+            let handle = match size {
+                AvatarSize::Small => {
+                    sys::SteamAPI_ISteamRemotePlay_GetSmallSessionAvatar(self.rp, session)
+                }
+                AvatarSize::Medium => {
+                    sys::SteamAPI_ISteamRemotePlay_GetMediumSessionAvatar(self.rp, session)
+                }
+                AvatarSize::Large => {
+                    sys::SteamAPI_ISteamRemotePlay_GetLargeSessionAvatar(self.rp, session)
+                }
+            };
+            SteamImage::from_handle(sys::SteamAPI_SteamUtils_v010(), handle)
+        }
+    }
+
     /// Invites a friend to join the game using Remote Play Together
     pub fn invite(&self, friend: SteamId) -> bool {
         unsafe {
@@ -110,6 +110,79 @@ impl RemoteStorage {
             name: CString::new(name).unwrap(),
         }
     }
+
+    /// Downloads a file shared by another user, e.g. one attached to a
+    /// [`LeaderboardEntry`].
+    ///
+    /// Downloads with a lower `priority` value are started first, with
+    /// `0` starting immediately.
+    pub fn download_ugc<F>(&self, ugc: UGCHandle, priority: u32, cb: F)
+    where
+        F: FnOnce(Result<DownloadedUGC, SteamError>) + 'static + Send,
+    {
+        unsafe {
+            let api_call = sys::SteamAPI_ISteamRemoteStorage_UGCDownload(self.rs, ugc.0, priority);
+            let rs = self.rs as isize;
+            register_call_result::<sys::RemoteStorageDownloadUGCResult_t, _>(
+                &self.inner,
+                api_call,
+                move |v, io_error| {
+                    if io_error {
+                        cb(Err(SteamError::IOFailure));
+                        return;
+                    }
+                    if v.m_eResult != sys::EResult::k_EResultOK {
+                        cb(Err(v.m_eResult.into()));
+                        return;
+                    }
+
+                    let size = v.m_nSizeInBytes.max(0);
+                    let mut data = vec![0u8; size as usize];
+                    let read = sys::SteamAPI_ISteamRemoteStorage_UGCRead(
+                        rs as *mut _,
+                        v.m_hFile,
+                        data.as_mut_ptr() as *mut _,
+                        size,
+                        0,
+                        sys::EUGCReadAction::k_EUGCRead_Close,
+                    );
+                    if read != size {
+                        cb(Err(SteamError::IOFailure));
+                        return;
+                    }
+
+                    cb(Ok(DownloadedUGC {
+                        file_name: CStr::from_ptr(v.m_pchFileName.as_ptr())
+                            .to_string_lossy()
+                            .into_owned(),
+                        owner: SteamId(v.m_ulSteamIDOwner),
+                        data,
+                    }))
+                },
+            )
+        }
+    }
+}
+
+/// A file shared with [`SteamFile::share`]
+#[derive(Clone, Copy, Debug, Ord, PartialOrd, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct UGCHandle(pub u64);
+impl From<u64> for UGCHandle {
+    fn from(handle: u64) -> Self {
+        UGCHandle(handle)
+    }
+}
+
+/// A shared file downloaded with [`RemoteStorage::download_ugc`]
+#[derive(Clone, Debug)]
+pub struct DownloadedUGC {
+    /// The name of the file when it was shared
+    pub file_name: String,
+    /// The user who shared the file
+    pub owner: SteamId,
+    /// The contents of the file
+    pub data: Vec<u8>,
 }
 
 bitflags! {
@@ -210,7 +283,7 @@ impl SteamFile {
         }
     }
 
-    pub fn share(&self, cb: impl FnOnce(Result<u64, SteamError>) + 'static + Send) {
+    pub fn share(&self, cb: impl FnOnce(Result<UGCHandle, SteamError>) + 'static + Send) {
         let api_call =
             unsafe { sys::SteamAPI_ISteamRemoteStorage_FileShare(self.rs, self.name.as_ptr()) };
         unsafe {
@@ -227,7 +300,7 @@ impl SteamFile {
                         return;
                     }
 
-                    cb(Ok(v.m_hFile))
+                    cb(Ok(UGCHandle(v.m_hFile)))
                 },
             )
         }
@@ -246,6 +246,18 @@ impl Server {
         }
     }
 
+    /// Retrieve an authentication session ticket that is cancelled
+    /// when the returned guard is dropped.
+    ///
+    /// See `authentication_session_ticket` for details.
+    pub fn authentication_session_ticket_guard(
+        &self,
+        network_identity: NetworkingIdentity,
+    ) -> AuthTicketGuard {
+        let (ticket, data) = self.authentication_session_ticket(network_identity);
+        AuthTicketGuard::from_server(self.server, self.inner.clone(), ticket, data)
+    }
+
     /// Cancels an authentication session ticket received from
     /// `authentication_session_ticket`.
     ///
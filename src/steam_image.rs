@@ -0,0 +1,191 @@
+use super::*;
+
+/// An image in RGBA format that was loaded from the Steam image cache.
+///
+/// Avatars, achievement icons and other images handed out by Steam are
+/// referenced by an image handle; this type holds the decoded pixel data
+/// of such a handle.
+#[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(try_from = "RawSteamImage")
+)]
+pub struct SteamImage {
+    width: u32,
+    height: u32,
+    rgba: Vec<u8>,
+}
+
+/// A deserialized [`SteamImage`] whose size has not been checked yet
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct RawSteamImage {
+    width: u32,
+    height: u32,
+    rgba: Vec<u8>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<RawSteamImage> for SteamImage {
+    type Error = &'static str;
+
+    fn try_from(raw: RawSteamImage) -> Result<Self, Self::Error> {
+        SteamImage::from_rgba(raw.width, raw.height, raw.rgba)
+            .ok_or("image data does not match its dimensions")
+    }
+}
+
+impl SteamImage {
+    /// Creates an image from raw RGBA data.
+    ///
+    /// Returns `None` if the length of `rgba` does not match the
+    /// dimensions of the image.
+    pub fn from_rgba(width: u32, height: u32, rgba: Vec<u8>) -> Option<SteamImage> {
+        if rgba.len() as u64 != u64::from(width) * u64::from(height) * 4 {
+            return None;
+        }
+        Some(SteamImage {
+            width,
+            height,
+            rgba,
+        })
+    }
+
+    /// Reads the image behind a steam image handle.
+    ///
+    /// Returns `None` if the handle is invalid (`0`) or still loading (`-1`).
+    pub(crate) unsafe fn from_handle(
+        utils: *mut sys::ISteamUtils,
+        handle: i32,
+    ) -> Option<SteamImage> {
+        if handle <= 0 {
+            return None;
+        }
+        let mut width = 0;
+        let mut height = 0;
+        if !sys::SteamAPI_ISteamUtils_GetImageSize(utils, handle, &mut width, &mut height) {
+            return None;
+        }
+        let size = (width * height * 4) as usize;
+        let mut rgba = vec![0; size];
+        if !sys::SteamAPI_ISteamUtils_GetImageRGBA(utils, handle, rgba.as_mut_ptr(), size as i32) {
+            return None;
+        }
+        Some(SteamImage {
+            width,
+            height,
+            rgba,
+        })
+    }
+
+    /// Returns the width of the image in pixels
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Returns the height of the image in pixels
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the pixel data of the image, 4 bytes per pixel in RGBA order
+    pub fn rgba(&self) -> &[u8] {
+        &self.rgba
+    }
+
+    /// Returns the pixel data of the image, consuming it
+    pub fn into_rgba(self) -> Vec<u8> {
+        self.rgba
+    }
+
+    /// Converts the image into an `image` crate buffer. Returns `None`
+    /// if the image is too large for the `image` crate.
+    #[cfg(feature = "image")]
+    pub fn to_image(&self) -> Option<image::RgbaImage> {
+        self.clone().try_into().ok()
+    }
+}
+
+impl Debug for SteamImage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SteamImage")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Fails if the `image` crate cannot hold an image of this size, returning
+/// the image unchanged
+#[cfg(feature = "image")]
+impl TryFrom<SteamImage> for image::RgbaImage {
+    type Error = SteamImage;
+
+    fn try_from(img: SteamImage) -> Result<Self, Self::Error> {
+        let len = (img.width as usize)
+            .checked_mul(img.height as usize)
+            .and_then(|pixels| pixels.checked_mul(4));
+        if len != Some(img.rgba.len()) {
+            return Err(img);
+        }
+        Ok(image::RgbaImage::from_vec(img.width, img.height, img.rgba)
+            .expect("buffer length was checked"))
+    }
+}
+
+/// The size of a user avatar
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum AvatarSize {
+    /// 32x32 pixels
+    Small,
+    /// 64x64 pixels
+    Medium,
+    /// 184x184 pixels
+    Large,
+}
+
+impl AvatarSize {
+    /// Returns the width and height of avatars of this size in pixels
+    pub fn dimensions(self) -> u32 {
+        match self {
+            AvatarSize::Small => 32,
+            AvatarSize::Medium => 64,
+            AvatarSize::Large => 184,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_rgba_checks_length() {
+        assert!(SteamImage::from_rgba(2, 2, vec![0; 16]).is_some());
+        assert!(SteamImage::from_rgba(2, 2, vec![0; 15]).is_none());
+        assert!(SteamImage::from_rgba(0, 0, vec![]).is_some());
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn converts_to_image_buffer() {
+        let img = SteamImage::from_rgba(1, 2, vec![1, 2, 3, 4, 5, 6, 7, 8]).unwrap();
+        let buf = img.to_image().unwrap();
+        assert_eq!(buf.dimensions(), (1, 2));
+        assert_eq!(buf.get_pixel(0, 1).0, [5, 6, 7, 8]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialize_checks_length() {
+        let img: SteamImage =
+            serde_json::from_str(r#"{"width":1,"height":1,"rgba":[1,2,3,4]}"#).unwrap();
+        assert_eq!(img.rgba(), [1, 2, 3, 4]);
+        assert!(
+            serde_json::from_str::<SteamImage>(r#"{"width":2,"height":2,"rgba":[1,2,3,4]}"#)
+                .is_err()
+        );
+    }
+}
@@ -1,3 +1,12 @@
+mod account;
+mod auth_session;
+mod microtxn;
+mod voice;
+
+pub use self::account::*;
+pub use self::auth_session::*;
+pub use self::microtxn::*;
+pub use self::voice::*;
 use super::*;
 use crate::networking_types::NetworkingIdentity;
 #[cfg(test)]
@@ -61,6 +70,18 @@ impl User {
         }
     }
 
+    /// Retrieve an authentication session ticket that is cancelled
+    /// when the returned guard is dropped.
+    ///
+    /// See `authentication_session_ticket` for details.
+    pub fn authentication_session_ticket_guard(
+        &self,
+        network_identity: NetworkingIdentity,
+    ) -> AuthTicketGuard {
+        let (ticket, data) = self.authentication_session_ticket(network_identity);
+        AuthTicketGuard::from_user(self, ticket, data)
+    }
+
     /// Cancels an authentication session ticket received from
     /// `authentication_session_ticket`.
     ///
@@ -149,6 +170,22 @@ impl User {
         }
     }
 
+    /// Retrieve an authentication ticket for the Web API that is
+    /// cancelled when the returned guard is dropped.
+    ///
+    /// See `authentication_session_ticket_for_webapi` for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `identity` contains a null byte.
+    pub fn authentication_session_ticket_for_webapi_guard(
+        &self,
+        identity: &str,
+    ) -> AuthTicketGuard {
+        let ticket = self.authentication_session_ticket_for_webapi(identity);
+        AuthTicketGuard::from_user(self, ticket, Vec::new())
+    }
+
     /// Checks if the user owns a piece of DLC specified by app id.
     ///
     /// This can only be called after authenticating
@@ -172,6 +209,75 @@ impl User {
             }
         }
     }
+
+    /// Returns a [`VoiceRecorder`] for recording and decompressing voice
+    /// chat
+    pub fn voice_recorder(&self) -> VoiceRecorder {
+        VoiceRecorder::new(self)
+    }
+
+    /// Requests an encrypted app ticket from the Steam servers, which can
+    /// be sent to a game server and decrypted there with the app's
+    /// encrypted ticket key.
+    ///
+    /// Up to 128 bytes of `user_data` can be included in the ticket. Steam
+    /// limits requests to one per minute, further requests fail with
+    /// `SteamError::LimitExceeded`.
+    ///
+    /// The callback receives the ticket once it has been retrieved, see
+    /// also [`encrypted_app_ticket`](Self::encrypted_app_ticket).
+    pub fn request_encrypted_app_ticket<F>(&self, user_data: Option<&[u8]>, cb: F)
+    where
+        F: FnOnce(SResult<Vec<u8>>) + 'static + Send,
+    {
+        let mut user_data = user_data.map(<[u8]>::to_vec).unwrap_or_default();
+        let user = self.user as isize;
+        unsafe {
+            let api_call = sys::SteamAPI_ISteamUser_RequestEncryptedAppTicket(
+                self.user,
+                if user_data.is_empty() {
+                    std::ptr::null_mut()
+                } else {
+                    user_data.as_mut_ptr().cast()
+                },
+                user_data.len() as _,
+            );
+            register_call_result::<sys::EncryptedAppTicketResponse_t, _>(
+                &self._inner,
+                api_call,
+                move |v, io_error| {
+                    cb(if io_error {
+                        Err(SteamError::IOFailure)
+                    } else {
+                        crate::to_steam_result(v.m_eResult).and_then(|_| {
+                            get_encrypted_app_ticket(user as *mut _).ok_or(SteamError::Generic)
+                        })
+                    })
+                },
+            );
+        }
+    }
+
+    /// Returns the encrypted app ticket of the last successful
+    /// [`request_encrypted_app_ticket`](Self::request_encrypted_app_ticket)
+    pub fn encrypted_app_ticket(&self) -> Option<Vec<u8>> {
+        unsafe { get_encrypted_app_ticket(self.user) }
+    }
+}
+
+unsafe fn get_encrypted_app_ticket(user: *mut sys::ISteamUser) -> Option<Vec<u8>> {
+    let mut ticket = vec![0; 2048];
+    let mut ticket_len = 0;
+    if !sys::SteamAPI_ISteamUser_GetEncryptedAppTicket(
+        user,
+        ticket.as_mut_ptr().cast(),
+        ticket.len() as _,
+        &mut ticket_len,
+    ) {
+        return None;
+    }
+    ticket.truncate(ticket_len as usize);
+    Some(ticket)
 }
 
 /// Errors from `begin_authentication_session`
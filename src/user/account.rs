@@ -0,0 +1,331 @@
+use super::*;
+use std::path::PathBuf;
+
+bitflags! {
+    /// Reasons the user is not allowed to use the Steam Community Market,
+    /// returned by [`User::market_eligibility`]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    #[repr(C)]
+    #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+    pub struct MarketNotAllowedReasons: u32 {
+        const TEMPORARY_FAILURE = sys::EMarketNotAllowedReasonFlags::k_EMarketNotAllowedReason_TemporaryFailure.0 as _;
+        const ACCOUNT_DISABLED = sys::EMarketNotAllowedReasonFlags::k_EMarketNotAllowedReason_AccountDisabled.0 as _;
+        const ACCOUNT_LOCKED_DOWN = sys::EMarketNotAllowedReasonFlags::k_EMarketNotAllowedReason_AccountLockedDown.0 as _;
+        const ACCOUNT_LIMITED = sys::EMarketNotAllowedReasonFlags::k_EMarketNotAllowedReason_AccountLimited.0 as _;
+        const TRADE_BANNED = sys::EMarketNotAllowedReasonFlags::k_EMarketNotAllowedReason_TradeBanned.0 as _;
+        const ACCOUNT_NOT_TRUSTED = sys::EMarketNotAllowedReasonFlags::k_EMarketNotAllowedReason_AccountNotTrusted.0 as _;
+        const STEAM_GUARD_NOT_ENABLED = sys::EMarketNotAllowedReasonFlags::k_EMarketNotAllowedReason_SteamGuardNotEnabled.0 as _;
+        const STEAM_GUARD_ONLY_RECENTLY_ENABLED = sys::EMarketNotAllowedReasonFlags::k_EMarketNotAllowedReason_SteamGuardOnlyRecentlyEnabled.0 as _;
+        const RECENT_PASSWORD_RESET = sys::EMarketNotAllowedReasonFlags::k_EMarketNotAllowedReason_RecentPasswordReset.0 as _;
+        const NEW_PAYMENT_METHOD = sys::EMarketNotAllowedReasonFlags::k_EMarketNotAllowedReason_NewPaymentMethod.0 as _;
+        const INVALID_COOKIE = sys::EMarketNotAllowedReasonFlags::k_EMarketNotAllowedReason_InvalidCookie.0 as _;
+        const USING_NEW_DEVICE = sys::EMarketNotAllowedReasonFlags::k_EMarketNotAllowedReason_UsingNewDevice.0 as _;
+        const RECENT_SELF_REFUND = sys::EMarketNotAllowedReasonFlags::k_EMarketNotAllowedReason_RecentSelfRefund.0 as _;
+        const NEW_PAYMENT_METHOD_CANNOT_BE_VERIFIED = sys::EMarketNotAllowedReasonFlags::k_EMarketNotAllowedReason_NewPaymentMethodCannotBeVerified.0 as _;
+        const NO_RECENT_PURCHASES = sys::EMarketNotAllowedReasonFlags::k_EMarketNotAllowedReason_NoRecentPurchases.0 as _;
+        const ACCEPTED_WALLET_GIFT = sys::EMarketNotAllowedReasonFlags::k_EMarketNotAllowedReason_AcceptedWalletGift.0 as _;
+        const TRADE_COOLDOWN = sys::EMarketNotAllowedReasonFlags::k_EMarketNotAllowedReason_TradeCooldown.0 as _;
+    }
+}
+
+/// Whether the user can use the Steam Community Market
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MarketEligibility {
+    pub allowed: bool,
+    pub not_allowed_reasons: MarketNotAllowedReasons,
+    /// When the user will be allowed to use the market, in seconds since
+    /// the unix epoch, or 0 if unknown
+    pub allowed_at_time: u32,
+    /// The number of days the user must have Steam Guard enabled for
+    pub steam_guard_required_days: u32,
+    /// The number of days a new device must wait before it can use the
+    /// market
+    pub new_device_cooldown_days: u32,
+}
+
+/// How much progress the user earns under anti-indulgence rules
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DurationControlProgress {
+    /// Full progress
+    Full,
+    /// Half progress, after three hours of play
+    Half,
+    /// No progress, after five hours of play
+    None,
+    /// The user must exit within the hour, after three hours of play
+    ExitSoon3h,
+    /// The user must exit within the hour, after five hours of play
+    ExitSoon5h,
+    /// The user must exit within the hour because of the night curfew
+    ExitSoonNight,
+}
+
+impl From<sys::EDurationControlProgress> for DurationControlProgress {
+    fn from(progress: sys::EDurationControlProgress) -> Self {
+        use sys::EDurationControlProgress::*;
+        match progress {
+            k_EDurationControlProgress_Half => DurationControlProgress::Half,
+            k_EDurationControlProgress_None => DurationControlProgress::None,
+            k_EDurationControl_ExitSoon_3h => DurationControlProgress::ExitSoon3h,
+            k_EDurationControl_ExitSoon_5h => DurationControlProgress::ExitSoon5h,
+            k_EDurationControl_ExitSoon_Night => DurationControlProgress::ExitSoonNight,
+            _ => DurationControlProgress::Full,
+        }
+    }
+}
+
+/// A notification the game should show the user under anti-indulgence
+/// rules
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DurationControlNotification {
+    /// Nothing needs to be shown
+    None,
+    /// The user has played for an hour
+    OneHour,
+    /// The user has played for three hours
+    ThreeHours,
+    /// The user now earns half progress
+    HalfProgress,
+    /// The user now earns no progress
+    NoProgress,
+    /// The user must exit within the hour, after three hours of play
+    ExitSoon3h,
+    /// The user must exit within the hour, after five hours of play
+    ExitSoon5h,
+    /// The user must exit within the hour because of the night curfew
+    ExitSoonNight,
+}
+
+impl From<sys::EDurationControlNotification> for DurationControlNotification {
+    fn from(notification: sys::EDurationControlNotification) -> Self {
+        use sys::EDurationControlNotification::*;
+        match notification {
+            k_EDurationControlNotification_1Hour => DurationControlNotification::OneHour,
+            k_EDurationControlNotification_3Hours => DurationControlNotification::ThreeHours,
+            k_EDurationControlNotification_HalfProgress => {
+                DurationControlNotification::HalfProgress
+            }
+            k_EDurationControlNotification_NoProgress => DurationControlNotification::NoProgress,
+            k_EDurationControlNotification_ExitSoon_3h => DurationControlNotification::ExitSoon3h,
+            k_EDurationControlNotification_ExitSoon_5h => DurationControlNotification::ExitSoon5h,
+            k_EDurationControlNotification_ExitSoon_Night => {
+                DurationControlNotification::ExitSoonNight
+            }
+            _ => DurationControlNotification::None,
+        }
+    }
+}
+
+/// Whether the game is being played offline or online, which affects how
+/// play time is counted under anti-indulgence rules
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DurationControlOnlineState {
+    Offline,
+    Online,
+    /// Online in a state where the game cannot be exited without
+    /// penalizing other players, e.g. a competitive match
+    OnlineHighPriority,
+}
+
+impl From<DurationControlOnlineState> for sys::EDurationControlOnlineState {
+    fn from(state: DurationControlOnlineState) -> Self {
+        match state {
+            DurationControlOnlineState::Offline => {
+                sys::EDurationControlOnlineState::k_EDurationControlOnlineState_Offline
+            }
+            DurationControlOnlineState::Online => {
+                sys::EDurationControlOnlineState::k_EDurationControlOnlineState_Online
+            }
+            DurationControlOnlineState::OnlineHighPriority => {
+                sys::EDurationControlOnlineState::k_EDurationControlOnlineState_OnlineHighPri
+            }
+        }
+    }
+}
+
+/// The anti-indulgence play time state of the user.
+///
+/// Sent by [`User::duration_control`] and as a callback whenever the
+/// state changes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DurationControl {
+    pub app_id: AppId,
+    /// Whether anti-indulgence rules apply to the user
+    pub applicable: bool,
+    /// The play time in the last five hours, in seconds
+    pub seconds_last_5h: i32,
+    pub progress: DurationControlProgress,
+    pub notification: DurationControlNotification,
+    /// The play time today, in seconds
+    pub seconds_today: i32,
+    /// The play time remaining until the user must exit, in seconds
+    pub seconds_remaining: i32,
+}
+
+impl From<&sys::DurationControl_t> for DurationControl {
+    fn from(cb: &sys::DurationControl_t) -> Self {
+        DurationControl {
+            app_id: AppId(cb.m_appid),
+            applicable: cb.m_bApplicable,
+            seconds_last_5h: cb.m_csecsLast5h,
+            progress: cb.m_progress.into(),
+            notification: cb.m_notification.into(),
+            seconds_today: cb.m_csecsToday,
+            seconds_remaining: cb.m_csecsRemaining,
+        }
+    }
+}
+
+impl_callback!(cb: DurationControl_t => DurationControl {
+    DurationControl::from(&cb)
+});
+
+impl User {
+    /// Returns the folder the Steam client stores data for the current
+    /// user and game in
+    pub fn user_data_folder(&self) -> Option<PathBuf> {
+        let mut buffer = vec![0u8; 4096];
+        unsafe {
+            if !sys::SteamAPI_ISteamUser_GetUserDataFolder(
+                self.user,
+                buffer.as_mut_ptr().cast(),
+                buffer.len() as _,
+            ) {
+                return None;
+            }
+            let folder = CStr::from_ptr(buffer.as_ptr().cast());
+            Some(PathBuf::from(folder.to_string_lossy().into_owned()))
+        }
+    }
+
+    /// Returns whether the user appears to be behind a NAT device
+    pub fn is_behind_nat(&self) -> bool {
+        unsafe { sys::SteamAPI_ISteamUser_BIsBehindNAT(self.user) }
+    }
+
+    /// Returns the level of the game's badge for the user, or 0 if they
+    /// have not crafted it.
+    ///
+    /// `series` is 1 for games with a single badge series.
+    pub fn game_badge_level(&self, series: i32, foil: bool) -> u32 {
+        unsafe { sys::SteamAPI_ISteamUser_GetGameBadgeLevel(self.user, series, foil) as u32 }
+    }
+
+    /// Returns whether the user has a verified phone number
+    pub fn is_phone_verified(&self) -> bool {
+        unsafe { sys::SteamAPI_ISteamUser_BIsPhoneVerified(self.user) }
+    }
+
+    /// Returns whether the user has the Steam Guard mobile authenticator
+    /// enabled
+    pub fn is_two_factor_enabled(&self) -> bool {
+        unsafe { sys::SteamAPI_ISteamUser_BIsTwoFactorEnabled(self.user) }
+    }
+
+    /// Returns whether the user's phone number is used to uniquely
+    /// identify them
+    pub fn is_phone_identifying(&self) -> bool {
+        unsafe { sys::SteamAPI_ISteamUser_BIsPhoneIdentifying(self.user) }
+    }
+
+    /// Returns whether the user's phone number is awaiting
+    /// (re)verification
+    pub fn is_phone_requiring_verification(&self) -> bool {
+        unsafe { sys::SteamAPI_ISteamUser_BIsPhoneRequiringVerification(self.user) }
+    }
+
+    /// Requests whether the user is allowed to use the Steam Community
+    /// Market, e.g. to gate trading features
+    pub fn market_eligibility<F>(&self, cb: F)
+    where
+        F: FnOnce(SResult<MarketEligibility>) + 'static + Send,
+    {
+        unsafe {
+            let api_call = sys::SteamAPI_ISteamUser_GetMarketEligibility(self.user);
+            register_call_result::<sys::MarketEligibilityResponse_t, _>(
+                &self._inner,
+                api_call,
+                move |v, io_error| {
+                    cb(if io_error {
+                        Err(SteamError::IOFailure)
+                    } else {
+                        Ok(MarketEligibility {
+                            allowed: v.m_bAllowed,
+                            not_allowed_reasons: MarketNotAllowedReasons::from_bits_truncate(
+                                v.m_eNotAllowedReason.0,
+                            ),
+                            allowed_at_time: v.m_rtAllowedAtTime,
+                            steam_guard_required_days: v.m_cdaySteamGuardRequiredDays.max(0) as u32,
+                            new_device_cooldown_days: v.m_cdayNewDeviceCooldown.max(0) as u32,
+                        })
+                    })
+                },
+            );
+        }
+    }
+
+    /// Requests the anti-indulgence play time state of the user.
+    ///
+    /// Steam also sends [`DurationControl`] as a callback when the state
+    /// changes, which should be registered to show the notifications.
+    pub fn duration_control<F>(&self, cb: F)
+    where
+        F: FnOnce(SResult<DurationControl>) + 'static + Send,
+    {
+        unsafe {
+            let api_call = sys::SteamAPI_ISteamUser_GetDurationControl(self.user);
+            register_call_result::<sys::DurationControl_t, _>(
+                &self._inner,
+                api_call,
+                move |v, io_error| {
+                    cb(if io_error {
+                        Err(SteamError::IOFailure)
+                    } else {
+                        crate::to_steam_result(v.m_eResult).map(|_| DurationControl::from(v))
+                    })
+                },
+            );
+        }
+    }
+
+    /// Tells Steam whether the game is being played online, so play time
+    /// is counted correctly under anti-indulgence rules.
+    ///
+    /// Returns `false` if the state could not be set.
+    pub fn set_duration_control_online_state(&self, state: DurationControlOnlineState) -> bool {
+        unsafe { sys::SteamAPI_ISteamUser_BSetDurationControlOnlineState(self.user, state.into()) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duration_control_from_sys() {
+        let raw = sys::DurationControl_t {
+            m_eResult: sys::EResult::k_EResultOK,
+            m_appid: 480,
+            m_bApplicable: true,
+            m_csecsLast5h: 3 * 60 * 60,
+            m_progress: sys::EDurationControlProgress::k_EDurationControlProgress_Half,
+            m_notification:
+                sys::EDurationControlNotification::k_EDurationControlNotification_3Hours,
+            m_csecsToday: 4 * 60 * 60,
+            m_csecsRemaining: 60 * 60,
+        };
+        let control = DurationControl::from(&raw);
+        assert_eq!(control.app_id, AppId(480));
+        assert_eq!(control.progress, DurationControlProgress::Half);
+        assert_eq!(
+            control.notification,
+            DurationControlNotification::ThreeHours
+        );
+        assert_eq!(control.seconds_remaining, 3600);
+    }
+}
@@ -0,0 +1,554 @@
+use super::*;
+use std::time::{Duration, Instant};
+
+enum TicketIssuer {
+    User(*mut sys::ISteamUser),
+    Server(*mut sys::ISteamGameServer),
+}
+
+/// An authentication session ticket that is cancelled when dropped.
+///
+/// Created with [`User::authentication_session_ticket_guard`] or
+/// [`Server::authentication_session_ticket_guard`].
+pub struct AuthTicketGuard {
+    ticket: AuthTicket,
+    data: Vec<u8>,
+    issuer: TicketIssuer,
+    _inner: Arc<Inner>,
+}
+
+unsafe impl Send for AuthTicketGuard {}
+unsafe impl Sync for AuthTicketGuard {}
+
+impl AuthTicketGuard {
+    pub(crate) fn from_user(user: &User, ticket: AuthTicket, data: Vec<u8>) -> AuthTicketGuard {
+        AuthTicketGuard {
+            ticket,
+            data,
+            issuer: TicketIssuer::User(user.user),
+            _inner: user._inner.clone(),
+        }
+    }
+
+    pub(crate) fn from_server(
+        server: *mut sys::ISteamGameServer,
+        inner: Arc<Inner>,
+        ticket: AuthTicket,
+        data: Vec<u8>,
+    ) -> AuthTicketGuard {
+        AuthTicketGuard {
+            ticket,
+            data,
+            issuer: TicketIssuer::Server(server),
+            _inner: inner,
+        }
+    }
+
+    /// Returns the handle of the ticket
+    pub fn ticket(&self) -> AuthTicket {
+        self.ticket
+    }
+
+    /// Returns the ticket data to send to the entity verifying it.
+    ///
+    /// This is empty for web API tickets, which are delivered by the
+    /// [`TicketForWebApiResponse`] callback.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Cancels the ticket, same as dropping it
+    pub fn cancel(self) {}
+}
+
+impl Drop for AuthTicketGuard {
+    fn drop(&mut self) {
+        unsafe {
+            match self.issuer {
+                TicketIssuer::User(user) => {
+                    sys::SteamAPI_ISteamUser_CancelAuthTicket(user, self.ticket.0)
+                }
+                TicketIssuer::Server(server) => {
+                    sys::SteamAPI_ISteamGameServer_CancelAuthTicket(server, self.ticket.0)
+                }
+            }
+        }
+    }
+}
+
+impl Debug for AuthTicketGuard {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AuthTicketGuard")
+            .field("ticket", &self.ticket)
+            .field("len", &self.data.len())
+            .finish()
+    }
+}
+
+/// Something that can validate authentication session tickets, either
+/// a [`User`] for peer to peer games or a [`Server`]
+pub trait AuthSessionProvider {
+    fn begin_authentication_session(
+        &self,
+        user: SteamId,
+        ticket: &[u8],
+    ) -> Result<(), AuthSessionError>;
+
+    fn end_authentication_session(&self, user: SteamId);
+}
+
+impl AuthSessionProvider for User {
+    fn begin_authentication_session(
+        &self,
+        user: SteamId,
+        ticket: &[u8],
+    ) -> Result<(), AuthSessionError> {
+        User::begin_authentication_session(self, user, ticket)
+    }
+
+    fn end_authentication_session(&self, user: SteamId) {
+        User::end_authentication_session(self, user)
+    }
+}
+
+impl AuthSessionProvider for Server {
+    fn begin_authentication_session(
+        &self,
+        user: SteamId,
+        ticket: &[u8],
+    ) -> Result<(), AuthSessionError> {
+        Server::begin_authentication_session(self, user, ticket)
+    }
+
+    fn end_authentication_session(&self, user: SteamId) {
+        Server::end_authentication_session(self, user)
+    }
+}
+
+/// Why a player failed authentication
+#[derive(Clone, Debug)]
+pub enum AuthSessionDenial {
+    /// Steam rejected the ticket
+    Validation(AuthSessionValidateError),
+    /// The server denied the player with [`GSClientDeny`]
+    Denied(DenyReason, String),
+    /// The server asked to kick the player with [`GSClientKick`]
+    Kicked(DenyReason),
+    /// Steam did not respond in time
+    TimedOut,
+}
+
+/// The state of a player's authentication session
+#[derive(Clone, Debug)]
+pub enum AuthSessionState {
+    /// The ticket was accepted, waiting for Steam to validate it
+    Pending,
+    /// The ticket was validated. `owner` differs from the player if the
+    /// game is borrowed through Family Sharing.
+    Validated { owner: SteamId },
+    /// The player failed authentication and should be disconnected
+    Denied(AuthSessionDenial),
+    /// The session was ended with [`AuthSessionManager::disconnect`]
+    Ended,
+}
+
+/// A change of a player's [`AuthSessionState`], returned by
+/// [`AuthSessionManager::poll`]
+#[derive(Clone, Debug)]
+pub struct AuthSessionChange {
+    pub user: SteamId,
+    pub state: AuthSessionState,
+}
+
+#[derive(Debug)]
+struct Session {
+    state: AuthSessionState,
+    started: Instant,
+}
+
+/// Tracks the authentication sessions of connected players.
+///
+/// Each player starts as [`Pending`](AuthSessionState::Pending) when
+/// their ticket is passed to [`begin`](Self::begin), becomes
+/// [`Validated`](AuthSessionState::Validated) or
+/// [`Denied`](AuthSessionState::Denied) once Steam responds, and
+/// [`Ended`](AuthSessionState::Ended) once they
+/// [`disconnect`](Self::disconnect). Sessions that stay pending for
+/// longer than the timeout are denied. The Steam session is always
+/// ended on disconnect, and for all remaining players when the manager
+/// is dropped.
+///
+/// The manager is a [`CallbackStateMachine`]. On a client only
+/// [`ValidateAuthTicketResponse`] is sent, while a game server also gets
+/// [`GSClientApprove`], [`GSClientDeny`] and [`GSClientKick`] from
+/// [`Server::process_callbacks`].
+pub struct AuthSessionManager<P: AuthSessionProvider> {
+    provider: P,
+    timeout: Duration,
+    sessions: HashMap<SteamId, Session>,
+    changes: Vec<AuthSessionChange>,
+}
+
+impl<P: AuthSessionProvider> AuthSessionManager<P> {
+    /// Creates a manager with a timeout of 30 seconds
+    pub fn new(provider: P) -> Self {
+        AuthSessionManager {
+            provider,
+            timeout: Duration::from_secs(30),
+            sessions: HashMap::new(),
+            changes: Vec::new(),
+        }
+    }
+
+    /// Sets how long a session may stay pending before it is denied
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Returns the provider the sessions are validated with
+    pub fn provider(&self) -> &P {
+        &self.provider
+    }
+
+    /// Starts validating the ticket of a connecting player.
+    ///
+    /// If the player already has a session, e.g. because they reconnected,
+    /// the old session is ended first.
+    pub fn begin(&mut self, user: SteamId, ticket: &[u8]) -> Result<(), AuthSessionError> {
+        self.begin_at(user, ticket, Instant::now())
+    }
+
+    fn begin_at(
+        &mut self,
+        user: SteamId,
+        ticket: &[u8],
+        now: Instant,
+    ) -> Result<(), AuthSessionError> {
+        if self.sessions.remove(&user).is_some() {
+            self.provider.end_authentication_session(user);
+        }
+        self.provider.begin_authentication_session(user, ticket)?;
+        self.sessions.insert(
+            user,
+            Session {
+                state: AuthSessionState::Pending,
+                started: now,
+            },
+        );
+        self.changes.push(AuthSessionChange {
+            user,
+            state: AuthSessionState::Pending,
+        });
+        Ok(())
+    }
+
+    /// Ends the session of a player, e.g. when they disconnect
+    pub fn disconnect(&mut self, user: SteamId) {
+        if self.sessions.remove(&user).is_some() {
+            self.provider.end_authentication_session(user);
+            self.changes.push(AuthSessionChange {
+                user,
+                state: AuthSessionState::Ended,
+            });
+        }
+    }
+
+    /// Returns the state of a player's session, or `None` if they have
+    /// no session
+    pub fn state(&self, user: SteamId) -> Option<&AuthSessionState> {
+        self.sessions.get(&user).map(|s| &s.state)
+    }
+
+    /// Returns whether the player has been validated
+    pub fn is_validated(&self, user: SteamId) -> bool {
+        matches!(self.state(user), Some(AuthSessionState::Validated { .. }))
+    }
+
+    /// Returns the players with a session
+    pub fn users(&self) -> impl Iterator<Item = SteamId> + '_ {
+        self.sessions.keys().copied()
+    }
+
+    fn transition(&mut self, user: SteamId, state: AuthSessionState) {
+        if let Some(session) = self.sessions.get_mut(&user) {
+            match (&session.state, &state) {
+                (AuthSessionState::Denied(_), _) => return,
+                // A game server gets both `GSClientApprove` and
+                // `ValidateAuthTicketResponse` for the same player
+                (
+                    AuthSessionState::Validated { owner: old },
+                    AuthSessionState::Validated { owner: new },
+                ) if old == new => return,
+                _ => {}
+            }
+            session.state = state.clone();
+            self.changes.push(AuthSessionChange { user, state });
+        }
+    }
+
+    /// Applies a [`ValidateAuthTicketResponse`]. This is also sent for
+    /// validated players, e.g. when they cancel their ticket.
+    pub fn on_validate_auth_ticket_response(&mut self, response: &ValidateAuthTicketResponse) {
+        let state = match &response.response {
+            Ok(()) => AuthSessionState::Validated {
+                owner: response.owner_steam_id,
+            },
+            Err(err) => AuthSessionState::Denied(AuthSessionDenial::Validation(err.clone())),
+        };
+        self.transition(response.steam_id, state);
+    }
+
+    /// Applies a [`GSClientApprove`]
+    pub fn on_client_approve(&mut self, approve: &GSClientApprove) {
+        self.transition(
+            approve.user,
+            AuthSessionState::Validated {
+                owner: approve.owner,
+            },
+        );
+    }
+
+    /// Applies a [`GSClientDeny`]
+    pub fn on_client_deny(&mut self, deny: &GSClientDeny) {
+        self.transition(
+            deny.user,
+            AuthSessionState::Denied(AuthSessionDenial::Denied(
+                deny.deny_reason,
+                deny.optional_text.clone(),
+            )),
+        );
+    }
+
+    /// Applies a [`GSClientKick`]
+    pub fn on_client_kick(&mut self, kick: &GSClientKick) {
+        self.transition(
+            kick.user,
+            AuthSessionState::Denied(AuthSessionDenial::Kicked(kick.deny_reason)),
+        );
+    }
+
+    fn poll_at(&mut self, now: Instant) -> Vec<AuthSessionChange> {
+        let timed_out: Vec<_> = self
+            .sessions
+            .iter()
+            .filter(|(_, s)| {
+                matches!(s.state, AuthSessionState::Pending)
+                    && now.saturating_duration_since(s.started) >= self.timeout
+            })
+            .map(|(&user, _)| user)
+            .collect();
+        for user in timed_out {
+            self.transition(user, AuthSessionState::Denied(AuthSessionDenial::TimedOut));
+        }
+        std::mem::take(&mut self.changes)
+    }
+}
+
+impl<P: AuthSessionProvider> CallbackStateMachine for AuthSessionManager<P> {
+    type Event = AuthSessionChange;
+
+    fn handle_callback(&mut self, callback: &CallbackResult) {
+        match callback {
+            CallbackResult::ValidateAuthTicketResponse(response) => {
+                self.on_validate_auth_ticket_response(response)
+            }
+            CallbackResult::GSClientApprove(approve) => self.on_client_approve(approve),
+            CallbackResult::GSClientDeny(deny) => self.on_client_deny(deny),
+            CallbackResult::GSClientKick(kick) => self.on_client_kick(kick),
+            _ => {}
+        }
+    }
+
+    /// Denies sessions that have timed out and returns all state changes
+    /// since the last call
+    fn poll(&mut self) -> Vec<AuthSessionChange> {
+        self.poll_at(Instant::now())
+    }
+}
+
+impl<P: AuthSessionProvider> Drop for AuthSessionManager<P> {
+    fn drop(&mut self) {
+        for user in self.sessions.keys() {
+            self.provider.end_authentication_session(*user);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    const PLAYER: SteamId = SteamId(76561197960287930);
+
+    #[derive(Clone, Default)]
+    struct TestProvider {
+        active: Rc<RefCell<Vec<SteamId>>>,
+    }
+
+    impl AuthSessionProvider for TestProvider {
+        fn begin_authentication_session(
+            &self,
+            user: SteamId,
+            ticket: &[u8],
+        ) -> Result<(), AuthSessionError> {
+            if ticket.is_empty() {
+                return Err(AuthSessionError::InvalidTicket);
+            }
+            let mut active = self.active.borrow_mut();
+            if active.contains(&user) {
+                return Err(AuthSessionError::DuplicateRequest);
+            }
+            active.push(user);
+            Ok(())
+        }
+
+        fn end_authentication_session(&self, user: SteamId) {
+            self.active.borrow_mut().retain(|&u| u != user);
+        }
+    }
+
+    fn states(changes: Vec<AuthSessionChange>) -> Vec<String> {
+        changes
+            .into_iter()
+            .map(|c| format!("{:?}", c.state))
+            .collect()
+    }
+
+    #[test]
+    fn validate_and_disconnect() {
+        let provider = TestProvider::default();
+        let mut manager = AuthSessionManager::new(provider.clone());
+        manager.begin(PLAYER, &[1]).unwrap();
+        assert!(matches!(
+            manager.state(PLAYER),
+            Some(AuthSessionState::Pending)
+        ));
+
+        manager.on_client_approve(&GSClientApprove {
+            user: PLAYER,
+            owner: PLAYER,
+        });
+        assert!(manager.is_validated(PLAYER));
+
+        manager.disconnect(PLAYER);
+        assert!(manager.state(PLAYER).is_none());
+        assert!(provider.active.borrow().is_empty());
+        assert_eq!(
+            states(manager.poll()),
+            [
+                "Pending",
+                "Validated { owner: SteamId(76561197960287930) }",
+                "Ended"
+            ]
+        );
+        assert!(manager.poll().is_empty());
+    }
+
+    #[test]
+    fn approve_and_validate_report_once() {
+        let mut manager = AuthSessionManager::new(TestProvider::default());
+        manager.begin(PLAYER, &[1]).unwrap();
+        manager.handle_callback(&CallbackResult::GSClientApprove(GSClientApprove {
+            user: PLAYER,
+            owner: PLAYER,
+        }));
+        manager.handle_callback(&CallbackResult::ValidateAuthTicketResponse(
+            ValidateAuthTicketResponse {
+                steam_id: PLAYER,
+                response: Ok(()),
+                owner_steam_id: PLAYER,
+            },
+        ));
+        assert!(manager.is_validated(PLAYER));
+        assert_eq!(
+            states(manager.poll()),
+            ["Pending", "Validated { owner: SteamId(76561197960287930) }"]
+        );
+    }
+
+    #[test]
+    fn denied_stays_denied() {
+        let mut manager = AuthSessionManager::new(TestProvider::default());
+        manager.begin(PLAYER, &[1]).unwrap();
+        manager.on_client_deny(&GSClientDeny {
+            user: PLAYER,
+            deny_reason: DenyReason::NoLicense,
+            optional_text: String::new(),
+        });
+        manager.on_client_approve(&GSClientApprove {
+            user: PLAYER,
+            owner: PLAYER,
+        });
+        assert!(matches!(
+            manager.state(PLAYER),
+            Some(AuthSessionState::Denied(AuthSessionDenial::Denied(
+                DenyReason::NoLicense,
+                _
+            )))
+        ));
+    }
+
+    #[test]
+    fn kicked_after_validation() {
+        let mut manager = AuthSessionManager::new(TestProvider::default());
+        manager.begin(PLAYER, &[1]).unwrap();
+        manager.on_validate_auth_ticket_response(&ValidateAuthTicketResponse {
+            steam_id: PLAYER,
+            response: Ok(()),
+            owner_steam_id: SteamId(1),
+        });
+        assert!(manager.is_validated(PLAYER));
+        manager.on_client_kick(&GSClientKick {
+            user: PLAYER,
+            deny_reason: DenyReason::Cheater,
+        });
+        assert!(matches!(
+            manager.state(PLAYER),
+            Some(AuthSessionState::Denied(AuthSessionDenial::Kicked(
+                DenyReason::Cheater
+            )))
+        ));
+    }
+
+    #[test]
+    fn times_out() {
+        let mut manager =
+            AuthSessionManager::new(TestProvider::default()).with_timeout(Duration::from_secs(5));
+        let start = Instant::now();
+        manager.begin_at(PLAYER, &[1], start).unwrap();
+        assert_eq!(
+            states(manager.poll_at(start + Duration::from_secs(4))),
+            ["Pending"]
+        );
+        assert_eq!(
+            states(manager.poll_at(start + Duration::from_secs(5))),
+            ["Denied(TimedOut)"]
+        );
+    }
+
+    #[test]
+    fn reconnect_and_drop_end_sessions() {
+        let provider = TestProvider::default();
+        let mut manager = AuthSessionManager::new(provider.clone());
+        assert!(manager.begin(PLAYER, &[]).is_err());
+        assert!(manager.state(PLAYER).is_none());
+
+        manager.begin(PLAYER, &[1]).unwrap();
+        // Would be a duplicate request without ending the first session
+        manager.begin(PLAYER, &[2]).unwrap();
+        assert_eq!(provider.active.borrow().len(), 1);
+
+        // Callbacks for unknown players are ignored
+        manager.on_client_approve(&GSClientApprove {
+            user: SteamId(1),
+            owner: SteamId(1),
+        });
+        assert!(manager.state(SteamId(1)).is_none());
+
+        drop(manager);
+        assert!(provider.active.borrow().is_empty());
+    }
+}
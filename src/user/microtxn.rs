@@ -0,0 +1,278 @@
+use super::*;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+/// How many timed out orders are remembered for late responses
+const EXPIRED_ORDERS: usize = 32;
+
+/// How a microtransaction checkout ended
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MicroTxnOutcome {
+    /// The user authorized the purchase, which should now be finalized by
+    /// the backend with `ISteamMicroTxn/FinalizeTxn`
+    Authorized,
+    /// The user declined the purchase or closed the overlay
+    Denied,
+    /// Steam did not respond before the timeout
+    TimedOut,
+}
+
+/// The outcome of an order, returned by [`MicroTxnFlow::poll`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MicroTxnResult {
+    pub order_id: u64,
+    pub outcome: MicroTxnOutcome,
+}
+
+/// Tracks the microtransaction checkouts started by the game.
+///
+/// After the backend has started a transaction with
+/// `ISteamMicroTxn/InitTxn`, the order is passed to
+/// [`begin`](Self::begin) while Steam asks the user to authorize it in
+/// the overlay. The flow matches [`MicroTxnAuthorizationResponse`]s to the
+/// pending orders and reports orders without a response as
+/// [`TimedOut`](MicroTxnOutcome::TimedOut). If the response for a timed
+/// out order arrives later it is still reported, so an authorized
+/// purchase is never lost.
+///
+/// The flow is a [`CallbackStateMachine`] that handles
+/// [`MicroTxnAuthorizationResponse`] and reports an outcome for each order.
+pub struct MicroTxnFlow {
+    app_id: AppId,
+    timeout: Duration,
+    pending: HashMap<u64, Instant>,
+    expired: VecDeque<u64>,
+    results: Vec<MicroTxnResult>,
+}
+
+impl MicroTxnFlow {
+    /// Creates a flow for orders of the given app with a timeout of 10
+    /// minutes
+    pub fn new(app_id: AppId) -> MicroTxnFlow {
+        MicroTxnFlow {
+            app_id,
+            timeout: Duration::from_secs(10 * 60),
+            pending: HashMap::new(),
+            expired: VecDeque::new(),
+            results: Vec::new(),
+        }
+    }
+
+    /// Sets how long the user may take to authorize a purchase
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Starts waiting for the authorization of an order. Beginning an
+    /// order that is already pending restarts its timeout.
+    pub fn begin(&mut self, order_id: u64) {
+        self.begin_at(order_id, Instant::now())
+    }
+
+    fn begin_at(&mut self, order_id: u64, now: Instant) {
+        self.expired.retain(|&id| id != order_id);
+        self.pending.insert(order_id, now);
+    }
+
+    /// Stops tracking an order without reporting an outcome. Returns
+    /// whether the order was pending.
+    pub fn cancel(&mut self, order_id: u64) -> bool {
+        self.expired.retain(|&id| id != order_id);
+        self.pending.remove(&order_id).is_some()
+    }
+
+    /// Returns whether the order is waiting for authorization
+    pub fn is_pending(&self, order_id: u64) -> bool {
+        self.pending.contains_key(&order_id)
+    }
+
+    /// Returns the orders waiting for authorization
+    pub fn pending(&self) -> impl Iterator<Item = u64> + '_ {
+        self.pending.keys().copied()
+    }
+
+    /// Applies a [`MicroTxnAuthorizationResponse`]. Responses for other
+    /// apps or unknown orders are ignored.
+    pub fn on_authorization_response(&mut self, response: &MicroTxnAuthorizationResponse) {
+        if response.app_id != self.app_id {
+            return;
+        }
+        let order_id = response.order_id;
+        let expired = self.expired.iter().position(|&id| id == order_id);
+        if self.pending.remove(&order_id).is_none() {
+            match expired {
+                Some(index) => {
+                    self.expired.remove(index);
+                }
+                None => return,
+            }
+        }
+        self.results.push(MicroTxnResult {
+            order_id,
+            outcome: if response.authorized {
+                MicroTxnOutcome::Authorized
+            } else {
+                MicroTxnOutcome::Denied
+            },
+        });
+    }
+
+    fn poll_at(&mut self, now: Instant) -> Vec<MicroTxnResult> {
+        let timeout = self.timeout;
+        let mut timed_out: Vec<_> = self
+            .pending
+            .iter()
+            .filter(|(_, &started)| now.saturating_duration_since(started) >= timeout)
+            .map(|(&order_id, _)| order_id)
+            .collect();
+        timed_out.sort_unstable();
+        for order_id in timed_out {
+            self.pending.remove(&order_id);
+            if self.expired.len() == EXPIRED_ORDERS {
+                self.expired.pop_front();
+            }
+            self.expired.push_back(order_id);
+            self.results.push(MicroTxnResult {
+                order_id,
+                outcome: MicroTxnOutcome::TimedOut,
+            });
+        }
+        std::mem::take(&mut self.results)
+    }
+}
+
+impl CallbackStateMachine for MicroTxnFlow {
+    type Event = MicroTxnResult;
+
+    fn handle_callback(&mut self, callback: &CallbackResult) {
+        if let CallbackResult::MicroTxnAuthorizationResponse(response) = callback {
+            self.on_authorization_response(response);
+        }
+    }
+
+    /// Times out abandoned orders and returns all outcomes since the last
+    /// call
+    fn poll(&mut self) -> Vec<MicroTxnResult> {
+        self.poll_at(Instant::now())
+    }
+}
+
+impl User {
+    /// Requests a URL that logs the user into the Steam store in the
+    /// overlay browser and then redirects to `redirect_url`.
+    ///
+    /// The URL should be opened with
+    /// [`Friends::activate_game_overlay_to_web_page`]. Steam limits how
+    /// often URLs can be requested, so they should only be requested
+    /// when they are needed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `redirect_url` contains a null byte.
+    pub fn request_store_auth_url<F>(&self, redirect_url: &str, cb: F)
+    where
+        F: FnOnce(SResult<String>) + 'static + Send,
+    {
+        let redirect_url = CString::new(redirect_url).unwrap();
+        unsafe {
+            let api_call =
+                sys::SteamAPI_ISteamUser_RequestStoreAuthURL(self.user, redirect_url.as_ptr());
+            register_call_result::<sys::StoreAuthURLResponse_t, _>(
+                &self._inner,
+                api_call,
+                move |v, io_error| {
+                    cb(if io_error {
+                        Err(SteamError::IOFailure)
+                    } else {
+                        let url = CStr::from_ptr(v.m_szURL.as_ptr()).to_string_lossy();
+                        if url.is_empty() {
+                            Err(SteamError::Generic)
+                        } else {
+                            Ok(url.into_owned())
+                        }
+                    })
+                },
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(order_id: u64, authorized: bool) -> MicroTxnAuthorizationResponse {
+        MicroTxnAuthorizationResponse {
+            app_id: AppId(480),
+            order_id,
+            authorized,
+        }
+    }
+
+    #[test]
+    fn authorized_and_denied() {
+        let start = Instant::now();
+        let mut flow = MicroTxnFlow::new(AppId(480));
+        flow.begin_at(1, start);
+        flow.begin_at(2, start);
+        flow.on_authorization_response(&response(1, true));
+        flow.on_authorization_response(&response(2, false));
+        // Unknown orders and other apps are ignored
+        flow.on_authorization_response(&response(3, true));
+        flow.on_authorization_response(&MicroTxnAuthorizationResponse {
+            app_id: AppId(481),
+            ..response(1, true)
+        });
+        assert_eq!(
+            flow.poll_at(start),
+            [
+                MicroTxnResult {
+                    order_id: 1,
+                    outcome: MicroTxnOutcome::Authorized,
+                },
+                MicroTxnResult {
+                    order_id: 2,
+                    outcome: MicroTxnOutcome::Denied,
+                },
+            ]
+        );
+        assert_eq!(flow.pending().count(), 0);
+    }
+
+    #[test]
+    fn timeouts() {
+        let start = Instant::now();
+        let mut flow = MicroTxnFlow::new(AppId(480)).with_timeout(Duration::from_secs(60));
+        flow.begin_at(1, start);
+        flow.begin_at(2, start + Duration::from_secs(30));
+        assert!(flow.poll_at(start + Duration::from_secs(59)).is_empty());
+
+        let results = flow.poll_at(start + Duration::from_secs(60));
+        assert_eq!(
+            results,
+            [MicroTxnResult {
+                order_id: 1,
+                outcome: MicroTxnOutcome::TimedOut,
+            }]
+        );
+        assert!(!flow.is_pending(1));
+        assert!(flow.is_pending(2));
+
+        // A late authorization is still reported, once
+        flow.on_authorization_response(&response(1, true));
+        flow.on_authorization_response(&response(1, true));
+        assert_eq!(
+            flow.poll_at(start + Duration::from_secs(61)),
+            [MicroTxnResult {
+                order_id: 1,
+                outcome: MicroTxnOutcome::Authorized,
+            }]
+        );
+
+        assert!(flow.cancel(2));
+        assert!(flow.poll_at(start + Duration::from_secs(120)).is_empty());
+    }
+}
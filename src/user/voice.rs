@@ -0,0 +1,229 @@
+use super::*;
+
+/// The size of the compressed voice buffer Steam recommends
+const COMPRESSED_BUFFER_SIZE: usize = 8 * 1024;
+/// The range of sample rates Steam can decompress voice at
+const SAMPLE_RATES: std::ops::RangeInclusive<u32> = 11025..=48000;
+
+/// Errors from [`VoiceRecorder`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Error)]
+pub enum VoiceError {
+    #[error("voice is not initialized")]
+    NotInitialized,
+    /// [`VoiceRecorder::start`] has not been called
+    #[error("not recording")]
+    NotRecording,
+    /// No voice data is available, e.g. because the user is not talking
+    #[error("no voice data available")]
+    NoData,
+    #[error("buffer too small")]
+    BufferTooSmall,
+    /// The compressed voice data is invalid
+    #[error("voice data corrupted")]
+    DataCorrupted,
+    /// The user is not allowed to use voice chat
+    #[error("voice chat restricted")]
+    Restricted,
+    #[error("unsupported voice codec")]
+    UnsupportedCodec,
+    #[error("receiver out of date")]
+    ReceiverOutOfDate,
+    #[error("receiver did not answer")]
+    ReceiverDidNotAnswer,
+    /// A result this version does not know about
+    #[error("unknown voice result {0}")]
+    Unknown(i32),
+}
+
+fn voice_result(result: sys::EVoiceResult) -> Result<(), VoiceError> {
+    use sys::EVoiceResult::*;
+    match result {
+        k_EVoiceResultOK => Ok(()),
+        k_EVoiceResultNotInitialized => Err(VoiceError::NotInitialized),
+        k_EVoiceResultNotRecording => Err(VoiceError::NotRecording),
+        k_EVoiceResultNoData => Err(VoiceError::NoData),
+        k_EVoiceResultBufferTooSmall => Err(VoiceError::BufferTooSmall),
+        k_EVoiceResultDataCorrupted => Err(VoiceError::DataCorrupted),
+        k_EVoiceResultRestricted => Err(VoiceError::Restricted),
+        k_EVoiceResultUnsupportedCodec => Err(VoiceError::UnsupportedCodec),
+        k_EVoiceResultReceiverOutOfDate => Err(VoiceError::ReceiverOutOfDate),
+        k_EVoiceResultReceiverDidNotAnswer => Err(VoiceError::ReceiverDidNotAnswer),
+        _ => Err(VoiceError::Unknown(result as i32)),
+    }
+}
+
+/// Records the user's voice with Steam's voice codec, created with
+/// [`User::voice_recorder`].
+///
+/// Compressed voice is read with [`read_compressed`](Self::read_compressed)
+/// and sent to other players, who turn it into 16-bit mono PCM with
+/// [`decompress`](Self::decompress). The recorder keeps its buffers
+/// between calls so reading and decompressing voice does not allocate
+/// once the buffers have grown to fit.
+///
+/// Recording is stopped when the recorder is dropped.
+pub struct VoiceRecorder {
+    user: *mut sys::ISteamUser,
+    sample_rate: u32,
+    recording: bool,
+    compressed: Vec<u8>,
+    pcm: Vec<i16>,
+    _inner: Arc<Inner>,
+}
+
+unsafe impl Send for VoiceRecorder {}
+
+impl VoiceRecorder {
+    pub(crate) fn new(user: &User) -> VoiceRecorder {
+        VoiceRecorder {
+            user: user.user,
+            sample_rate: unsafe { sys::SteamAPI_ISteamUser_GetVoiceOptimalSampleRate(user.user) },
+            recording: false,
+            compressed: vec![0; COMPRESSED_BUFFER_SIZE],
+            pcm: Vec::new(),
+            _inner: user._inner.clone(),
+        }
+    }
+
+    /// Starts recording the user's voice.
+    ///
+    /// Steam keeps recording for a short while after
+    /// [`stop`](Self::stop) so the end of a sentence is not cut off.
+    /// Voice should keep being read until [`VoiceError::NotRecording`]
+    /// is returned.
+    pub fn start(&mut self) {
+        unsafe { sys::SteamAPI_ISteamUser_StartVoiceRecording(self.user) }
+        self.recording = true;
+    }
+
+    /// Stops recording the user's voice
+    pub fn stop(&mut self) {
+        unsafe { sys::SteamAPI_ISteamUser_StopVoiceRecording(self.user) }
+        self.recording = false;
+    }
+
+    /// Returns whether [`start`](Self::start) has been called without a
+    /// matching [`stop`](Self::stop)
+    pub fn is_recording(&self) -> bool {
+        self.recording
+    }
+
+    /// Returns the sample rate Steam decompresses voice at with the best
+    /// quality and performance
+    pub fn optimal_sample_rate(&self) -> u32 {
+        unsafe { sys::SteamAPI_ISteamUser_GetVoiceOptimalSampleRate(self.user) }
+    }
+
+    /// Returns the sample rate voice is decompressed at
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Sets the sample rate voice is decompressed at, clamped to between
+    /// 11025 and 48000 Hz. Defaults to
+    /// [`optimal_sample_rate`](Self::optimal_sample_rate).
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.sample_rate = sample_rate.clamp(*SAMPLE_RATES.start(), *SAMPLE_RATES.end());
+    }
+
+    /// Returns the number of compressed bytes of voice that are ready to
+    /// be read
+    pub fn available(&self) -> Result<u32, VoiceError> {
+        let mut compressed = 0;
+        voice_result(unsafe {
+            sys::SteamAPI_ISteamUser_GetAvailableVoice(
+                self.user,
+                &mut compressed,
+                std::ptr::null_mut(),
+                0,
+            )
+        })?;
+        Ok(compressed)
+    }
+
+    /// Reads the compressed voice recorded since the last call.
+    ///
+    /// This should be called once per frame while recording. Returns
+    /// [`VoiceError::NoData`] if the user is not talking.
+    pub fn read_compressed(&mut self) -> Result<&[u8], VoiceError> {
+        let available = self.available()? as usize;
+        if available > self.compressed.len() {
+            self.compressed.resize(available, 0);
+        }
+        let mut written = 0;
+        voice_result(unsafe {
+            sys::SteamAPI_ISteamUser_GetVoice(
+                self.user,
+                true,
+                self.compressed.as_mut_ptr().cast(),
+                self.compressed.len() as u32,
+                &mut written,
+                false,
+                std::ptr::null_mut(),
+                0,
+                std::ptr::null_mut(),
+                0,
+            )
+        })?;
+        Ok(&self.compressed[..written as usize])
+    }
+
+    /// Decompresses voice read by [`read_compressed`](Self::read_compressed)
+    /// into 16-bit mono PCM at [`sample_rate`](Self::sample_rate).
+    ///
+    /// The samples are valid until the next call to `decompress`.
+    pub fn decompress(&mut self, compressed: &[u8]) -> Result<&[i16], VoiceError> {
+        if self.pcm.is_empty() {
+            // 20 ms of audio, grown below if a packet holds more
+            self.pcm.resize(self.sample_rate as usize / 50, 0);
+        }
+        loop {
+            let mut written = 0;
+            let result = voice_result(unsafe {
+                sys::SteamAPI_ISteamUser_DecompressVoice(
+                    self.user,
+                    compressed.as_ptr().cast(),
+                    compressed.len() as u32,
+                    self.pcm.as_mut_ptr().cast(),
+                    (self.pcm.len() * 2) as u32,
+                    &mut written,
+                    self.sample_rate,
+                )
+            });
+            match result {
+                Ok(()) => return Ok(&self.pcm[..written as usize / 2]),
+                // `written` holds the required size in bytes
+                Err(VoiceError::BufferTooSmall) if written as usize > self.pcm.len() * 2 => {
+                    self.pcm.resize((written as usize).div_ceil(2), 0);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+
+impl Drop for VoiceRecorder {
+    fn drop(&mut self) {
+        if self.recording {
+            self.stop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn voice_results() {
+        assert_eq!(voice_result(sys::EVoiceResult::k_EVoiceResultOK), Ok(()));
+        assert_eq!(
+            voice_result(sys::EVoiceResult::k_EVoiceResultNoData),
+            Err(VoiceError::NoData)
+        );
+        assert_eq!(
+            voice_result(sys::EVoiceResult::k_EVoiceResultRestricted),
+            Err(VoiceError::Restricted)
+        );
+    }
+}
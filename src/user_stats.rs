@@ -1,7 +1,17 @@
+mod leaderboard_view;
+mod progress;
+mod schema;
+mod session;
 mod stat_callback;
 pub mod stats;
+mod user_cache;
 
+pub use self::leaderboard_view::*;
+pub use self::progress::*;
+pub use self::schema::*;
+pub use self::session::*;
 pub use self::stat_callback::*;
+pub use self::user_cache::*;
 use super::*;
 #[cfg(test)]
 use serial_test::serial;
@@ -156,6 +166,29 @@ impl UserStats {
         cb: F,
     ) where
         F: FnOnce(Result<Vec<LeaderboardEntry>, SteamError>) + 'static + Send,
+    {
+        self.download_leaderboard_entries_in(
+            leaderboard,
+            request,
+            start as i32,
+            end as i32,
+            max_details_len,
+            cb,
+        )
+    }
+
+    /// Like [`download_leaderboard_entries`](Self::download_leaderboard_entries),
+    /// but with signed offsets as used for ranges around the user
+    pub(crate) fn download_leaderboard_entries_in<F>(
+        &self,
+        leaderboard: &Leaderboard,
+        request: LeaderboardDataRequest,
+        start: i32,
+        end: i32,
+        max_details_len: usize,
+        cb: F,
+    ) where
+        F: FnOnce(Result<Vec<LeaderboardEntry>, SteamError>) + 'static + Send,
     {
         unsafe {
             let request = match request {
@@ -173,8 +206,8 @@ impl UserStats {
                 self.user_stats,
                 leaderboard.0,
                 request,
-                start as _,
-                end as _,
+                start,
+                end,
             );
             let user_stats = self.user_stats as isize;
             register_call_result::<sys::LeaderboardScoresDownloaded_t, _>(
@@ -184,34 +217,85 @@ impl UserStats {
                     cb(if io_error {
                         Err(SteamError::IOFailure)
                     } else {
-                        let len = v.m_cEntryCount;
-                        let mut entries = Vec::with_capacity(len as usize);
-                        for idx in 0..len {
-                            let mut entry: sys::LeaderboardEntry_t = std::mem::zeroed();
-                            let mut details = Vec::with_capacity(max_details_len);
-
-                            sys::SteamAPI_ISteamUserStats_GetDownloadedLeaderboardEntry(
-                                user_stats as *mut _,
-                                v.m_hSteamLeaderboardEntries,
-                                idx,
-                                &mut entry,
-                                details.as_mut_ptr(),
-                                max_details_len as _,
-                            );
-
-                            details.set_len(std::cmp::min(
-                                entry.m_cDetails as usize,
-                                max_details_len as usize,
-                            ));
-
-                            entries.push(LeaderboardEntry {
-                                user: SteamId(entry.m_steamIDUser.m_steamid.m_unAll64Bits),
-                                global_rank: entry.m_nGlobalRank,
-                                score: entry.m_nScore,
-                                details,
-                            })
-                        }
-                        Ok(entries)
+                        Ok(downloaded_entries(user_stats as *mut _, v, max_details_len))
+                    })
+                },
+            );
+        }
+    }
+
+    /// Downloads the entries of specific users, e.g. to show the scores of
+    /// players in the current match.
+    ///
+    /// Users without an entry are left out of the result. Between 1 and
+    /// 100 users can be requested at once, otherwise the request fails
+    /// with [`SteamError::InvalidParameter`].
+    pub fn download_leaderboard_entries_for_users<F>(
+        &self,
+        leaderboard: &Leaderboard,
+        users: &[SteamId],
+        max_details_len: usize,
+        cb: F,
+    ) where
+        F: FnOnce(Result<Vec<LeaderboardEntry>, SteamError>) + 'static + Send,
+    {
+        if users.is_empty() || users.len() > 100 {
+            cb(Err(SteamError::InvalidParameter));
+            return;
+        }
+        unsafe {
+            let mut users: Vec<sys::CSteamID> = users
+                .iter()
+                .map(|user| {
+                    let mut id: sys::CSteamID = std::mem::zeroed();
+                    id.m_steamid.m_unAll64Bits = user.0;
+                    id
+                })
+                .collect();
+            let api_call = sys::SteamAPI_ISteamUserStats_DownloadLeaderboardEntriesForUsers(
+                self.user_stats,
+                leaderboard.0,
+                users.as_mut_ptr(),
+                users.len() as _,
+            );
+            let user_stats = self.user_stats as isize;
+            register_call_result::<sys::LeaderboardScoresDownloaded_t, _>(
+                &self.inner,
+                api_call,
+                move |v, io_error| {
+                    cb(if io_error {
+                        Err(SteamError::IOFailure)
+                    } else {
+                        Ok(downloaded_entries(user_stats as *mut _, v, max_details_len))
+                    })
+                },
+            );
+        }
+    }
+
+    /// Attaches a file shared with [`SteamFile::share`] to the current
+    /// user's entry on a leaderboard, e.g. a replay of the run.
+    ///
+    /// The file is returned as [`LeaderboardEntry::ugc`] to other users,
+    /// who can download it with [`LeaderboardEntry::download_ugc`].
+    pub fn attach_leaderboard_ugc<F>(&self, leaderboard: &Leaderboard, ugc: UGCHandle, cb: F)
+    where
+        F: FnOnce(Result<(), SteamError>) + 'static + Send,
+    {
+        unsafe {
+            let api_call = sys::SteamAPI_ISteamUserStats_AttachLeaderboardUGC(
+                self.user_stats,
+                leaderboard.0,
+                ugc.0,
+            );
+            register_call_result::<sys::LeaderboardUGCSet_t, _>(
+                &self.inner,
+                api_call,
+                move |v, io_error| {
+                    cb(if io_error {
+                        Err(SteamError::IOFailure)
+                    } else {
+                        crate::to_steam_result(v.m_eResult)
                     })
                 },
             );
@@ -281,10 +365,108 @@ impl UserStats {
         }
     }
 
-    /// Triggers a [`UserStatsReceived`](./struct.UserStatsReceived.html) callback.
-    pub fn request_user_stats(&self, steam_user_id: u64) {
+    /// Asynchronously downloads the stats and achievements of another user.
+    ///
+    /// Once the callback returns the user's id the stats can be read with
+    /// [`get_user_stat_i32()`](Self::get_user_stat_i32),
+    /// [`get_user_stat_f32()`](Self::get_user_stat_f32) and
+    /// [`get_user_achievement()`](Self::get_user_achievement). Fails with
+    /// [`SteamError::Generic`] if the user has no stats for the current game.
+    ///
+    /// Steam keeps the stats of a limited number of users in memory and sends
+    /// a [`UserStatsUnloaded`] callback when it drops them, after which they
+    /// must be requested again. See [`UserStatsCache`] for keeping track of
+    /// this.
+    pub fn request_user_stats<F>(&self, user: SteamId, cb: F)
+    where
+        F: FnOnce(Result<SteamId, SteamError>) + 'static + Send,
+    {
         unsafe {
-            sys::SteamAPI_ISteamUserStats_RequestUserStats(self.user_stats, steam_user_id);
+            let api_call = sys::SteamAPI_ISteamUserStats_RequestUserStats(self.user_stats, user.0);
+            register_call_result::<sys::UserStatsReceived_t, _>(
+                &self.inner,
+                api_call,
+                move |v, io_error| {
+                    cb(if io_error {
+                        Err(SteamError::IOFailure)
+                    } else {
+                        crate::to_steam_result(v.m_eResult)
+                            .map(|_| SteamId(v.m_steamIDUser.m_steamid.m_unAll64Bits))
+                    })
+                },
+            );
+        }
+    }
+
+    /// Gets the value of an integer stat for another user.
+    ///
+    /// Requires [`request_user_stats()`](Self::request_user_stats) to have
+    /// completed successfully for the user.
+    pub fn get_user_stat_i32(&self, user: SteamId, name: &str) -> Result<i32, ()> {
+        let name = CString::new(name).map_err(|_| ())?;
+        let mut value: i32 = 0;
+        let success = unsafe {
+            sys::SteamAPI_ISteamUserStats_GetUserStatInt32(
+                self.user_stats,
+                user.0,
+                name.as_ptr(),
+                &mut value,
+            )
+        };
+        if success {
+            Ok(value)
+        } else {
+            Err(())
+        }
+    }
+
+    /// Gets the value of a float stat for another user.
+    ///
+    /// Requires [`request_user_stats()`](Self::request_user_stats) to have
+    /// completed successfully for the user.
+    pub fn get_user_stat_f32(&self, user: SteamId, name: &str) -> Result<f32, ()> {
+        let name = CString::new(name).map_err(|_| ())?;
+        let mut value: f32 = 0.0;
+        let success = unsafe {
+            sys::SteamAPI_ISteamUserStats_GetUserStatFloat(
+                self.user_stats,
+                user.0,
+                name.as_ptr(),
+                &mut value,
+            )
+        };
+        if success {
+            Ok(value)
+        } else {
+            Err(())
+        }
+    }
+
+    /// Gets whether another user has unlocked an achievement, and when.
+    ///
+    /// The unlock time is in seconds since the Unix epoch, and is zero for
+    /// locked achievements and achievements unlocked before Steam began
+    /// tracking unlock times.
+    ///
+    /// Requires [`request_user_stats()`](Self::request_user_stats) to have
+    /// completed successfully for the user.
+    pub fn get_user_achievement(&self, user: SteamId, name: &str) -> Result<(bool, u32), ()> {
+        let name = CString::new(name).map_err(|_| ())?;
+        let mut achieved = false;
+        let mut unlock_time = 0u32;
+        let success = unsafe {
+            sys::SteamAPI_ISteamUserStats_GetUserAchievementAndUnlockTime(
+                self.user_stats,
+                user.0,
+                name.as_ptr(),
+                &mut achieved,
+                &mut unlock_time,
+            )
+        };
+        if success {
+            Ok((achieved, unlock_time))
+        } else {
+            Err(())
         }
     }
 
@@ -367,6 +549,68 @@ impl UserStats {
         }
     }
 
+    /// Returns the global achievement percentages, from the most to the
+    /// least achieved, along with whether the current user has unlocked
+    /// each achievement.
+    ///
+    /// Requires [`request_global_achievement_percentages()`](Self::request_global_achievement_percentages)
+    /// to have completed successfully. Returns an empty list otherwise.
+    pub fn get_achievement_percentages(&self) -> Vec<AchievementPercent> {
+        let mut achievements = Vec::new();
+        let mut name = [0 as c_char; sys::k_cchStatNameMax as usize];
+        let mut percent = 0.0;
+        let mut achieved = false;
+        unsafe {
+            let mut iterator = sys::SteamAPI_ISteamUserStats_GetMostAchievedAchievementInfo(
+                self.user_stats,
+                name.as_mut_ptr(),
+                name.len() as _,
+                &mut percent,
+                &mut achieved,
+            );
+            while iterator != -1 {
+                achievements.push(AchievementPercent {
+                    name: CStr::from_ptr(name.as_ptr()).to_string_lossy().into_owned(),
+                    percent,
+                    achieved,
+                });
+                iterator = sys::SteamAPI_ISteamUserStats_GetNextMostAchievedAchievementInfo(
+                    self.user_stats,
+                    iterator,
+                    name.as_mut_ptr(),
+                    name.len() as _,
+                    &mut percent,
+                    &mut achieved,
+                );
+            }
+        }
+        achievements
+    }
+
+    /// Asynchronously gets the number of players currently playing the
+    /// game, both online and offline.
+    pub fn get_number_of_current_players<F>(&self, cb: F)
+    where
+        F: FnOnce(Result<i32, SteamError>) + 'static + Send,
+    {
+        unsafe {
+            let api_call = sys::SteamAPI_ISteamUserStats_GetNumberOfCurrentPlayers(self.user_stats);
+            register_call_result::<sys::NumberOfCurrentPlayers_t, _>(
+                &self.inner,
+                api_call,
+                move |v, io_error| {
+                    cb(if io_error {
+                        Err(SteamError::IOFailure)
+                    } else if v.m_bSuccess == 0 {
+                        Err(SteamError::Generic)
+                    } else {
+                        Ok(v.m_cPlayers)
+                    })
+                },
+            );
+        }
+    }
+
     /// Gets the lifetime total for an aggregated stat as an `i64`.
     ///
     /// The specified stat must exist and be marked as "aggregated" in the Steamworks App Admin.
@@ -624,6 +868,38 @@ impl UserStats {
         }
     }
 
+    /// Adds a session to an average rate stat for the current user, e.g.
+    /// the points scored in a match and the length of the match.
+    ///
+    /// The session length is in the units of the stat's window set on the
+    /// Steamworks App Admin website. Like [`set_stat_f32()`](Self::set_stat_f32)
+    /// this only changes the value in-memory until the stats are stored.
+    ///
+    /// Requires [`request_current_stats()`](#method.request_current_stats) to have been called
+    /// and a successful [`UserStatsReceived`](./struct.UserStatsReceived.html) callback processed.
+    pub fn update_avg_rate_stat(
+        &self,
+        name: &str,
+        count_this_session: f32,
+        session_length: f64,
+    ) -> Result<(), ()> {
+        let name = CString::new(name).map_err(|_| ())?;
+
+        let success = unsafe {
+            sys::SteamAPI_ISteamUserStats_UpdateAvgRateStat(
+                self.user_stats,
+                name.as_ptr(),
+                count_this_session,
+                session_length,
+            )
+        };
+        if success {
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+
     /// Access achievement API for a given achievement 'API Name'.
     ///
     /// Requires [`request_current_stats()`](#method.request_current_stats) to have been called
@@ -678,15 +954,82 @@ impl UserStats {
     }
 }
 
-#[derive(Clone, Debug)]
+/// The global unlock rate of an achievement, returned by
+/// [`UserStats::get_achievement_percentages`]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AchievementPercent {
+    /// The API name of the achievement
+    pub name: String,
+    /// The percentage of players who have unlocked the achievement
+    pub percent: f32,
+    /// Whether the current user has unlocked the achievement
+    pub achieved: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct LeaderboardEntry {
     pub user: SteamId,
     pub global_rank: i32,
     pub score: i32,
     pub details: Vec<i32>,
+    /// The file attached with
+    /// [`attach_leaderboard_ugc`](UserStats::attach_leaderboard_ugc), if any
+    pub ugc: Option<UGCHandle>,
+}
+
+impl LeaderboardEntry {
+    /// Downloads the file attached to the entry, e.g. a replay to show
+    /// next to the score. Fails with [`SteamError::FileNotFound`] if the
+    /// entry has no file.
+    pub fn download_ugc<F>(&self, remote_storage: &RemoteStorage, cb: F)
+    where
+        F: FnOnce(Result<DownloadedUGC, SteamError>) + 'static + Send,
+    {
+        match self.ugc {
+            Some(ugc) => remote_storage.download_ugc(ugc, 0, cb),
+            None => cb(Err(SteamError::FileNotFound)),
+        }
+    }
+}
+
+/// Reads the entries of a `LeaderboardScoresDownloaded_t` result
+unsafe fn downloaded_entries(
+    user_stats: *mut sys::ISteamUserStats,
+    v: &sys::LeaderboardScoresDownloaded_t,
+    max_details_len: usize,
+) -> Vec<LeaderboardEntry> {
+    let len = v.m_cEntryCount;
+    let mut entries = Vec::with_capacity(len as usize);
+    for idx in 0..len {
+        let mut entry: sys::LeaderboardEntry_t = std::mem::zeroed();
+        let mut details = Vec::with_capacity(max_details_len);
+
+        sys::SteamAPI_ISteamUserStats_GetDownloadedLeaderboardEntry(
+            user_stats,
+            v.m_hSteamLeaderboardEntries,
+            idx,
+            &mut entry,
+            details.as_mut_ptr(),
+            max_details_len as _,
+        );
+
+        details.set_len(std::cmp::min(entry.m_cDetails as usize, max_details_len));
+
+        entries.push(LeaderboardEntry {
+            user: SteamId(entry.m_steamIDUser.m_steamid.m_unAll64Bits),
+            global_rank: entry.m_nGlobalRank,
+            score: entry.m_nScore,
+            details,
+            ugc: (entry.m_hUGC != 0 && entry.m_hUGC != sys::k_UGCHandleInvalid)
+                .then_some(UGCHandle(entry.m_hUGC)),
+        })
+    }
+    entries
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum LeaderboardDataRequest {
     Global,
     GlobalAroundUser,
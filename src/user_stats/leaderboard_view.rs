@@ -0,0 +1,744 @@
+use super::*;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long a failed page waits before it is downloaded again, doubled
+/// for every further failure up to the page TTL
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Callback passed to [`LeaderboardSource::find_leaderboard`]
+pub type LeaderboardFindCallback = Box<dyn FnOnce(Result<Option<Leaderboard>, SteamError>) + Send>;
+/// Callback passed to [`LeaderboardSource::download_leaderboard_entries`]
+pub type LeaderboardEntriesCallback =
+    Box<dyn FnOnce(Result<Vec<LeaderboardEntry>, SteamError>) + Send>;
+
+/// Finds leaderboards and downloads their entries.
+///
+/// Implemented for [`UserStats`], and can be implemented by tests to use
+/// a [`LeaderboardView`] without Steam.
+pub trait LeaderboardSource {
+    fn find_leaderboard(&self, name: &str, cb: LeaderboardFindCallback);
+    fn download_leaderboard_entries(
+        &self,
+        leaderboard: &Leaderboard,
+        request: LeaderboardDataRequest,
+        start: i32,
+        end: i32,
+        max_details_len: usize,
+        cb: LeaderboardEntriesCallback,
+    );
+}
+
+impl LeaderboardSource for UserStats {
+    fn find_leaderboard(&self, name: &str, cb: LeaderboardFindCallback) {
+        UserStats::find_leaderboard(self, name, cb)
+    }
+
+    fn download_leaderboard_entries(
+        &self,
+        leaderboard: &Leaderboard,
+        request: LeaderboardDataRequest,
+        start: i32,
+        end: i32,
+        max_details_len: usize,
+        cb: LeaderboardEntriesCallback,
+    ) {
+        self.download_leaderboard_entries_in(leaderboard, request, start, end, max_details_len, cb)
+    }
+}
+
+/// A page of a [`LeaderboardView`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LeaderboardPage<'a> {
+    /// The page is being downloaded
+    Loading,
+    /// The entries of the page, which may be older than the page TTL while
+    /// they are downloaded again
+    Loaded(&'a [LeaderboardEntry]),
+    /// No leaderboard with the name exists
+    NotFound,
+    /// The page failed to download
+    Failed(SteamError),
+}
+
+/// An event returned by [`LeaderboardView::poll`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LeaderboardViewEvent {
+    /// A page was downloaded
+    PageLoaded {
+        name: String,
+        request: LeaderboardDataRequest,
+        page: i32,
+    },
+    /// A page failed to download
+    PageFailed {
+        name: String,
+        request: LeaderboardDataRequest,
+        page: i32,
+        error: SteamError,
+    },
+    /// No leaderboard with the name exists
+    NotFound { name: String },
+}
+
+#[derive(Clone, Debug)]
+enum Handle {
+    Finding,
+    Found(Leaderboard),
+    NotFound,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct PageKey {
+    name: String,
+    request: LeaderboardDataRequest,
+    page: i32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Pending {
+    None,
+    WaitingForHandle,
+    InFlight(u64),
+}
+
+struct Page {
+    entries: Option<Vec<LeaderboardEntry>>,
+    error: Option<SteamError>,
+    fetched: Option<Instant>,
+    pending: Pending,
+    /// Failed downloads in a row and when the last one failed
+    failures: u32,
+    failed: Option<Instant>,
+}
+
+impl Page {
+    /// Returns whether the page should not be downloaded yet
+    fn is_waiting(&self, now: Instant, ttl: Duration) -> bool {
+        let since = |time: Instant| now.saturating_duration_since(time);
+        if let Some(failed) = self.failed {
+            let delay = RETRY_DELAY
+                .saturating_mul(1 << (self.failures - 1).min(16))
+                .min(ttl.max(RETRY_DELAY));
+            if since(failed) < delay {
+                return true;
+            }
+        }
+        self.fetched.is_some_and(|fetched| since(fetched) < ttl)
+    }
+}
+
+enum Response {
+    Handle(String, Result<Option<Leaderboard>, SteamError>),
+    Page(PageKey, u64, Result<Vec<LeaderboardEntry>, SteamError>),
+}
+
+/// Pages through leaderboards, caching leaderboard handles and
+/// downloaded pages.
+///
+/// Leaderboards are looked up by name once and pages are downloaded again
+/// when they are older than the page TTL, showing the old entries in the
+/// meantime. Pages that failed to download are retried with a backoff of
+/// up to the page TTL. When a page is shown the following pages are
+/// prefetched, and
+/// for [`GlobalAroundUser`](LeaderboardDataRequest::GlobalAroundUser) the
+/// preceding ones too. [`Friends`](LeaderboardDataRequest::Friends) is
+/// downloaded at once and split into pages locally.
+///
+/// Pages are numbered from 0. Global pages start at rank 1, and page 0
+/// around the user has the user in the middle with negative pages above
+/// them.
+///
+/// The view is a [`CallbackStateMachine`] that only waits for its own
+/// downloads, so it just needs [`poll`](CallbackStateMachine::poll). As
+/// score uploads are async calls of the game, their results are passed
+/// to [`on_score_uploaded`](Self::on_score_uploaded) instead so the
+/// changed ranks are downloaded again.
+///
+/// # Example
+///
+/// ```no_run
+/// # use steamworks::*;
+/// let client = Client::init().unwrap();
+/// let mut view = LeaderboardView::new(client.user_stats()).with_page_size(20);
+/// loop {
+///     client.run_callbacks();
+///     view.poll();
+///     match view.page("high_scores", LeaderboardDataRequest::Global, 0) {
+///         LeaderboardPage::Loading => continue,
+///         LeaderboardPage::Loaded(entries) => {
+///             for entry in entries {
+///                 println!("{}. {:?} {}", entry.global_rank, entry.user, entry.score);
+///             }
+///         }
+///         LeaderboardPage::NotFound => println!("no such leaderboard"),
+///         LeaderboardPage::Failed(err) => println!("{}", err),
+///     }
+///     break;
+/// }
+/// ```
+pub struct LeaderboardView<S: LeaderboardSource> {
+    source: S,
+    page_size: usize,
+    prefetch: usize,
+    page_ttl: Duration,
+    max_details_len: usize,
+    handles: HashMap<String, Handle>,
+    pages: HashMap<PageKey, Page>,
+    responses: Arc<Mutex<Vec<Response>>>,
+    events: Vec<LeaderboardViewEvent>,
+    generation: u64,
+}
+
+impl<S: LeaderboardSource> LeaderboardView<S> {
+    /// Creates a view with pages of 10 entries, prefetching one page and
+    /// downloading pages again after a minute
+    pub fn new(source: S) -> LeaderboardView<S> {
+        LeaderboardView {
+            source,
+            page_size: 10,
+            prefetch: 1,
+            page_ttl: Duration::from_secs(60),
+            max_details_len: 0,
+            handles: HashMap::new(),
+            pages: HashMap::new(),
+            responses: Arc::new(Mutex::new(Vec::new())),
+            events: Vec::new(),
+            generation: 0,
+        }
+    }
+
+    /// Sets the number of entries per page
+    ///
+    /// # Panics
+    ///
+    /// Panics if `page_size` is zero.
+    pub fn with_page_size(mut self, page_size: usize) -> Self {
+        assert!(page_size > 0, "page size must not be zero");
+        self.page_size = page_size;
+        self
+    }
+
+    /// Sets how many pages next to the shown one are downloaded in advance
+    pub fn with_prefetch(mut self, pages: usize) -> Self {
+        self.prefetch = pages;
+        self
+    }
+
+    /// Sets how long downloaded pages are shown before they are
+    /// downloaded again
+    pub fn with_page_ttl(mut self, ttl: Duration) -> Self {
+        self.page_ttl = ttl;
+        self
+    }
+
+    /// Sets how many details are downloaded with each entry
+    pub fn with_max_details(mut self, max_details_len: usize) -> Self {
+        self.max_details_len = max_details_len;
+        self
+    }
+
+    /// Returns the source the leaderboards are read from
+    pub fn source(&self) -> &S {
+        &self.source
+    }
+
+    /// Returns the number of entries per page
+    pub fn page_size(&self) -> usize {
+        self.page_size
+    }
+
+    /// Returns the handle of a leaderboard if it has been found
+    pub fn leaderboard(&self, name: &str) -> Option<&Leaderboard> {
+        match self.handles.get(name) {
+            Some(Handle::Found(leaderboard)) => Some(leaderboard),
+            _ => None,
+        }
+    }
+
+    /// Returns a page of a leaderboard, downloading it and the pages next
+    /// to it as needed
+    pub fn page(
+        &mut self,
+        name: &str,
+        request: LeaderboardDataRequest,
+        page: i32,
+    ) -> LeaderboardPage<'_> {
+        self.page_at(name, request, page, Instant::now())
+    }
+
+    fn page_at(
+        &mut self,
+        name: &str,
+        request: LeaderboardDataRequest,
+        page: i32,
+        now: Instant,
+    ) -> LeaderboardPage<'_> {
+        if let Some(Handle::NotFound) = self.handles.get(name) {
+            return LeaderboardPage::NotFound;
+        }
+        let (stored_page, range) = match request {
+            LeaderboardDataRequest::Global if page < 0 => return LeaderboardPage::Loaded(&[]),
+            LeaderboardDataRequest::Global => {
+                let ahead = (1..=self.prefetch as i32).map(|i| page + i);
+                (page, std::iter::once(page).chain(ahead).collect::<Vec<_>>())
+            }
+            LeaderboardDataRequest::GlobalAroundUser => {
+                let around = (1..=self.prefetch as i32).flat_map(|i| [page + i, page - i]);
+                (page, std::iter::once(page).chain(around).collect())
+            }
+            // All friends are downloaded at once as page 0
+            LeaderboardDataRequest::Friends => (0, vec![0]),
+        };
+        for page in range {
+            self.load(name, request, page, now);
+        }
+
+        let key = PageKey {
+            name: name.to_owned(),
+            request,
+            page: stored_page,
+        };
+        let stored = &self.pages[&key];
+        match (&stored.entries, stored.error) {
+            (Some(entries), _) if request == LeaderboardDataRequest::Friends => {
+                let start = (page.max(0) as usize).saturating_mul(self.page_size);
+                let end = start.saturating_add(self.page_size);
+                LeaderboardPage::Loaded(if page < 0 {
+                    &[]
+                } else {
+                    &entries[start.min(entries.len())..end.min(entries.len())]
+                })
+            }
+            (Some(entries), _) => LeaderboardPage::Loaded(entries),
+            (None, Some(err)) => LeaderboardPage::Failed(err),
+            (None, None) => LeaderboardPage::Loading,
+        }
+    }
+
+    /// Starts downloading a page unless it is fresh or already loading
+    fn load(&mut self, name: &str, request: LeaderboardDataRequest, page: i32, now: Instant) {
+        if request == LeaderboardDataRequest::Global && page < 0 {
+            return;
+        }
+        let key = PageKey {
+            name: name.to_owned(),
+            request,
+            page,
+        };
+        let ttl = self.page_ttl;
+        let stored = self.pages.entry(key.clone()).or_insert(Page {
+            entries: None,
+            error: None,
+            fetched: None,
+            pending: Pending::None,
+            failures: 0,
+            failed: None,
+        });
+        if stored.pending != Pending::None || stored.is_waiting(now, ttl) {
+            return;
+        }
+        stored.pending = Pending::WaitingForHandle;
+
+        match self.handles.get(name) {
+            Some(Handle::Found(leaderboard)) => {
+                let leaderboard = leaderboard.clone();
+                self.download(key, &leaderboard);
+            }
+            Some(Handle::Finding) => {}
+            Some(Handle::NotFound) | None => {
+                self.handles.insert(name.to_owned(), Handle::Finding);
+                let owned = name.to_owned();
+                let responses = Arc::clone(&self.responses);
+                self.source.find_leaderboard(
+                    name,
+                    Box::new(move |result| {
+                        responses
+                            .lock()
+                            .unwrap()
+                            .push(Response::Handle(owned, result))
+                    }),
+                );
+            }
+        }
+    }
+
+    fn download(&mut self, key: PageKey, leaderboard: &Leaderboard) {
+        self.generation += 1;
+        let generation = self.generation;
+        if let Some(stored) = self.pages.get_mut(&key) {
+            stored.pending = Pending::InFlight(generation);
+        }
+        let size = self.page_size as i32;
+        let (start, end) = match key.request {
+            LeaderboardDataRequest::Global => (key.page * size + 1, (key.page + 1) * size),
+            LeaderboardDataRequest::GlobalAroundUser => {
+                let start = key.page * size - size / 2;
+                (start, start + size - 1)
+            }
+            LeaderboardDataRequest::Friends => (0, 0),
+        };
+        let responses = Arc::clone(&self.responses);
+        let request = key.request;
+        self.source.download_leaderboard_entries(
+            leaderboard,
+            request,
+            start,
+            end,
+            self.max_details_len,
+            Box::new(move |result| {
+                responses
+                    .lock()
+                    .unwrap()
+                    .push(Response::Page(key, generation, result))
+            }),
+        );
+    }
+
+    /// Drops the cached pages of a leaderboard, downloading them again
+    /// when they are next shown
+    pub fn invalidate(&mut self, name: &str) {
+        self.pages.retain(|key, _| key.name != name);
+    }
+
+    /// Drops all cached pages and leaderboard handles
+    pub fn clear(&mut self) {
+        self.pages.clear();
+        self.handles.clear();
+    }
+
+    /// Applies the result of
+    /// [`upload_leaderboard_score`](UserStats::upload_leaderboard_score)
+    /// to a leaderboard, dropping its pages if the user's rank changed
+    pub fn on_score_uploaded(&mut self, name: &str, uploaded: &LeaderboardScoreUploaded) {
+        if uploaded.was_changed && uploaded.global_rank_new != uploaded.global_rank_previous {
+            self.invalidate(name);
+        }
+    }
+
+    fn poll_at(&mut self, now: Instant) -> Vec<LeaderboardViewEvent> {
+        let responses = std::mem::take(&mut *self.responses.lock().unwrap());
+        for response in responses {
+            match response {
+                Response::Handle(name, result) => self.on_handle(name, result, now),
+                Response::Page(key, generation, result) => {
+                    self.on_page(key, generation, result, now)
+                }
+            }
+        }
+        std::mem::take(&mut self.events)
+    }
+
+    fn on_handle(
+        &mut self,
+        name: String,
+        result: Result<Option<Leaderboard>, SteamError>,
+        now: Instant,
+    ) {
+        let mut waiting: Vec<PageKey> = self
+            .pages
+            .iter()
+            .filter(|(key, page)| key.name == name && page.pending == Pending::WaitingForHandle)
+            .map(|(key, _)| key.clone())
+            .collect();
+        waiting.sort_by_key(|key| key.page);
+        match result {
+            Ok(Some(leaderboard)) => {
+                self.handles
+                    .insert(name, Handle::Found(leaderboard.clone()));
+                for key in waiting {
+                    self.download(key, &leaderboard);
+                }
+            }
+            Ok(None) => {
+                self.handles.insert(name.clone(), Handle::NotFound);
+                self.pages.retain(|key, _| key.name != name);
+                self.events.push(LeaderboardViewEvent::NotFound { name });
+            }
+            Err(error) => {
+                // Looked up again when the page is next shown
+                self.handles.remove(&name);
+                for key in waiting {
+                    self.fail(key, error, now);
+                }
+            }
+        }
+    }
+
+    fn on_page(
+        &mut self,
+        key: PageKey,
+        generation: u64,
+        result: Result<Vec<LeaderboardEntry>, SteamError>,
+        now: Instant,
+    ) {
+        match self.pages.get_mut(&key) {
+            Some(stored) if stored.pending == Pending::InFlight(generation) => {
+                stored.pending = Pending::None;
+            }
+            // Invalidated since
+            _ => return,
+        }
+        match result {
+            Ok(entries) => {
+                let stored = self.pages.get_mut(&key).unwrap();
+                stored.entries = Some(entries);
+                stored.error = None;
+                stored.fetched = Some(now);
+                stored.failures = 0;
+                stored.failed = None;
+                self.events.push(LeaderboardViewEvent::PageLoaded {
+                    name: key.name,
+                    request: key.request,
+                    page: key.page,
+                });
+            }
+            Err(error) => self.fail(key, error, now),
+        }
+    }
+
+    fn fail(&mut self, key: PageKey, error: SteamError, now: Instant) {
+        if let Some(stored) = self.pages.get_mut(&key) {
+            stored.pending = Pending::None;
+            stored.error = Some(error);
+            stored.failures += 1;
+            stored.failed = Some(now);
+        }
+        self.events.push(LeaderboardViewEvent::PageFailed {
+            name: key.name,
+            request: key.request,
+            page: key.page,
+            error,
+        });
+    }
+}
+
+impl<S: LeaderboardSource> CallbackStateMachine for LeaderboardView<S> {
+    type Event = LeaderboardViewEvent;
+
+    /// Applies finished downloads and returns the events since the last
+    /// call
+    fn poll(&mut self) -> Vec<LeaderboardViewEvent> {
+        self.poll_at(Instant::now())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    type Download = (LeaderboardDataRequest, i32, i32, LeaderboardEntriesCallback);
+
+    #[derive(Default)]
+    struct FakeSource {
+        finds: RefCell<Vec<(String, LeaderboardFindCallback)>>,
+        downloads: RefCell<Vec<Download>>,
+    }
+
+    impl FakeSource {
+        fn find(&self, found: bool) {
+            let (_, cb) = self.finds.borrow_mut().remove(0);
+            cb(Ok(found.then_some(Leaderboard(1))));
+        }
+
+        /// Answers the oldest download with ranks `start..=end`, or six
+        /// friends
+        fn download(&self) -> (i32, i32) {
+            let (request, start, end, cb) = self.downloads.borrow_mut().remove(0);
+            let ranks = match request {
+                LeaderboardDataRequest::Friends => 1..=6,
+                _ => start..=end,
+            };
+            cb(Ok(ranks.map(entry).collect()));
+            (start, end)
+        }
+    }
+
+    fn entry(rank: i32) -> LeaderboardEntry {
+        LeaderboardEntry {
+            user: SteamId(rank as u64),
+            global_rank: rank,
+            score: 1000 - rank,
+            details: Vec::new(),
+            ugc: None,
+        }
+    }
+
+    impl LeaderboardSource for FakeSource {
+        fn find_leaderboard(&self, name: &str, cb: LeaderboardFindCallback) {
+            self.finds.borrow_mut().push((name.to_owned(), cb));
+        }
+
+        fn download_leaderboard_entries(
+            &self,
+            _: &Leaderboard,
+            request: LeaderboardDataRequest,
+            start: i32,
+            end: i32,
+            _: usize,
+            cb: LeaderboardEntriesCallback,
+        ) {
+            self.downloads.borrow_mut().push((request, start, end, cb));
+        }
+    }
+
+    fn ranks(page: LeaderboardPage) -> Vec<i32> {
+        match page {
+            LeaderboardPage::Loaded(entries) => entries.iter().map(|e| e.global_rank).collect(),
+            other => panic!("page not loaded: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn pages_and_prefetch() {
+        use LeaderboardDataRequest::*;
+        let now = Instant::now();
+        let mut view = LeaderboardView::new(FakeSource::default()).with_page_size(5);
+        assert_eq!(
+            view.page_at("scores", Global, 0, now),
+            LeaderboardPage::Loading
+        );
+        assert_eq!(
+            view.page_at("scores", Global, 1, now),
+            LeaderboardPage::Loading
+        );
+        // The handle is only looked up once
+        assert_eq!(view.source().finds.borrow().len(), 1);
+        view.source().find(true);
+        view.poll_at(now);
+        assert!(view.leaderboard("scores").is_some());
+
+        // Pages 0 to 2 were requested, each once
+        assert_eq!(view.source().download(), (1, 5));
+        assert_eq!(view.source().download(), (6, 10));
+        assert_eq!(view.source().download(), (11, 15));
+        assert!(view.source().downloads.borrow().is_empty());
+        assert_eq!(view.poll_at(now).len(), 3);
+        assert_eq!(
+            ranks(view.page_at("scores", Global, 1, now)),
+            [6, 7, 8, 9, 10]
+        );
+
+        // Around the user, pages are prefetched in both directions
+        view.page_at("scores", GlobalAroundUser, 0, now);
+        let downloads: Vec<_> = view
+            .source()
+            .downloads
+            .borrow()
+            .iter()
+            .map(|d| (d.1, d.2))
+            .collect();
+        assert_eq!(downloads, [(-2, 2), (3, 7), (-7, -3)]);
+    }
+
+    #[test]
+    fn ttl_and_invalidation() {
+        use LeaderboardDataRequest::*;
+        let now = Instant::now();
+        let mut view = LeaderboardView::new(FakeSource::default())
+            .with_prefetch(0)
+            .with_page_ttl(Duration::from_secs(10));
+        view.page_at("scores", Global, 0, now);
+        view.source().find(true);
+        view.poll_at(now);
+        view.source().download();
+        view.poll_at(now);
+
+        // Stale pages are shown while downloaded again
+        let later = now + Duration::from_secs(10);
+        assert_eq!(ranks(view.page_at("scores", Global, 0, later)).len(), 10);
+        assert_eq!(view.source().downloads.borrow().len(), 1);
+
+        // Invalidated downloads are ignored
+        view.on_score_uploaded(
+            "scores",
+            &LeaderboardScoreUploaded {
+                score: 1,
+                was_changed: true,
+                global_rank_new: 3,
+                global_rank_previous: 5,
+            },
+        );
+        view.source().download();
+        assert!(view.poll_at(later).is_empty());
+        assert_eq!(
+            view.page_at("scores", Global, 0, later),
+            LeaderboardPage::Loading
+        );
+        view.source().download();
+        assert_eq!(view.poll_at(later).len(), 1);
+    }
+
+    #[test]
+    fn failed_pages_back_off() {
+        use LeaderboardDataRequest::*;
+        let now = Instant::now();
+        let mut view = LeaderboardView::new(FakeSource::default())
+            .with_prefetch(0)
+            .with_page_ttl(Duration::from_secs(3));
+        view.page_at("scores", Global, 0, now);
+        view.source().find(true);
+        view.poll_at(now);
+        let fail = |view: &mut LeaderboardView<FakeSource>, at: Instant| {
+            let (_, _, _, cb) = view.source().downloads.borrow_mut().remove(0);
+            cb(Err(SteamError::IOFailure));
+            view.poll_at(at);
+        };
+        fail(&mut view, now);
+        assert_eq!(
+            view.page_at("scores", Global, 0, now),
+            LeaderboardPage::Failed(SteamError::IOFailure)
+        );
+        assert!(view.source().downloads.borrow().is_empty());
+
+        // Retried after a second, then after two more
+        let later = now + Duration::from_secs(1);
+        view.page_at("scores", Global, 0, later);
+        assert_eq!(view.source().downloads.borrow().len(), 1);
+        fail(&mut view, later);
+        view.page_at("scores", Global, 0, later + Duration::from_millis(1999));
+        assert!(view.source().downloads.borrow().is_empty());
+        let retry = later + Duration::from_secs(2);
+        view.page_at("scores", Global, 0, retry);
+        assert_eq!(view.source().downloads.borrow().len(), 1);
+
+        // The delay does not grow past the TTL
+        fail(&mut view, retry);
+        view.page_at("scores", Global, 0, retry + Duration::from_secs(3));
+        assert_eq!(view.source().downloads.borrow().len(), 1);
+        view.source().download();
+        view.poll_at(retry);
+        assert_eq!(ranks(view.page_at("scores", Global, 0, retry)).len(), 10);
+    }
+
+    #[test]
+    fn friends_and_missing() {
+        use LeaderboardDataRequest::*;
+        let now = Instant::now();
+        let mut view = LeaderboardView::new(FakeSource::default()).with_page_size(4);
+        view.page_at("scores", Friends, 1, now);
+        view.source().find(true);
+        view.poll_at(now);
+        view.source().download();
+        view.poll_at(now);
+        // Friends come at once and are paged locally
+        assert_eq!(ranks(view.page_at("scores", Friends, 1, now)), [5, 6]);
+        assert_eq!(ranks(view.page_at("scores", Friends, 0, now)), [1, 2, 3, 4]);
+        assert!(view.source().downloads.borrow().is_empty());
+
+        view.page_at("missing", Global, 0, now);
+        view.source().find(false);
+        assert_eq!(
+            view.poll_at(now),
+            [LeaderboardViewEvent::NotFound {
+                name: "missing".into()
+            }]
+        );
+        assert_eq!(
+            view.page_at("missing", Global, 0, now),
+            LeaderboardPage::NotFound
+        );
+    }
+}
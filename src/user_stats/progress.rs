@@ -0,0 +1,225 @@
+use super::stats::AchievementProgressLimits;
+use super::*;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Thresholds {
+    Every(u32),
+    At(Vec<u32>),
+}
+
+/// Shows achievement progress pop-ups, like "47/100", when the stat an
+/// achievement is tied to crosses a threshold.
+///
+/// Progress is measured from the minimum of the stat's range, so for a
+/// range of 10 to 110 a stat of 60 is shown as "50/100". No pop-up is
+/// shown once the stat reaches the maximum, as the achievement is unlocked
+/// instead, or when the achievement is already unlocked.
+///
+/// # Example
+///
+/// ```no_run
+/// # use steamworks::*;
+/// const KILLS: IntStat = IntStat::new("Kills").min(0);
+/// const KILL_100: Achievement = Achievement::new("ACH_KILL_100");
+///
+/// let client = Client::init().unwrap();
+/// let stats = client.user_stats();
+/// let toast = ProgressToast::new(KILL_100, 100).every(25);
+/// // Shows "75/100" when going from 74 to 75 kills
+/// toast.add(&stats, KILLS, 1).unwrap();
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProgressToast {
+    achievement: Achievement,
+    min: i64,
+    max: i64,
+    thresholds: Thresholds,
+}
+
+impl ProgressToast {
+    /// Creates a helper for a stat going from 0 to `max`, showing progress
+    /// every 10% of `max`
+    pub fn new(achievement: Achievement, max: u32) -> ProgressToast {
+        ProgressToast::with_range(achievement, 0, max.into())
+    }
+
+    fn with_range(achievement: Achievement, min: i64, max: i64) -> ProgressToast {
+        let max = max.max(min);
+        ProgressToast {
+            achievement,
+            min,
+            max,
+            thresholds: Thresholds::Every(((max - min) / 10).clamp(1, u32::MAX.into()) as u32),
+        }
+    }
+
+    /// Creates a helper for the progress range configured on the
+    /// Steamworks website, see
+    /// [`get_progress_limits`](stats::AchievementHelper::get_progress_limits)
+    pub fn from_limits(
+        achievement: Achievement,
+        limits: AchievementProgressLimits,
+    ) -> ProgressToast {
+        let (min, max) = match limits {
+            AchievementProgressLimits::Int { min, max } => (min.into(), max.into()),
+            AchievementProgressLimits::Float { min, max } => (min as i64, max as i64),
+        };
+        ProgressToast::with_range(achievement, min, max)
+    }
+
+    /// Shows progress at every multiple of `step` from the minimum. A step
+    /// of 0 never shows progress.
+    pub fn every(mut self, step: u32) -> Self {
+        self.thresholds = Thresholds::Every(step);
+        self
+    }
+
+    /// Shows progress at the given values, measured from the minimum
+    pub fn at(mut self, thresholds: impl IntoIterator<Item = u32>) -> Self {
+        self.thresholds = Thresholds::At(thresholds.into_iter().collect());
+        self
+    }
+
+    /// Returns the achievement progress is shown for
+    pub fn achievement(&self) -> Achievement {
+        self.achievement
+    }
+
+    /// Returns the value progress is measured from
+    pub fn min(&self) -> i64 {
+        self.min
+    }
+
+    /// Returns the value the achievement is unlocked at
+    pub fn max(&self) -> i64 {
+        self.max
+    }
+
+    /// Returns the highest threshold crossed when the stat changes from
+    /// `old` to `new`, measured from the minimum, or `None` if no pop-up
+    /// should be shown
+    pub fn crossed(&self, old: i64, new: i64) -> Option<u32> {
+        let (old, new, max) = (old - self.min, new - self.min, self.max - self.min);
+        if new <= old || new >= max {
+            return None;
+        }
+        let threshold = match &self.thresholds {
+            Thresholds::Every(0) => return None,
+            Thresholds::Every(step) => new / i64::from(*step) * i64::from(*step),
+            Thresholds::At(thresholds) => thresholds
+                .iter()
+                .map(|&t| i64::from(t))
+                .filter(|&t| t <= new)
+                .max()?,
+        };
+        (threshold > old && threshold > 0).then_some(threshold as u32)
+    }
+
+    /// Shows the progress if the stat crossed a threshold when it changed
+    /// from `old` to `new`. Returns whether a pop-up was shown.
+    pub fn on_stat_changed<B: StatsBackend + ?Sized>(
+        &self,
+        backend: &B,
+        old: i32,
+        new: i32,
+    ) -> bool {
+        self.crossed(old.into(), new.into()).is_some()
+            && backend.indicate_achievement_progress(
+                self.achievement.name(),
+                (i64::from(new) - self.min) as u32,
+                (self.max - self.min) as u32,
+            )
+    }
+
+    /// Adds to an integer stat like [`IntStat::add`], showing the
+    /// progress if a threshold was crossed. Returns the new value.
+    pub fn add<B: StatsBackend + ?Sized>(
+        &self,
+        backend: &B,
+        stat: IntStat,
+        delta: i32,
+    ) -> Result<i32, StatError> {
+        let old = stat.get(backend)?;
+        let new = stat.add(backend, delta)?;
+        self.on_stat_changed(backend, old, new);
+        Ok(new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::schema::tests::MemoryStats;
+    use super::*;
+
+    const KILLS: IntStat = IntStat::new("kills").min(0);
+    const KILL_100: Achievement = Achievement::new("ACH_KILL_100");
+
+    #[test]
+    fn thresholds() {
+        let toast = ProgressToast::new(KILL_100, 100);
+        assert_eq!(toast.crossed(9, 10), Some(10));
+        assert_eq!(toast.crossed(10, 19), None);
+        assert_eq!(toast.crossed(15, 47), Some(40));
+        // Reaching the maximum unlocks the achievement instead
+        assert_eq!(toast.crossed(95, 100), None);
+        assert_eq!(toast.crossed(-5, 5), None);
+
+        let toast = toast.at([25, 50, 75]);
+        assert_eq!(toast.crossed(24, 25), Some(25));
+        assert_eq!(toast.crossed(26, 49), None);
+        assert_eq!(toast.crossed(26, 80), Some(75));
+
+        let toast = ProgressToast::from_limits(
+            KILL_100,
+            AchievementProgressLimits::Float {
+                min: 0.0,
+                max: 20.0,
+            },
+        );
+        assert_eq!(toast.max(), 20);
+        assert_eq!(toast.crossed(1, 2), Some(2));
+
+        // Thresholds are measured from the minimum
+        let toast = ProgressToast::from_limits(
+            KILL_100,
+            AchievementProgressLimits::Int { min: 10, max: 110 },
+        );
+        assert_eq!(toast.crossed(15, 19), None);
+        assert_eq!(toast.crossed(15, 31), Some(20));
+        assert_eq!(toast.crossed(100, 110), None);
+    }
+
+    #[test]
+    fn shows_progress() {
+        let stats = MemoryStats::default();
+        stats.ints.borrow_mut().insert("kills".into(), 46);
+        stats
+            .achievements
+            .borrow_mut()
+            .push(("ACH_KILL_100".into(), false));
+
+        let toast = ProgressToast::new(KILL_100, 100).every(25);
+        assert_eq!(toast.add(&stats, KILLS, 1), Ok(47));
+        assert!(stats.progress.borrow().is_empty());
+        assert_eq!(toast.add(&stats, KILLS, 3), Ok(50));
+        assert_eq!(
+            *stats.progress.borrow(),
+            [("ACH_KILL_100".to_owned(), 50, 100)]
+        );
+
+        // The pop-up shows the progress within the range
+        let ranged = ProgressToast::from_limits(
+            KILL_100,
+            AchievementProgressLimits::Int { min: 10, max: 110 },
+        )
+        .every(25);
+        assert!(ranged.on_stat_changed(&stats, 30, 35));
+        assert_eq!(
+            stats.progress.borrow()[1],
+            ("ACH_KILL_100".to_owned(), 25, 100)
+        );
+
+        KILL_100.unlock(&stats).unwrap();
+        assert!(!toast.on_stat_changed(&stats, 74, 75));
+    }
+}
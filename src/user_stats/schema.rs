@@ -0,0 +1,780 @@
+use super::*;
+
+/// The stats and achievements storage used by the typed stats of
+/// [`stats_schema!`], implemented by [`UserStats`].
+///
+/// Implementing it for an in-memory store allows testing game logic that
+/// uses stats without Steam.
+pub trait StatsBackend {
+    /// Returns the value of an integer stat, or `None` if the stat does
+    /// not exist or is not an integer
+    fn get_stat_i32(&self, name: &str) -> Option<i32>;
+    /// Sets an integer stat, returning whether it succeeded
+    fn set_stat_i32(&self, name: &str, value: i32) -> bool;
+    /// Returns the value of a float stat, or `None` if the stat does not
+    /// exist or is not a float
+    fn get_stat_f32(&self, name: &str) -> Option<f32>;
+    /// Sets a float stat, returning whether it succeeded
+    fn set_stat_f32(&self, name: &str, value: f32) -> bool;
+    /// Adds a session to an average rate stat, returning whether it
+    /// succeeded
+    fn update_avg_rate_stat(
+        &self,
+        name: &str,
+        count_this_session: f32,
+        session_length: f64,
+    ) -> bool;
+    /// Returns whether an achievement is unlocked, or `None` if it does
+    /// not exist
+    fn get_achievement(&self, name: &str) -> Option<bool>;
+    /// Unlocks an achievement, returning whether it succeeded
+    fn set_achievement(&self, name: &str) -> bool;
+    /// Locks an achievement again, returning whether it succeeded
+    fn clear_achievement(&self, name: &str) -> bool;
+    /// Shows the progress of an achievement to the user, returning
+    /// whether it succeeded
+    fn indicate_achievement_progress(&self, name: &str, current: u32, max: u32) -> bool;
+    /// Returns the API names of all achievements of the app
+    fn achievement_names(&self) -> Vec<String>;
+    /// Starts sending the changed stats and achievements to the server,
+    /// returning whether the request was made
+    fn store_stats(&self) -> bool;
+}
+
+impl StatsBackend for UserStats {
+    fn get_stat_i32(&self, name: &str) -> Option<i32> {
+        UserStats::get_stat_i32(self, name).ok()
+    }
+
+    fn set_stat_i32(&self, name: &str, value: i32) -> bool {
+        UserStats::set_stat_i32(self, name, value).is_ok()
+    }
+
+    fn get_stat_f32(&self, name: &str) -> Option<f32> {
+        UserStats::get_stat_f32(self, name).ok()
+    }
+
+    fn set_stat_f32(&self, name: &str, value: f32) -> bool {
+        UserStats::set_stat_f32(self, name, value).is_ok()
+    }
+
+    fn update_avg_rate_stat(
+        &self,
+        name: &str,
+        count_this_session: f32,
+        session_length: f64,
+    ) -> bool {
+        UserStats::update_avg_rate_stat(self, name, count_this_session, session_length).is_ok()
+    }
+
+    fn get_achievement(&self, name: &str) -> Option<bool> {
+        self.achievement(name).get().ok()
+    }
+
+    fn set_achievement(&self, name: &str) -> bool {
+        self.achievement(name).set().is_ok()
+    }
+
+    fn clear_achievement(&self, name: &str) -> bool {
+        self.achievement(name).clear().is_ok()
+    }
+
+    fn indicate_achievement_progress(&self, name: &str, current: u32, max: u32) -> bool {
+        self.achievement(name)
+            .indicate_progress(current, max)
+            .is_ok()
+    }
+
+    fn achievement_names(&self) -> Vec<String> {
+        // Unlike `get_achievement_names` this does not panic for apps
+        // without achievements
+        unsafe {
+            let count = sys::SteamAPI_ISteamUserStats_GetNumAchievements(self.user_stats);
+            (0..count)
+                .map(|i| {
+                    let name = sys::SteamAPI_ISteamUserStats_GetAchievementName(self.user_stats, i);
+                    CStr::from_ptr(name).to_string_lossy().into_owned()
+                })
+                .collect()
+        }
+    }
+
+    fn store_stats(&self) -> bool {
+        UserStats::store_stats(self).is_ok()
+    }
+}
+
+/// Errors from the typed stats of [`stats_schema!`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Error)]
+pub enum StatError {
+    /// Steam rejected the call, e.g. because the stats have not been
+    /// received yet or the stat does not exist
+    #[error("stat {0} is not available")]
+    Unavailable(&'static str),
+    /// The value is outside of the declared range
+    #[error("value of stat {0} is out of range")]
+    OutOfRange(&'static str),
+    /// The stat is increment-only and the value is lower than the current
+    /// value
+    #[error("stat {0} can only be incremented")]
+    Decreasing(&'static str),
+}
+
+/// The type of a stat as configured on the Steamworks website
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StatKind {
+    Int,
+    Float,
+    AvgRate,
+}
+
+/// A stat declared in a [`StatsSchema`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct StatDef {
+    pub name: &'static str,
+    pub kind: StatKind,
+}
+
+fn check_value<T: PartialOrd + Copy>(
+    name: &'static str,
+    value: T,
+    min: Option<T>,
+    max: Option<T>,
+    current: Option<T>,
+) -> Result<(), StatError> {
+    if min.is_some_and(|min| value < min) || max.is_some_and(|max| value > max) {
+        return Err(StatError::OutOfRange(name));
+    }
+    if current.is_some_and(|current| value < current) {
+        return Err(StatError::Decreasing(name));
+    }
+    Ok(())
+}
+
+/// An integer stat declared with [`stats_schema!`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct IntStat {
+    name: &'static str,
+    min: Option<i32>,
+    max: Option<i32>,
+    increment_only: bool,
+}
+
+impl IntStat {
+    /// Declares an integer stat with the given API name
+    pub const fn new(name: &'static str) -> IntStat {
+        IntStat {
+            name,
+            min: None,
+            max: None,
+            increment_only: false,
+        }
+    }
+
+    /// Sets the minimum value, matching the Steamworks configuration
+    pub const fn min(mut self, min: i32) -> IntStat {
+        self.min = Some(min);
+        self
+    }
+
+    /// Sets the maximum value, matching the Steamworks configuration
+    pub const fn max(mut self, max: i32) -> IntStat {
+        self.max = Some(max);
+        self
+    }
+
+    /// Only allows the value to increase, matching the Steamworks
+    /// configuration
+    pub const fn increment_only(mut self) -> IntStat {
+        self.increment_only = true;
+        self
+    }
+
+    /// Returns the API name of the stat
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the declaration of the stat in a [`StatsSchema`]
+    pub const fn def(&self) -> StatDef {
+        StatDef {
+            name: self.name,
+            kind: StatKind::Int,
+        }
+    }
+
+    /// Returns whether the stat may only increase
+    pub const fn is_increment_only(&self) -> bool {
+        self.increment_only
+    }
+
+    /// Checks the value against the declared range
+    pub(crate) fn check_range(&self, value: i32) -> Result<(), StatError> {
+        check_value(self.name, value, self.min, self.max, None)
+    }
+
+    /// Returns the value of the stat
+    pub fn get<B: StatsBackend + ?Sized>(&self, backend: &B) -> Result<i32, StatError> {
+        backend
+            .get_stat_i32(self.name)
+            .ok_or(StatError::Unavailable(self.name))
+    }
+
+    /// Sets the value of the stat after checking the declared
+    /// constraints
+    pub fn set<B: StatsBackend + ?Sized>(&self, backend: &B, value: i32) -> Result<(), StatError> {
+        let current = if self.increment_only {
+            Some(self.get(backend)?)
+        } else {
+            None
+        };
+        check_value(self.name, value, self.min, self.max, current)?;
+        if backend.set_stat_i32(self.name, value) {
+            Ok(())
+        } else {
+            Err(StatError::Unavailable(self.name))
+        }
+    }
+
+    /// Adds to the value of the stat, clamping it to the declared range.
+    /// Returns the new value.
+    pub fn add<B: StatsBackend + ?Sized>(&self, backend: &B, delta: i32) -> Result<i32, StatError> {
+        let mut value = self.get(backend)?.saturating_add(delta);
+        if let Some(max) = self.max {
+            value = value.min(max);
+        }
+        if let Some(min) = self.min {
+            value = value.max(min);
+        }
+        self.set(backend, value)?;
+        Ok(value)
+    }
+}
+
+/// A float stat declared with [`stats_schema!`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FloatStat {
+    name: &'static str,
+    min: Option<f32>,
+    max: Option<f32>,
+    increment_only: bool,
+}
+
+impl FloatStat {
+    /// Declares a float stat with the given API name
+    pub const fn new(name: &'static str) -> FloatStat {
+        FloatStat {
+            name,
+            min: None,
+            max: None,
+            increment_only: false,
+        }
+    }
+
+    /// Sets the minimum value, matching the Steamworks configuration
+    pub const fn min(mut self, min: f32) -> FloatStat {
+        self.min = Some(min);
+        self
+    }
+
+    /// Sets the maximum value, matching the Steamworks configuration
+    pub const fn max(mut self, max: f32) -> FloatStat {
+        self.max = Some(max);
+        self
+    }
+
+    /// Only allows the value to increase, matching the Steamworks
+    /// configuration
+    pub const fn increment_only(mut self) -> FloatStat {
+        self.increment_only = true;
+        self
+    }
+
+    /// Returns the API name of the stat
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the declaration of the stat in a [`StatsSchema`]
+    pub const fn def(&self) -> StatDef {
+        StatDef {
+            name: self.name,
+            kind: StatKind::Float,
+        }
+    }
+
+    /// Returns whether the stat may only increase
+    pub const fn is_increment_only(&self) -> bool {
+        self.increment_only
+    }
+
+    /// Checks the value against the declared range
+    pub(crate) fn check_range(&self, value: f32) -> Result<(), StatError> {
+        check_value(self.name, value, self.min, self.max, None)
+    }
+
+    /// Returns the value of the stat
+    pub fn get<B: StatsBackend + ?Sized>(&self, backend: &B) -> Result<f32, StatError> {
+        backend
+            .get_stat_f32(self.name)
+            .ok_or(StatError::Unavailable(self.name))
+    }
+
+    /// Sets the value of the stat after checking the declared
+    /// constraints. `NaN` is always out of range.
+    pub fn set<B: StatsBackend + ?Sized>(&self, backend: &B, value: f32) -> Result<(), StatError> {
+        if value.is_nan() {
+            return Err(StatError::OutOfRange(self.name));
+        }
+        let current = if self.increment_only {
+            Some(self.get(backend)?)
+        } else {
+            None
+        };
+        check_value(self.name, value, self.min, self.max, current)?;
+        if backend.set_stat_f32(self.name, value) {
+            Ok(())
+        } else {
+            Err(StatError::Unavailable(self.name))
+        }
+    }
+
+    /// Adds to the value of the stat, clamping it to the declared range.
+    /// Returns the new value.
+    pub fn add<B: StatsBackend + ?Sized>(&self, backend: &B, delta: f32) -> Result<f32, StatError> {
+        let mut value = self.get(backend)? + delta;
+        if let Some(max) = self.max {
+            value = value.min(max);
+        }
+        if let Some(min) = self.min {
+            value = value.max(min);
+        }
+        self.set(backend, value)?;
+        Ok(value)
+    }
+}
+
+/// An average rate stat declared with [`stats_schema!`], e.g. points per
+/// hour
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct AvgRateStat {
+    name: &'static str,
+}
+
+impl AvgRateStat {
+    /// Declares an average rate stat with the given API name
+    pub const fn new(name: &'static str) -> AvgRateStat {
+        AvgRateStat { name }
+    }
+
+    /// Returns the API name of the stat
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the declaration of the stat in a [`StatsSchema`]
+    pub const fn def(&self) -> StatDef {
+        StatDef {
+            name: self.name,
+            kind: StatKind::AvgRate,
+        }
+    }
+
+    /// Returns the current average rate
+    pub fn get<B: StatsBackend + ?Sized>(&self, backend: &B) -> Result<f32, StatError> {
+        backend
+            .get_stat_f32(self.name)
+            .ok_or(StatError::Unavailable(self.name))
+    }
+
+    /// Adds a session in which `count` happened over `session_length`, in
+    /// the units of the stat's window, e.g. the points scored in a match
+    /// and the length of the match in hours. The session length must be
+    /// positive.
+    pub fn update<B: StatsBackend + ?Sized>(
+        &self,
+        backend: &B,
+        count: f32,
+        session_length: f64,
+    ) -> Result<(), StatError> {
+        if count.is_nan() || session_length.is_nan() || session_length <= 0.0 {
+            return Err(StatError::OutOfRange(self.name));
+        }
+        if backend.update_avg_rate_stat(self.name, count, session_length) {
+            Ok(())
+        } else {
+            Err(StatError::Unavailable(self.name))
+        }
+    }
+}
+
+/// An achievement declared with [`stats_schema!`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Achievement {
+    name: &'static str,
+}
+
+impl Achievement {
+    /// Declares an achievement with the given API name
+    pub const fn new(name: &'static str) -> Achievement {
+        Achievement { name }
+    }
+
+    /// Returns the API name of the achievement
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns whether the achievement is unlocked
+    pub fn is_unlocked<B: StatsBackend + ?Sized>(&self, backend: &B) -> Result<bool, StatError> {
+        backend
+            .get_achievement(self.name)
+            .ok_or(StatError::Unavailable(self.name))
+    }
+
+    /// Unlocks the achievement. The unlock is shown to the user once the
+    /// stats are stored.
+    pub fn unlock<B: StatsBackend + ?Sized>(&self, backend: &B) -> Result<(), StatError> {
+        if backend.set_achievement(self.name) {
+            Ok(())
+        } else {
+            Err(StatError::Unavailable(self.name))
+        }
+    }
+
+    /// Locks the achievement again, mostly useful for testing
+    pub fn clear<B: StatsBackend + ?Sized>(&self, backend: &B) -> Result<(), StatError> {
+        if backend.clear_achievement(self.name) {
+            Ok(())
+        } else {
+            Err(StatError::Unavailable(self.name))
+        }
+    }
+}
+
+/// The differences between a [`StatsSchema`] and the stats configured
+/// for the app, returned by [`StatsSchema::validate`]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Error)]
+#[error(
+    "stats schema mismatch: {} invalid stats, {} missing achievements, {} undeclared achievements",
+    .invalid_stats.len(),
+    .missing_achievements.len(),
+    .undeclared_achievements.len()
+)]
+pub struct SchemaMismatch {
+    /// Declared stats that do not exist or have a different type
+    pub invalid_stats: Vec<&'static str>,
+    /// Declared achievements that do not exist
+    pub missing_achievements: Vec<&'static str>,
+    /// Achievements of the app that are not declared
+    pub undeclared_achievements: Vec<String>,
+}
+
+/// The stats and achievements declared with [`stats_schema!`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StatsSchema {
+    pub stats: &'static [StatDef],
+    pub achievements: &'static [&'static str],
+}
+
+impl StatsSchema {
+    /// Checks the schema against the stats configured for the app.
+    ///
+    /// This should be called once [`UserStatsReceived`] has been received
+    /// for the current user, as Steam only knows the stats after that.
+    pub fn validate<B: StatsBackend + ?Sized>(&self, backend: &B) -> Result<(), SchemaMismatch> {
+        let mut mismatch = SchemaMismatch::default();
+        for stat in self.stats {
+            let exists = match stat.kind {
+                StatKind::Int => backend.get_stat_i32(stat.name).is_some(),
+                StatKind::Float | StatKind::AvgRate => backend.get_stat_f32(stat.name).is_some(),
+            };
+            if !exists {
+                mismatch.invalid_stats.push(stat.name);
+            }
+        }
+        let names = backend.achievement_names();
+        mismatch.missing_achievements = self
+            .achievements
+            .iter()
+            .copied()
+            .filter(|declared| !names.iter().any(|name| name == declared))
+            .collect();
+        mismatch.undeclared_achievements = names
+            .into_iter()
+            .filter(|name| !self.achievements.contains(&name.as_str()))
+            .collect();
+
+        if mismatch == SchemaMismatch::default() {
+            Ok(())
+        } else {
+            Err(mismatch)
+        }
+    }
+}
+
+/// Declares the stats and achievements of a game as typed constants.
+///
+/// Each stat is declared with its API name and the constraints configured
+/// on the Steamworks website, which are checked before values are sent to
+/// Steam. Misspelled stats become compile errors instead of failing at
+/// runtime, and the whole schema can be checked against the app's
+/// configuration with [`StatsSchema::validate`].
+///
+/// # Example
+///
+/// ```no_run
+/// # use steamworks::*;
+/// stats_schema! {
+///     /// The stats of the game
+///     pub struct GameStats {
+///         stats {
+///             GAMES_PLAYED: IntStat = IntStat::new("NumGames").min(0).increment_only(),
+///             FEET_TRAVELED: FloatStat = FloatStat::new("FeetTraveled"),
+///             AVERAGE_SPEED: AvgRateStat = AvgRateStat::new("AverageSpeed"),
+///         }
+///         achievements {
+///             WIN_ONE_GAME = "ACH_WIN_ONE_GAME",
+///             TRAVEL_FAR = "ACH_TRAVEL_FAR_ACCUM",
+///         }
+///     }
+/// }
+///
+/// let client = Client::init().unwrap();
+/// let c2 = client.clone();
+/// let _cb = client.register_callback(move |received: UserStatsReceived| {
+///     if received.result.is_ok() {
+///         if let Err(mismatch) = GameStats::SCHEMA.validate(&c2.user_stats()) {
+///             eprintln!("{:?}", mismatch);
+///         }
+///     }
+/// });
+///
+/// // Once the stats have been received
+/// let stats = client.user_stats();
+/// GameStats::GAMES_PLAYED.add(&stats, 1).unwrap();
+/// GameStats::WIN_ONE_GAME.unlock(&stats).unwrap();
+/// stats.store_stats().unwrap();
+/// ```
+#[macro_export]
+macro_rules! stats_schema {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            stats {
+                $($(#[$stat_meta:meta])* $stat:ident : $stat_ty:ty = $stat_def:expr),* $(,)?
+            }
+            achievements {
+                $($(#[$achievement_meta:meta])* $achievement:ident = $achievement_name:expr),* $(,)?
+            }
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug)]
+        $vis struct $name;
+
+        impl $name {
+            $(
+                $(#[$stat_meta])*
+                pub const $stat: $stat_ty = $stat_def;
+            )*
+            $(
+                $(#[$achievement_meta])*
+                pub const $achievement: $crate::Achievement =
+                    $crate::Achievement::new($achievement_name);
+            )*
+
+            /// All declared stats and achievements
+            pub const SCHEMA: $crate::StatsSchema = $crate::StatsSchema {
+                stats: &[$(Self::$stat.def()),*],
+                achievements: &[$(Self::$achievement.name()),*],
+            };
+        }
+    };
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::cell::{Cell, RefCell};
+    use std::collections::HashMap;
+
+    #[derive(Default)]
+    pub(crate) struct MemoryStats {
+        pub(crate) ints: RefCell<HashMap<String, i32>>,
+        pub(crate) floats: RefCell<HashMap<String, f32>>,
+        pub(crate) achievements: RefCell<Vec<(String, bool)>>,
+        pub(crate) stores: Cell<u32>,
+        pub(crate) progress: RefCell<Vec<(String, u32, u32)>>,
+    }
+
+    impl StatsBackend for MemoryStats {
+        fn get_stat_i32(&self, name: &str) -> Option<i32> {
+            self.ints.borrow().get(name).copied()
+        }
+
+        fn set_stat_i32(&self, name: &str, value: i32) -> bool {
+            self.ints
+                .borrow_mut()
+                .get_mut(name)
+                .map(|v| *v = value)
+                .is_some()
+        }
+
+        fn get_stat_f32(&self, name: &str) -> Option<f32> {
+            self.floats.borrow().get(name).copied()
+        }
+
+        fn set_stat_f32(&self, name: &str, value: f32) -> bool {
+            self.floats
+                .borrow_mut()
+                .get_mut(name)
+                .map(|v| *v = value)
+                .is_some()
+        }
+
+        fn update_avg_rate_stat(
+            &self,
+            name: &str,
+            count_this_session: f32,
+            session_length: f64,
+        ) -> bool {
+            // Only the rate of the last session is kept
+            self.set_stat_f32(
+                name,
+                (f64::from(count_this_session) / session_length) as f32,
+            )
+        }
+
+        fn get_achievement(&self, name: &str) -> Option<bool> {
+            let achievements = self.achievements.borrow();
+            achievements
+                .iter()
+                .find(|(n, _)| n == name)
+                .map(|(_, a)| *a)
+        }
+
+        fn set_achievement(&self, name: &str) -> bool {
+            let mut achievements = self.achievements.borrow_mut();
+            achievements
+                .iter_mut()
+                .find(|(n, _)| n == name)
+                .map(|(_, a)| *a = true)
+                .is_some()
+        }
+
+        fn clear_achievement(&self, name: &str) -> bool {
+            let mut achievements = self.achievements.borrow_mut();
+            achievements
+                .iter_mut()
+                .find(|(n, _)| n == name)
+                .map(|(_, a)| *a = false)
+                .is_some()
+        }
+
+        fn indicate_achievement_progress(&self, name: &str, current: u32, max: u32) -> bool {
+            if self.get_achievement(name) != Some(false) {
+                return false;
+            }
+            self.progress
+                .borrow_mut()
+                .push((name.to_owned(), current, max));
+            true
+        }
+
+        fn achievement_names(&self) -> Vec<String> {
+            self.achievements
+                .borrow()
+                .iter()
+                .map(|(n, _)| n.clone())
+                .collect()
+        }
+
+        fn store_stats(&self) -> bool {
+            self.stores.set(self.stores.get() + 1);
+            true
+        }
+    }
+
+    stats_schema! {
+        struct TestStats {
+            stats {
+                GAMES: IntStat = IntStat::new("games").min(0).max(100).increment_only(),
+                HEALTH: IntStat = IntStat::new("health").min(0).max(10),
+                DISTANCE: FloatStat = FloatStat::new("distance").min(0.0),
+                SPEED: AvgRateStat = AvgRateStat::new("speed"),
+            }
+            achievements {
+                WIN = "ACH_WIN",
+            }
+        }
+    }
+
+    fn backend() -> MemoryStats {
+        let stats = MemoryStats::default();
+        stats.ints.borrow_mut().insert("games".into(), 5);
+        stats.ints.borrow_mut().insert("health".into(), 10);
+        stats.floats.borrow_mut().insert("distance".into(), 1.5);
+        stats.floats.borrow_mut().insert("speed".into(), 2.0);
+        stats
+            .achievements
+            .borrow_mut()
+            .push(("ACH_WIN".into(), false));
+        stats
+    }
+
+    #[test]
+    fn constraints() {
+        let stats = backend();
+        assert_eq!(TestStats::GAMES.add(&stats, 2), Ok(7));
+        assert_eq!(
+            TestStats::GAMES.set(&stats, 6),
+            Err(StatError::Decreasing("games"))
+        );
+        assert_eq!(
+            TestStats::GAMES.set(&stats, 101),
+            Err(StatError::OutOfRange("games"))
+        );
+        assert_eq!(TestStats::GAMES.add(&stats, 1000), Ok(100));
+
+        assert_eq!(TestStats::HEALTH.add(&stats, -20), Ok(0));
+        assert_eq!(TestStats::HEALTH.set(&stats, 3), Ok(()));
+        assert_eq!(TestStats::HEALTH.get(&stats), Ok(3));
+
+        assert_eq!(
+            TestStats::DISTANCE.set(&stats, f32::NAN),
+            Err(StatError::OutOfRange("distance"))
+        );
+        assert_eq!(TestStats::DISTANCE.add(&stats, 1.0), Ok(2.5));
+        assert_eq!(TestStats::SPEED.get(&stats), Ok(2.0));
+        assert_eq!(
+            TestStats::SPEED.update(&stats, 10.0, 0.0),
+            Err(StatError::OutOfRange("speed"))
+        );
+        assert_eq!(TestStats::SPEED.update(&stats, 10.0, 4.0), Ok(()));
+        assert_eq!(TestStats::SPEED.get(&stats), Ok(2.5));
+
+        assert_eq!(TestStats::WIN.is_unlocked(&stats), Ok(false));
+        TestStats::WIN.unlock(&stats).unwrap();
+        assert_eq!(TestStats::WIN.is_unlocked(&stats), Ok(true));
+    }
+
+    #[test]
+    fn validate() {
+        let stats = backend();
+        assert_eq!(TestStats::SCHEMA.validate(&stats), Ok(()));
+
+        // A stat with the wrong type, a missing and an undeclared achievement
+        stats.ints.borrow_mut().remove("games");
+        stats.floats.borrow_mut().insert("games".into(), 0.0);
+        *stats.achievements.borrow_mut() = vec![("ACH_LOSE".into(), false)];
+        assert_eq!(
+            TestStats::SCHEMA.validate(&stats),
+            Err(SchemaMismatch {
+                invalid_stats: vec!["games"],
+                missing_achievements: vec!["ACH_WIN"],
+                undeclared_achievements: vec!["ACH_LOSE".to_owned()],
+            })
+        );
+    }
+}
@@ -0,0 +1,667 @@
+use super::*;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// The first line of a journal file
+const JOURNAL_HEADER: &str = "steamworks-stats-journal 1";
+/// The longest delay between retries of a failed store
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(5 * 60);
+
+/// The value of a stat
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum StatValue {
+    Int(i32),
+    Float(f32),
+}
+
+/// A write to an increment-only stat that was dropped because the server
+/// value is higher, e.g. because the game was played on another computer
+#[derive(Clone, Debug, PartialEq)]
+pub struct StatConflict {
+    pub name: String,
+    pub local: StatValue,
+    pub server: StatValue,
+}
+
+/// An event returned by [`StatsSession::poll`]
+#[derive(Debug)]
+pub enum StatsSessionEvent {
+    /// The buffered writes were applied after the stats were received
+    Replayed { writes: usize },
+    /// A write was dropped in favor of the server value
+    Conflict(StatConflict),
+    /// Steam rejected a write, e.g. because the stat does not exist. The
+    /// write is dropped.
+    Rejected(String),
+    /// The stats were stored on the server
+    Stored,
+    /// Storing the stats failed and will be retried
+    StoreFailed(SteamError),
+    /// The journal file could not be written
+    JournalFailed(io::Error),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Write {
+    Int {
+        name: String,
+        value: i32,
+        increment_only: bool,
+    },
+    Float {
+        name: String,
+        value: f32,
+        increment_only: bool,
+    },
+    Achievement {
+        name: String,
+    },
+}
+
+impl Write {
+    fn name(&self) -> &str {
+        match self {
+            Write::Int { name, .. } | Write::Float { name, .. } | Write::Achievement { name } => {
+                name
+            }
+        }
+    }
+
+    fn is_achievement(&self) -> bool {
+        matches!(self, Write::Achievement { .. })
+    }
+
+    fn to_line(&self) -> String {
+        match self {
+            Write::Int {
+                name,
+                value,
+                increment_only,
+            } => format!("i\t{}\t{}\t{}", name, value, *increment_only as u8),
+            Write::Float {
+                name,
+                value,
+                increment_only,
+            } => format!("f\t{}\t{}\t{}", name, value, *increment_only as u8),
+            Write::Achievement { name } => format!("a\t{}", name),
+        }
+    }
+
+    fn from_line(line: &str) -> Option<Write> {
+        let mut fields = line.split('\t');
+        let kind = fields.next()?;
+        let name = fields.next()?.to_owned();
+        let write = match kind {
+            "i" => Write::Int {
+                name,
+                value: fields.next()?.parse().ok()?,
+                increment_only: fields.next()? == "1",
+            },
+            "f" => Write::Float {
+                name,
+                value: fields.next()?.parse().ok()?,
+                increment_only: fields.next()? == "1",
+            },
+            "a" => Write::Achievement { name },
+            _ => return None,
+        };
+        Some(write)
+    }
+}
+
+#[derive(Debug)]
+struct Entry {
+    write: Write,
+    /// Whether the write has been applied to Steam's in-memory stats
+    applied: bool,
+    /// Whether the write is part of the store in flight
+    storing: bool,
+}
+
+enum Applied {
+    Ok,
+    Conflict(StatConflict),
+    Rejected,
+}
+
+/// Buffers stat and achievement writes until they are safely stored on
+/// the server.
+///
+/// Writes made before [`UserStatsReceived`] has arrived, e.g. because
+/// Steam is offline, are kept in memory and replayed once the stats are
+/// received. Writes stay buffered until a [`UserStatsStored`] confirms
+/// them, and can also be kept in a journal file with
+/// [`with_journal`](Self::with_journal) so they survive a crash or a
+/// game that is closed while offline.
+///
+/// Calls to `store_stats` are rate limited to one per
+/// [`store interval`](Self::with_store_interval), as Valve recommends,
+/// unless [`store_now`](Self::store_now) is called for important events
+/// such as the end of a match. Failed stores are retried with backoff.
+///
+/// Writes to increment-only stats that are lower than the server value
+/// are dropped and reported as [`StatsSessionEvent::Conflict`].
+///
+/// The session is a [`CallbackStateMachine`] that needs
+/// [`UserStatsReceived`] before it writes anything and [`UserStatsStored`]
+/// to finish a store. Stores are only started from
+/// [`poll`](CallbackStateMachine::poll), so it should be called regularly
+/// even without callbacks.
+pub struct StatsSession<B: StatsBackend> {
+    backend: B,
+    user: SteamId,
+    received: bool,
+    entries: Vec<Entry>,
+    journal: Option<PathBuf>,
+    store_interval: Duration,
+    retry_delay: Duration,
+    last_store: Option<Instant>,
+    failures: u32,
+    dirty: bool,
+    important: bool,
+    in_flight: bool,
+    events: Vec<StatsSessionEvent>,
+}
+
+impl<B: StatsBackend> StatsSession<B> {
+    /// Creates a session for the stats of `user`, usually the current
+    /// user. Stores are rate limited to one every five minutes.
+    pub fn new(backend: B, user: SteamId) -> Self {
+        StatsSession {
+            backend,
+            user,
+            received: false,
+            entries: Vec::new(),
+            journal: None,
+            store_interval: Duration::from_secs(5 * 60),
+            retry_delay: Duration::from_secs(10),
+            last_store: None,
+            failures: 0,
+            dirty: false,
+            important: false,
+            in_flight: false,
+            events: Vec::new(),
+        }
+    }
+
+    /// Keeps unstored writes in a journal file, loading the writes left
+    /// in it by a previous session
+    pub fn with_journal(mut self, path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        match fs::read_to_string(&path) {
+            Ok(contents) => {
+                let mut lines = contents.lines();
+                if lines.next() != Some(JOURNAL_HEADER) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "not a stats journal",
+                    ));
+                }
+                for write in lines.filter_map(Write::from_line) {
+                    self.push(write);
+                }
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err),
+        }
+        self.journal = Some(path);
+        Ok(self)
+    }
+
+    /// Sets the minimum time between two stores, unless
+    /// [`store_now`](Self::store_now) is called
+    pub fn with_store_interval(mut self, interval: Duration) -> Self {
+        self.store_interval = interval;
+        self
+    }
+
+    /// Sets the delay before the first retry of a failed store, which
+    /// doubles with each failure
+    pub fn with_retry_delay(mut self, delay: Duration) -> Self {
+        self.retry_delay = delay;
+        self
+    }
+
+    /// Returns the backend the writes are applied to
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    /// Returns whether the stats have been received
+    pub fn is_received(&self) -> bool {
+        self.received
+    }
+
+    /// Returns the number of writes that have not been stored on the
+    /// server yet
+    pub fn unstored_writes(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Sets an integer stat
+    pub fn set_int(&mut self, stat: IntStat, value: i32) -> Result<(), StatError> {
+        stat.check_range(value)?;
+        self.write(Write::Int {
+            name: stat.name().to_owned(),
+            value,
+            increment_only: stat.is_increment_only(),
+        });
+        Ok(())
+    }
+
+    /// Sets a float stat
+    pub fn set_float(&mut self, stat: FloatStat, value: f32) -> Result<(), StatError> {
+        if value.is_nan() {
+            return Err(StatError::OutOfRange(stat.name()));
+        }
+        stat.check_range(value)?;
+        self.write(Write::Float {
+            name: stat.name().to_owned(),
+            value,
+            increment_only: stat.is_increment_only(),
+        });
+        Ok(())
+    }
+
+    /// Unlocks an achievement
+    pub fn unlock(&mut self, achievement: Achievement) {
+        self.write(Write::Achievement {
+            name: achievement.name().to_owned(),
+        });
+    }
+
+    /// Stores the stats on the next [`poll`](CallbackStateMachine::poll) regardless of the
+    /// store interval
+    pub fn store_now(&mut self) {
+        self.important = true;
+    }
+
+    fn push(&mut self, write: Write) {
+        // A later write to the same stat replaces the earlier one
+        self.entries.retain(|e| {
+            e.write.name() != write.name() || e.write.is_achievement() != write.is_achievement()
+        });
+        self.entries.push(Entry {
+            write,
+            applied: false,
+            storing: false,
+        });
+    }
+
+    fn write(&mut self, write: Write) {
+        self.push(write);
+        if self.received {
+            self.apply_pending();
+        }
+        self.save_journal();
+    }
+
+    fn apply(&self, write: &Write) -> Applied {
+        let backend = &self.backend;
+        match write {
+            Write::Int {
+                name,
+                value,
+                increment_only,
+            } => {
+                if *increment_only {
+                    match backend.get_stat_i32(name) {
+                        Some(server) if server > *value => {
+                            return Applied::Conflict(StatConflict {
+                                name: name.clone(),
+                                local: StatValue::Int(*value),
+                                server: StatValue::Int(server),
+                            })
+                        }
+                        Some(_) => {}
+                        None => return Applied::Rejected,
+                    }
+                }
+                if backend.set_stat_i32(name, *value) {
+                    Applied::Ok
+                } else {
+                    Applied::Rejected
+                }
+            }
+            Write::Float {
+                name,
+                value,
+                increment_only,
+            } => {
+                if *increment_only {
+                    match backend.get_stat_f32(name) {
+                        Some(server) if server > *value => {
+                            return Applied::Conflict(StatConflict {
+                                name: name.clone(),
+                                local: StatValue::Float(*value),
+                                server: StatValue::Float(server),
+                            })
+                        }
+                        Some(_) => {}
+                        None => return Applied::Rejected,
+                    }
+                }
+                if backend.set_stat_f32(name, *value) {
+                    Applied::Ok
+                } else {
+                    Applied::Rejected
+                }
+            }
+            Write::Achievement { name } => {
+                if backend.set_achievement(name) {
+                    Applied::Ok
+                } else {
+                    Applied::Rejected
+                }
+            }
+        }
+    }
+
+    /// Applies the writes that have not been applied yet, returning how
+    /// many were applied
+    fn apply_pending(&mut self) -> usize {
+        let mut applied = 0;
+        let mut i = 0;
+        while i < self.entries.len() {
+            if self.entries[i].applied {
+                i += 1;
+                continue;
+            }
+            match self.apply(&self.entries[i].write) {
+                Applied::Ok => {
+                    self.entries[i].applied = true;
+                    self.dirty = true;
+                    applied += 1;
+                    i += 1;
+                }
+                Applied::Conflict(conflict) => {
+                    self.entries.remove(i);
+                    self.events.push(StatsSessionEvent::Conflict(conflict));
+                }
+                Applied::Rejected => {
+                    let entry = self.entries.remove(i);
+                    self.events
+                        .push(StatsSessionEvent::Rejected(entry.write.name().to_owned()));
+                }
+            }
+        }
+        applied
+    }
+
+    fn save_journal(&mut self) {
+        let Some(path) = &self.journal else {
+            return;
+        };
+        let result = if self.entries.is_empty() {
+            match fs::remove_file(path) {
+                Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+                _ => Ok(()),
+            }
+        } else {
+            let mut contents = String::from(JOURNAL_HEADER);
+            for entry in &self.entries {
+                contents.push('\n');
+                contents.push_str(&entry.write.to_line());
+            }
+            contents.push('\n');
+            // Write a new file first so a crash cannot leave a partial
+            // journal behind
+            let temp = path.with_extension("tmp");
+            fs::write(&temp, contents).and_then(|_| fs::rename(&temp, path))
+        };
+        if let Err(err) = result {
+            self.events.push(StatsSessionEvent::JournalFailed(err));
+        }
+    }
+
+    /// Applies a [`UserStatsReceived`], replaying the buffered writes if
+    /// it is for the session's user
+    pub fn on_user_stats_received(&mut self, received: &UserStatsReceived) {
+        if received.steam_id != self.user || received.result.is_err() {
+            return;
+        }
+        self.received = true;
+        // Steam's in-memory stats were replaced, so everything that has not
+        // been stored is applied again
+        for entry in &mut self.entries {
+            entry.applied = false;
+            entry.storing = false;
+        }
+        self.in_flight = false;
+        let writes = self.apply_pending();
+        if writes > 0 {
+            self.events.push(StatsSessionEvent::Replayed { writes });
+            self.important = true;
+        }
+        self.save_journal();
+    }
+
+    /// Applies a [`UserStatsStored`]
+    pub fn on_user_stats_stored(&mut self, stored: &UserStatsStored) {
+        if !self.in_flight {
+            return;
+        }
+        self.in_flight = false;
+        match stored.result {
+            Ok(()) => {
+                self.failures = 0;
+                self.entries.retain(|e| !e.storing);
+                self.events.push(StatsSessionEvent::Stored);
+                self.save_journal();
+            }
+            Err(err) => {
+                self.failures += 1;
+                self.dirty = true;
+                for entry in &mut self.entries {
+                    entry.storing = false;
+                }
+                self.events.push(StatsSessionEvent::StoreFailed(err));
+            }
+        }
+    }
+
+    fn poll_at(&mut self, now: Instant) -> Vec<StatsSessionEvent> {
+        if self.received && self.dirty && !self.in_flight {
+            let wait = if self.failures > 0 {
+                self.retry_delay
+                    .saturating_mul(1 << (self.failures - 1).min(16))
+                    .min(MAX_RETRY_DELAY)
+            } else if self.important {
+                Duration::ZERO
+            } else {
+                self.store_interval
+            };
+            let allowed = match self.last_store {
+                Some(last) => now.saturating_duration_since(last) >= wait,
+                None => true,
+            };
+            if allowed {
+                self.last_store = Some(now);
+                if self.backend.store_stats() {
+                    self.in_flight = true;
+                    self.dirty = false;
+                    self.important = false;
+                    for entry in &mut self.entries {
+                        entry.storing = entry.applied;
+                    }
+                } else {
+                    self.failures += 1;
+                }
+            }
+        }
+        std::mem::take(&mut self.events)
+    }
+}
+
+impl<B: StatsBackend> CallbackStateMachine for StatsSession<B> {
+    type Event = StatsSessionEvent;
+
+    fn handle_callback(&mut self, callback: &CallbackResult) {
+        match callback {
+            CallbackResult::UserStatsReceived(received) => self.on_user_stats_received(received),
+            CallbackResult::UserStatsStored(stored) => self.on_user_stats_stored(stored),
+            _ => {}
+        }
+    }
+
+    /// Stores the stats if needed and allowed, and returns all events
+    /// since the last call
+    fn poll(&mut self) -> Vec<StatsSessionEvent> {
+        self.poll_at(Instant::now())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::schema::tests::MemoryStats;
+    use super::*;
+
+    const GAMES: IntStat = IntStat::new("games").increment_only();
+    const DISTANCE: FloatStat = FloatStat::new("distance").min(0.0);
+    const WIN: Achievement = Achievement::new("ACH_WIN");
+
+    fn user() -> SteamId {
+        SteamId::from_raw(76561197960287930)
+    }
+
+    fn backend() -> MemoryStats {
+        let stats = MemoryStats::default();
+        stats.ints.borrow_mut().insert("games".into(), 5);
+        stats.floats.borrow_mut().insert("distance".into(), 0.0);
+        stats
+            .achievements
+            .borrow_mut()
+            .push(("ACH_WIN".into(), false));
+        stats
+    }
+
+    fn received() -> UserStatsReceived {
+        UserStatsReceived {
+            steam_id: user(),
+            game_id: GameId(480),
+            result: Ok(()),
+        }
+    }
+
+    fn stored(result: Result<(), SteamError>) -> UserStatsStored {
+        UserStatsStored {
+            game_id: GameId(480),
+            result,
+        }
+    }
+
+    #[test]
+    fn replay_and_conflicts() {
+        let start = Instant::now();
+        let mut session = StatsSession::new(backend(), user());
+        session.set_int(GAMES, 3).unwrap();
+        session.set_float(DISTANCE, 10.0).unwrap();
+        session.set_float(DISTANCE, 12.5).unwrap();
+        session.unlock(WIN);
+        assert_eq!(
+            session.set_float(DISTANCE, -1.0),
+            Err(StatError::OutOfRange("distance"))
+        );
+        assert_eq!(session.unstored_writes(), 3);
+        assert_eq!(session.backend().floats.borrow()["distance"], 0.0);
+        assert!(session.poll_at(start).is_empty());
+        assert_eq!(session.backend().stores.get(), 0);
+
+        session.on_user_stats_received(&received());
+        let events = session.poll_at(start);
+        // The server has played more games than the local value
+        assert!(matches!(
+            &events[0],
+            StatsSessionEvent::Conflict(StatConflict { name, server: StatValue::Int(5), .. })
+                if name == "games"
+        ));
+        assert!(matches!(
+            events[1],
+            StatsSessionEvent::Replayed { writes: 2 }
+        ));
+        assert_eq!(session.backend().floats.borrow()["distance"], 12.5);
+        assert_eq!(session.backend().get_achievement("ACH_WIN"), Some(true));
+        // Replayed writes are stored right away
+        assert_eq!(session.backend().stores.get(), 1);
+
+        session.on_user_stats_stored(&stored(Ok(())));
+        assert!(matches!(
+            session.poll_at(start)[..],
+            [StatsSessionEvent::Stored]
+        ));
+        assert_eq!(session.unstored_writes(), 0);
+    }
+
+    #[test]
+    fn store_rate_limit_and_retry() {
+        let start = Instant::now();
+        let mut session = StatsSession::new(backend(), user())
+            .with_store_interval(Duration::from_secs(60))
+            .with_retry_delay(Duration::from_secs(10));
+        session.on_user_stats_received(&received());
+
+        session.set_int(GAMES, 6).unwrap();
+        session.poll_at(start);
+        assert_eq!(session.backend().stores.get(), 1);
+        session.on_user_stats_stored(&stored(Err(SteamError::Timeout)));
+        assert!(matches!(
+            session.poll_at(start)[..],
+            [StatsSessionEvent::StoreFailed(SteamError::Timeout)]
+        ));
+        assert_eq!(session.unstored_writes(), 1);
+
+        session.poll_at(start + Duration::from_secs(9));
+        assert_eq!(session.backend().stores.get(), 1);
+        session.poll_at(start + Duration::from_secs(10));
+        assert_eq!(session.backend().stores.get(), 2);
+        session.on_user_stats_stored(&stored(Ok(())));
+        assert_eq!(session.unstored_writes(), 0);
+
+        // Regular writes wait for the store interval
+        session.set_int(GAMES, 7).unwrap();
+        session.poll_at(start + Duration::from_secs(30));
+        assert_eq!(session.backend().stores.get(), 2);
+        session.poll_at(start + Duration::from_secs(70));
+        assert_eq!(session.backend().stores.get(), 3);
+        session.on_user_stats_stored(&stored(Ok(())));
+
+        // Important events are stored right away
+        session.set_int(GAMES, 8).unwrap();
+        session.store_now();
+        session.poll_at(start + Duration::from_secs(71));
+        assert_eq!(session.backend().stores.get(), 4);
+    }
+
+    #[test]
+    fn journal() {
+        let path = std::env::temp_dir().join(format!(
+            "steamworks-stats-journal-{}.txt",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+
+        let mut session = StatsSession::new(backend(), user())
+            .with_journal(&path)
+            .unwrap();
+        session.set_int(GAMES, 9).unwrap();
+        session.set_float(DISTANCE, 0.1).unwrap();
+        session.unlock(WIN);
+        drop(session);
+
+        // The writes survive a restart and are replayed
+        let mut session = StatsSession::new(backend(), user())
+            .with_journal(&path)
+            .unwrap();
+        assert_eq!(session.unstored_writes(), 3);
+        session.on_user_stats_received(&received());
+        session.poll_at(Instant::now());
+        assert_eq!(session.backend().ints.borrow()["games"], 9);
+        assert_eq!(session.backend().floats.borrow()["distance"], 0.1);
+
+        session.on_user_stats_stored(&stored(Ok(())));
+        assert!(!path.exists());
+    }
+}
@@ -29,6 +29,22 @@ impl_callback!(cb: UserStatsReceived_t => UserStatsReceived {
     }
 });
 
+/// Sent when Steam has dropped the stats of another user from memory.
+///
+/// The stats must be requested again with
+/// [`request_user_stats()`](UserStats::request_user_stats) before they can
+/// be read.
+#[derive(Clone, Debug)]
+pub struct UserStatsUnloaded {
+    pub steam_id: SteamId,
+}
+
+impl_callback!(cb: UserStatsUnloaded_t => UserStatsUnloaded {
+    Self {
+        steam_id: SteamId(cb.m_steamIDUser.m_steamid.m_unAll64Bits),
+    }
+});
+
 /// Callback triggered by [`store()`](stats/struct.StatsHelper.html#method.store).
 ///
 /// # Example
@@ -1,5 +1,16 @@
 use super::*;
 
+/// The progress range of an achievement tied to a stat, returned by
+/// [`AchievementHelper::get_progress_limits`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum AchievementProgressLimits {
+    /// The achievement is tied to an integer stat
+    Int { min: i32, max: i32 },
+    /// The achievement is tied to a float stat
+    Float { min: f32, max: f32 },
+}
+
 /// Achievement API.
 ///
 /// Methods require
@@ -87,6 +98,61 @@ impl AchievementHelper<'_> {
         }
     }
 
+    /// Shows the user a pop-up notification with the current progress of
+    /// the achievement, e.g. "47/100".
+    ///
+    /// This does not change the progress or unlock the achievement, which
+    /// is done by setting the stat the achievement is tied to. Calling this
+    /// with `current` equal to `max` does nothing.
+    ///
+    /// Triggers a [`UserAchievementStored`](../struct.UserAchievementStored.html)
+    /// callback. Fails if the achievement is unknown or already unlocked.
+    pub fn indicate_progress(&self, current: u32, max: u32) -> Result<(), ()> {
+        let success = unsafe {
+            sys::SteamAPI_ISteamUserStats_IndicateAchievementProgress(
+                self.parent.user_stats,
+                self.name.as_ptr(),
+                current,
+                max,
+            )
+        };
+        if success {
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+
+    /// Gets the progress range of an achievement that is tied to a stat
+    /// on the Steamworks website.
+    ///
+    /// Fails if this achievement's 'API Name' is unknown, it has no
+    /// progress stat, or unsuccessful
+    /// [`UserStatsReceived`](../struct.UserStatsReceived.html).
+    pub fn get_progress_limits(&self) -> Result<AchievementProgressLimits, ()> {
+        unsafe {
+            let (mut min, mut max) = (0, 0);
+            if sys::SteamAPI_ISteamUserStats_GetAchievementProgressLimitsInt32(
+                self.parent.user_stats,
+                self.name.as_ptr(),
+                &mut min,
+                &mut max,
+            ) {
+                return Ok(AchievementProgressLimits::Int { min, max });
+            }
+            let (mut min, mut max) = (0.0, 0.0);
+            if sys::SteamAPI_ISteamUserStats_GetAchievementProgressLimitsFloat(
+                self.parent.user_stats,
+                self.name.as_ptr(),
+                &mut min,
+                &mut max,
+            ) {
+                return Ok(AchievementProgressLimits::Float { min, max });
+            }
+            Err(())
+        }
+    }
+
     /// Returns the percentage of users who have unlocked the specified achievement.
     ///
     /// You must have called `request_global_achievement_percentages()` and it needs to return
@@ -219,37 +285,22 @@ impl AchievementHelper<'_> {
     /// ** Note: This may return None if Steam has not retrieved the icon yet. In that case an `UserAchievementIconFetched` callback will be processed
 
     pub fn get_achievement_icon(&self) -> Option<Vec<u8>> {
-        Some(self.internal_get_achievement_icon(true)?.0)
+        self.get_achievement_icon_image()
+            .filter(|img| img.width() == 64 && img.height() == 64)
+            .map(SteamImage::into_rgba)
     }
 
-    fn internal_get_achievement_icon(&self, avoid_big_icons: bool) -> Option<(Vec<u8>, u32, u32)> {
+    /// Gets the icon for an achievement as a [`SteamImage`].
+    ///
+    /// Returns `None` if Steam has not retrieved the icon yet. In that case an
+    /// [`UserAchievementIconFetched`] callback will be processed once it is available.
+    pub fn get_achievement_icon_image(&self) -> Option<SteamImage> {
         unsafe {
-            let utils: *mut sys::ISteamUtils = sys::SteamAPI_SteamUtils_v010();
             let img = sys::SteamAPI_ISteamUserStats_GetAchievementIcon(
                 self.parent.user_stats,
                 self.name.as_ptr(),
             );
-            if img == 0 {
-                return None;
-            }
-            let mut width = 0;
-            let mut height = 0;
-            if !sys::SteamAPI_ISteamUtils_GetImageSize(utils, img, &mut width, &mut height) {
-                return None;
-            }
-            if avoid_big_icons && (width != 64 || height != 64) {
-                return None;
-            }
-            let mut dest = vec![0; (width * height * 4).try_into().unwrap()];
-            if !sys::SteamAPI_ISteamUtils_GetImageRGBA(
-                utils,
-                img,
-                dest.as_mut_ptr(),
-                (width * height * 4).try_into().unwrap(),
-            ) {
-                return None;
-            }
-            Some((dest, width, height))
+            SteamImage::from_handle(sys::SteamAPI_SteamUtils_v010(), img)
         }
     }
 
@@ -262,8 +313,6 @@ impl AchievementHelper<'_> {
     /// ** Note: This may return None if Steam has not retrieved the icon yet. In that case an `UserAchievementIconFetched` callback will be processed
     #[cfg(feature = "image")]
     pub fn get_achievement_icon_v2(&self) -> Option<image::ImageBuffer<image::Rgba<u8>, Vec<u8>>> {
-        let (vec, width, height) = self.internal_get_achievement_icon(false)?;
-        let img = image::ImageBuffer::<image::Rgba<u8>, Vec<u8>>::from_vec(width, height, vec)?;
-        return Some(img);
+        self.get_achievement_icon_image()?.to_image()
     }
 }
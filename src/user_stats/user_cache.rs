@@ -0,0 +1,477 @@
+use super::*;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Callback passed to [`UserStatsSource::request_user_stats`]
+pub type UserStatsRequestCallback = Box<dyn FnOnce(Result<SteamId, SteamError>) + Send>;
+
+/// Reads the stats and achievements of other users.
+///
+/// Implemented for [`UserStats`], and can be implemented by tests to use
+/// a [`UserStatsCache`] without Steam.
+pub trait UserStatsSource {
+    fn request_user_stats(&self, user: SteamId, cb: UserStatsRequestCallback);
+    fn get_user_stat_i32(&self, user: SteamId, name: &str) -> Option<i32>;
+    fn get_user_stat_f32(&self, user: SteamId, name: &str) -> Option<f32>;
+    /// Returns whether the achievement is unlocked and its unlock time
+    fn get_user_achievement(&self, user: SteamId, name: &str) -> Option<(bool, u32)>;
+}
+
+impl UserStatsSource for UserStats {
+    fn request_user_stats(&self, user: SteamId, cb: UserStatsRequestCallback) {
+        UserStats::request_user_stats(self, user, cb)
+    }
+
+    fn get_user_stat_i32(&self, user: SteamId, name: &str) -> Option<i32> {
+        UserStats::get_user_stat_i32(self, user, name).ok()
+    }
+
+    fn get_user_stat_f32(&self, user: SteamId, name: &str) -> Option<f32> {
+        UserStats::get_user_stat_f32(self, user, name).ok()
+    }
+
+    fn get_user_achievement(&self, user: SteamId, name: &str) -> Option<(bool, u32)> {
+        UserStats::get_user_achievement(self, user, name).ok()
+    }
+}
+
+/// An achievement of another user
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct UserAchievement {
+    pub name: &'static str,
+    pub achieved: bool,
+    /// Seconds since the Unix epoch, or zero if the achievement is locked
+    /// or was unlocked before Steam tracked unlock times
+    pub unlock_time: u32,
+}
+
+/// The stats and achievements of a user, copied when they were received
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct UserStatsSnapshot {
+    pub user: SteamId,
+    /// The schema stats the user has values for
+    pub stats: Vec<(&'static str, StatValue)>,
+    /// The schema achievements Steam knows about
+    pub achievements: Vec<UserAchievement>,
+}
+
+impl UserStatsSnapshot {
+    /// Returns the value of a stat
+    pub fn stat(&self, name: &str) -> Option<StatValue> {
+        self.stats
+            .iter()
+            .find(|(stat, _)| *stat == name)
+            .map(|&(_, value)| value)
+    }
+
+    /// Returns an achievement
+    pub fn achievement(&self, name: &str) -> Option<&UserAchievement> {
+        self.achievements.iter().find(|a| a.name == name)
+    }
+
+    /// Returns the number of unlocked achievements
+    pub fn unlocked_count(&self) -> usize {
+        self.achievements.iter().filter(|a| a.achieved).count()
+    }
+}
+
+/// The state of a user in a [`UserStatsCache`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UserStatsState {
+    /// The stats have been requested
+    Loading,
+    /// The stats are available with [`UserStatsCache::get`]
+    Loaded,
+    /// The request failed, e.g. with [`SteamError::Generic`] if the user
+    /// has never played the game
+    Failed(SteamError),
+}
+
+enum Entry {
+    Loading { unloaded: bool },
+    Loaded(UserStatsSnapshot),
+    Failed(SteamError),
+}
+
+struct Slot {
+    entry: Entry,
+    generation: u64,
+    last_used: u64,
+}
+
+type Responses = Arc<Mutex<Vec<(SteamId, u64, Result<SteamId, SteamError>)>>>;
+
+/// Keeps the stats and achievements of a bounded number of other users,
+/// e.g. for comparing profiles.
+///
+/// The stats declared by a [`StatsSchema`] are copied when Steam returns
+/// them, so they stay readable after Steam drops its own copy. When more
+/// than `capacity` users are cached the least recently used one is
+/// evicted.
+///
+/// Steam keeps the stats of a limited number of users on its side and
+/// unloads older ones by itself, sending [`UserStatsUnloaded`]. If a user
+/// is unloaded before their stats were copied, the cache requests them
+/// again. Evicting a user only drops the cache's copy, as the current SDK
+/// has no way to unload a user's stats from Steam.
+///
+/// The cache is a [`CallbackStateMachine`] that copies the stats in
+/// [`poll`](CallbackStateMachine::poll) and only needs
+/// [`UserStatsUnloaded`] forwarded to notice early unloads. The current
+/// user's stats should be read with [`UserStats`] directly instead.
+///
+/// # Example
+///
+/// ```no_run
+/// # use steamworks::*;
+/// stats_schema! {
+///     pub struct GameStats {
+///         stats {
+///             WINS: IntStat = IntStat::new("wins"),
+///         }
+///         achievements {
+///             FIRST_WIN = "ACH_FIRST_WIN",
+///         }
+///     }
+/// }
+///
+/// let client = Client::init().unwrap();
+/// let mut cache = UserStatsCache::new(client.user_stats(), GameStats::SCHEMA, 16);
+/// let friend = SteamId::from_raw(76561197960287930);
+/// cache.request(friend);
+/// loop {
+///     client.run_callbacks();
+///     if cache.poll().contains(&friend) {
+///         if let Some(stats) = cache.get(friend) {
+///             println!("{:?}", stats.stat("wins"));
+///         }
+///         break;
+///     }
+/// }
+/// ```
+pub struct UserStatsCache<S: UserStatsSource> {
+    source: S,
+    schema: StatsSchema,
+    capacity: usize,
+    slots: HashMap<SteamId, Slot>,
+    responses: Responses,
+    clock: u64,
+}
+
+impl<S: UserStatsSource> UserStatsCache<S> {
+    /// Creates a cache for the stats in `schema` of up to `capacity`
+    /// users
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn new(source: S, schema: StatsSchema, capacity: usize) -> UserStatsCache<S> {
+        assert!(capacity > 0, "capacity must not be zero");
+        UserStatsCache {
+            source,
+            schema,
+            capacity,
+            slots: HashMap::new(),
+            responses: Arc::new(Mutex::new(Vec::new())),
+            clock: 0,
+        }
+    }
+
+    /// Returns the source the stats are read from
+    pub fn source(&self) -> &S {
+        &self.source
+    }
+
+    /// Returns the maximum number of cached users
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of cached or loading users
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Returns whether no users are cached
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// Requests the stats of a user, unless they are already cached or
+    /// loading. Failed requests are retried.
+    pub fn request(&mut self, user: SteamId) {
+        self.clock += 1;
+        if let Some(slot) = self.slots.get_mut(&user) {
+            slot.last_used = self.clock;
+            if !matches!(slot.entry, Entry::Failed(_)) {
+                return;
+            }
+        }
+        self.send_request(user);
+    }
+
+    /// Requests the stats of a user again, keeping the current copy until
+    /// the new one arrives
+    pub fn refresh(&mut self, user: SteamId) {
+        self.clock += 1;
+        self.send_request(user);
+    }
+
+    fn send_request(&mut self, user: SteamId) {
+        if !self.slots.contains_key(&user) && self.slots.len() >= self.capacity {
+            let oldest = self
+                .slots
+                .iter()
+                .min_by_key(|(_, slot)| slot.last_used)
+                .map(|(&user, _)| user);
+            if let Some(oldest) = oldest {
+                self.slots.remove(&oldest);
+            }
+        }
+        let slot = self.slots.entry(user).or_insert(Slot {
+            entry: Entry::Loading { unloaded: false },
+            generation: 0,
+            last_used: 0,
+        });
+        slot.generation += 1;
+        slot.last_used = self.clock;
+        if !matches!(slot.entry, Entry::Loaded(_)) {
+            slot.entry = Entry::Loading { unloaded: false };
+        }
+
+        let generation = slot.generation;
+        let responses = Arc::clone(&self.responses);
+        self.source.request_user_stats(
+            user,
+            Box::new(move |result| {
+                responses.lock().unwrap().push((user, generation, result));
+            }),
+        );
+    }
+
+    /// Returns the state of a user, or `None` if they are not cached
+    pub fn state(&self, user: SteamId) -> Option<UserStatsState> {
+        self.slots.get(&user).map(|slot| match &slot.entry {
+            Entry::Loading { .. } => UserStatsState::Loading,
+            Entry::Loaded(_) => UserStatsState::Loaded,
+            Entry::Failed(err) => UserStatsState::Failed(*err),
+        })
+    }
+
+    /// Returns the cached stats of a user and marks them as recently used
+    pub fn get(&mut self, user: SteamId) -> Option<&UserStatsSnapshot> {
+        self.clock += 1;
+        let slot = self.slots.get_mut(&user)?;
+        slot.last_used = self.clock;
+        match &slot.entry {
+            Entry::Loaded(snapshot) => Some(snapshot),
+            _ => None,
+        }
+    }
+
+    /// Drops a user from the cache. Returns whether they were cached.
+    pub fn evict(&mut self, user: SteamId) -> bool {
+        self.slots.remove(&user).is_some()
+    }
+
+    /// Drops all users from the cache
+    pub fn clear(&mut self) {
+        self.slots.clear();
+    }
+
+    /// Applies a [`UserStatsUnloaded`] callback
+    pub fn on_user_stats_unloaded(&mut self, unloaded: &UserStatsUnloaded) {
+        if let Some(Slot {
+            entry: Entry::Loading { unloaded: flag },
+            ..
+        }) = self.slots.get_mut(&unloaded.steam_id)
+        {
+            *flag = true;
+        }
+    }
+}
+
+impl<S: UserStatsSource> CallbackStateMachine for UserStatsCache<S> {
+    type Event = SteamId;
+
+    fn handle_callback(&mut self, callback: &CallbackResult) {
+        if let CallbackResult::UserStatsUnloaded(unloaded) = callback {
+            self.on_user_stats_unloaded(unloaded);
+        }
+    }
+
+    /// Copies the stats of users whose requests completed and returns the
+    /// users that were loaded or failed since the last call
+    fn poll(&mut self) -> Vec<SteamId> {
+        let responses = std::mem::take(&mut *self.responses.lock().unwrap());
+        let mut changed = Vec::new();
+        let mut retry = Vec::new();
+        for (user, generation, result) in responses {
+            let slot = match self.slots.get_mut(&user) {
+                Some(slot) if slot.generation == generation => slot,
+                // Evicted or requested again since
+                _ => continue,
+            };
+            slot.entry = match result {
+                Ok(_) => {
+                    if let Entry::Loading { unloaded: true } = slot.entry {
+                        retry.push(user);
+                        continue;
+                    }
+                    Entry::Loaded(snapshot(&self.source, &self.schema, user))
+                }
+                // Keep the previous copy when a refresh fails
+                Err(_) if matches!(slot.entry, Entry::Loaded(_)) => continue,
+                Err(err) => Entry::Failed(err),
+            };
+            changed.push(user);
+        }
+        for user in retry {
+            self.send_request(user);
+        }
+        changed
+    }
+}
+
+fn snapshot<S: UserStatsSource>(
+    source: &S,
+    schema: &StatsSchema,
+    user: SteamId,
+) -> UserStatsSnapshot {
+    let stats = schema
+        .stats
+        .iter()
+        .filter_map(|stat| {
+            let value = match stat.kind {
+                StatKind::Int => StatValue::Int(source.get_user_stat_i32(user, stat.name)?),
+                StatKind::Float | StatKind::AvgRate => {
+                    StatValue::Float(source.get_user_stat_f32(user, stat.name)?)
+                }
+            };
+            Some((stat.name, value))
+        })
+        .collect();
+    let achievements = schema
+        .achievements
+        .iter()
+        .filter_map(|&name| {
+            let (achieved, unlock_time) = source.get_user_achievement(user, name)?;
+            Some(UserAchievement {
+                name,
+                achieved,
+                unlock_time,
+            })
+        })
+        .collect();
+    UserStatsSnapshot {
+        user,
+        stats,
+        achievements,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    #[derive(Default)]
+    struct FakeSource {
+        requests: RefCell<Vec<(SteamId, UserStatsRequestCallback)>>,
+    }
+
+    impl FakeSource {
+        fn respond(&self, result: Result<(), SteamError>) {
+            let (user, cb) = self.requests.borrow_mut().remove(0);
+            cb(result.map(|_| user));
+        }
+    }
+
+    impl UserStatsSource for FakeSource {
+        fn request_user_stats(&self, user: SteamId, cb: UserStatsRequestCallback) {
+            self.requests.borrow_mut().push((user, cb));
+        }
+
+        fn get_user_stat_i32(&self, user: SteamId, name: &str) -> Option<i32> {
+            (name == "wins").then_some(user.raw() as i32)
+        }
+
+        fn get_user_stat_f32(&self, _: SteamId, _: &str) -> Option<f32> {
+            None
+        }
+
+        fn get_user_achievement(&self, user: SteamId, name: &str) -> Option<(bool, u32)> {
+            (name == "ACH_WIN").then_some((user.raw() > 1, 100))
+        }
+    }
+
+    const SCHEMA: StatsSchema = StatsSchema {
+        stats: &[IntStat::new("wins").def(), FloatStat::new("missing").def()],
+        achievements: &["ACH_WIN"],
+    };
+
+    #[test]
+    fn loads_and_evicts() {
+        let (a, b, c) = (SteamId(1), SteamId(2), SteamId(3));
+        let mut cache = UserStatsCache::new(FakeSource::default(), SCHEMA, 2);
+        cache.request(a);
+        cache.request(a);
+        cache.request(b);
+        assert_eq!(cache.source().requests.borrow().len(), 2);
+        assert_eq!(cache.state(a), Some(UserStatsState::Loading));
+
+        cache.source().respond(Ok(()));
+        cache.source().respond(Err(SteamError::Generic));
+        assert_eq!(cache.poll(), [a, b]);
+        assert_eq!(
+            cache.state(b),
+            Some(UserStatsState::Failed(SteamError::Generic))
+        );
+        let stats = cache.get(a).unwrap();
+        assert_eq!(stats.stat("wins"), Some(StatValue::Int(1)));
+        assert_eq!(stats.stat("missing"), None);
+        assert_eq!(stats.unlocked_count(), 0);
+
+        // `a` was used more recently, so `b` is evicted
+        cache.request(c);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.state(b), None);
+        cache.source().respond(Ok(()));
+        assert_eq!(cache.poll(), [c]);
+        assert!(
+            cache
+                .get(c)
+                .unwrap()
+                .achievement("ACH_WIN")
+                .unwrap()
+                .achieved
+        );
+
+        // Responses for evicted users are ignored
+        cache.request(b);
+        assert!(cache.evict(b));
+        cache.source().respond(Ok(()));
+        assert!(cache.poll().is_empty());
+    }
+
+    #[test]
+    fn rerequests_unloaded() {
+        let user = SteamId(1);
+        let mut cache = UserStatsCache::new(FakeSource::default(), SCHEMA, 4);
+        cache.request(user);
+        cache.source().respond(Ok(()));
+        cache.handle_callback(&CallbackResult::UserStatsUnloaded(UserStatsUnloaded {
+            steam_id: user,
+        }));
+        assert!(cache.poll().is_empty());
+        assert_eq!(cache.state(user), Some(UserStatsState::Loading));
+
+        cache.source().respond(Ok(()));
+        assert_eq!(cache.poll(), [user]);
+        // Already copied stats survive unloading
+        cache.on_user_stats_unloaded(&UserStatsUnloaded { steam_id: user });
+        assert!(cache.get(user).is_some());
+    }
+}
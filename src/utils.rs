@@ -1,6 +1,12 @@
+mod deck;
+mod environment;
+
+pub use self::deck::*;
+pub use self::environment::*;
 use super::*;
 
 use std::ffi::CStr;
+use std::ops::Range;
 use std::os::raw::c_char;
 use std::panic;
 use std::process::abort;
@@ -99,6 +105,142 @@ impl From<FloatingGamepadTextInputMode> for sys::EFloatingGamepadTextInputMode {
     }
 }
 
+/// The context a piece of text is filtered in
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TextFilteringContext {
+    Unknown,
+    /// Game content, only legally required filtering is performed
+    GameContent,
+    /// Chat from another player
+    Chat,
+    /// A character or item name
+    Name,
+}
+
+impl From<TextFilteringContext> for sys::ETextFilteringContext {
+    fn from(context: TextFilteringContext) -> Self {
+        match context {
+            TextFilteringContext::Unknown => {
+                sys::ETextFilteringContext::k_ETextFilteringContextUnknown
+            }
+            TextFilteringContext::GameContent => {
+                sys::ETextFilteringContext::k_ETextFilteringContextGameContent
+            }
+            TextFilteringContext::Chat => sys::ETextFilteringContext::k_ETextFilteringContextChat,
+            TextFilteringContext::Name => sys::ETextFilteringContext::k_ETextFilteringContextName,
+        }
+    }
+}
+
+/// Text that has been passed through the Steam text filter
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FilteredText {
+    /// The filtered text, with filtered characters replaced
+    pub text: String,
+    /// The number of characters that were filtered
+    pub filtered_chars: u32,
+    /// The byte ranges of the original text that were replaced
+    pub replaced: Vec<Range<usize>>,
+}
+
+impl FilteredText {
+    /// Returns whether any part of the text was filtered
+    pub fn is_filtered(&self) -> bool {
+        self.filtered_chars > 0
+    }
+}
+
+/// Returns the byte ranges of `original` whose characters differ in
+/// `filtered`. The filter replaces characters one for one, so both strings
+/// are compared character by character.
+fn replaced_ranges(original: &str, filtered: &str) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    let mut filtered_chars = filtered.chars();
+    for (idx, c) in original.char_indices() {
+        if filtered_chars.next() == Some(c) {
+            continue;
+        }
+        let end = idx + c.len_utf8();
+        match ranges.last_mut() {
+            Some(last) if last.end == idx => last.end = end,
+            _ => ranges.push(idx..end),
+        }
+    }
+    ranges
+}
+
+/// Filters user generated text before it is displayed, honoring the
+/// profanity settings of the current user.
+///
+/// Created with [`Utils::text_filter`].
+pub struct TextFilter {
+    utils: *mut sys::ISteamUtils,
+    _inner: Arc<Inner>,
+}
+
+impl TextFilter {
+    /// Filters text in the given context
+    pub fn filter(
+        &self,
+        context: TextFilteringContext,
+        source: SteamId,
+        text: &str,
+    ) -> FilteredText {
+        unsafe { filter_text(self.utils, context, source, text) }
+    }
+
+    /// Filters a lobby chat message as returned by
+    /// [`Matchmaking::get_lobby_chat_entry`]
+    pub fn lobby_chat(&self, sender: SteamId, message: &[u8]) -> String {
+        let message = CStr::from_bytes_until_nul(message)
+            .map(|s| s.to_string_lossy())
+            .unwrap_or_else(|_| String::from_utf8_lossy(message));
+        self.filter(TextFilteringContext::Chat, sender, &message)
+            .text
+    }
+
+    /// Filters the name of a lobby created by `owner`
+    pub fn lobby_name(&self, owner: SteamId, name: &str) -> String {
+        self.filter(TextFilteringContext::Name, owner, name).text
+    }
+
+    /// Filters a rich presence value set by `friend`
+    pub fn rich_presence(&self, friend: SteamId, value: &str) -> String {
+        self.filter(TextFilteringContext::GameContent, friend, value)
+            .text
+    }
+}
+
+/// # Panics
+///
+/// Panics if `text` contains a null byte.
+unsafe fn filter_text(
+    utils: *mut sys::ISteamUtils,
+    context: TextFilteringContext,
+    source: SteamId,
+    text: &str,
+) -> FilteredText {
+    let input = CString::new(text).unwrap();
+    // Replacement characters may be wider than the characters they replace
+    let mut buffer = vec![0u8; text.len() * 4 + 1];
+    let filtered_chars = sys::SteamAPI_ISteamUtils_FilterText(
+        utils,
+        context.into(),
+        source.0,
+        input.as_ptr(),
+        buffer.as_mut_ptr().cast(),
+        buffer.len() as u32,
+    );
+    let filtered = CStr::from_bytes_until_nul(&buffer)
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    FilteredText {
+        replaced: replaced_ranges(text, &filtered),
+        text: filtered,
+        filtered_chars: filtered_chars.max(0) as u32,
+    }
+}
+
 /// Global rust warning callback
 static WARNING_CALLBACK: RwLock<Option<Box<dyn Fn(i32, &CStr) + Send + Sync>>> = RwLock::new(None);
 
@@ -165,6 +307,31 @@ impl Utils {
         unsafe { sys::SteamAPI_ISteamUtils_GetServerRealTime(self.utils) }
     }
 
+    /// Loads the image behind a steam image handle, as handed out by
+    /// callbacks like [`AvatarImageLoaded`] or [`UserAchievementIconFetched`].
+    ///
+    /// Returns `None` if the handle is not valid.
+    pub fn get_image(&self, handle: i32) -> Option<SteamImage> {
+        unsafe { SteamImage::from_handle(self.utils, handle) }
+    }
+
+    /// Returns whether the overlay needs the game to present frames.
+    ///
+    /// Games that only render on changes should keep presenting while
+    /// this returns true so the overlay can be drawn.
+    pub fn overlay_needs_present(&self) -> bool {
+        unsafe { sys::SteamAPI_ISteamUtils_BOverlayNeedsPresent(self.utils) }
+    }
+
+    /// Sets the inset of the overlay notifications from the corner set with
+    /// [`set_overlay_notification_position`](Self::set_overlay_notification_position),
+    /// in pixels.
+    pub fn set_overlay_notification_inset(&self, horizontal: i32, vertical: i32) {
+        unsafe {
+            sys::SteamAPI_ISteamUtils_SetOverlayNotificationInset(self.utils, horizontal, vertical);
+        }
+    }
+
     /// Sets the position on the screen where popups from the steam overlay
     /// should appear and display themselves in.
     pub fn set_overlay_notification_position(&self, position: NotificationPosition) {
@@ -183,6 +350,43 @@ impl Utils {
         }
     }
 
+    /// Initializes text filtering, loading the dictionaries for the
+    /// language the game is running in.
+    ///
+    /// Returns false if filtering is unavailable for the game's language,
+    /// in which case [`filter_text`](Self::filter_text) is a no-op.
+    pub fn init_filter_text(&self) -> bool {
+        unsafe { sys::SteamAPI_ISteamUtils_InitFilterText(self.utils, 0) }
+    }
+
+    /// Filters the text according to the user's profanity settings.
+    ///
+    /// `source` is the user that created the text, so text the current
+    /// user wrote themselves is not filtered. Requires
+    /// [`init_filter_text`](Self::init_filter_text) to have been called.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `text` contains a null byte.
+    pub fn filter_text(
+        &self,
+        context: TextFilteringContext,
+        source: SteamId,
+        text: &str,
+    ) -> FilteredText {
+        unsafe { filter_text(self.utils, context, source, text) }
+    }
+
+    /// Initializes text filtering and returns a [`TextFilter`] for
+    /// filtering chat, lobby names and rich presence before display.
+    pub fn text_filter(&self) -> TextFilter {
+        self.init_filter_text();
+        TextFilter {
+            utils: self.utils,
+            _inner: self._inner.clone(),
+        }
+    }
+
     /// Sets the Steam warning callback, which is called to emit warning messages.
     ///
     /// The passed-in function takes two arguments: a severity level (0 = info, 1 = warning) and
@@ -329,3 +533,23 @@ impl SteamParamStringArray {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replaced_ranges_of_filtered_text() {
+        assert!(replaced_ranges("hello", "hello").is_empty());
+        assert_eq!(replaced_ranges("a b a", "* b *"), [0..1, 4..5]);
+
+        let ranges = replaced_ranges("oh heck no", "oh **** no");
+        assert_eq!(ranges.len(), 1);
+        assert_eq!(ranges[0], 3..7);
+
+        // Multi byte characters in the input and the replacement
+        let ranges = replaced_ranges("ö heck", "ö \u{2665}\u{2665}\u{2665}\u{2665}");
+        assert_eq!(ranges.len(), 1);
+        assert_eq!(ranges[0], 3..7);
+    }
+}
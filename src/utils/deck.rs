@@ -0,0 +1,346 @@
+use super::*;
+
+/// The environment the game is being played in, used to adapt the user
+/// interface to Steam Deck, Big Picture and VR.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SteamPlatform {
+    /// Steam is running on a Steam Deck
+    pub steam_deck: bool,
+    /// Steam and the overlay are running in Big Picture mode
+    pub big_picture: bool,
+    /// Steam is running in VR mode
+    pub vr: bool,
+    /// The battery level in percent, `None` if running on AC power
+    pub battery: Option<u8>,
+}
+
+impl SteamPlatform {
+    /// Returns whether the game should default to a gamepad friendly
+    /// interface and on-screen keyboards
+    pub fn prefers_gamepad_ui(&self) -> bool {
+        self.steam_deck || self.big_picture || self.vr
+    }
+}
+
+/// Where the on-screen keyboard of a [`TextEntry`] is shown
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextEntryKeyboard {
+    /// The full screen Big Picture text input dialog. The entered text is
+    /// returned when the dialog is submitted.
+    FullScreen,
+    /// A floating keyboard that types directly into a text field of the
+    /// game, positioned so it does not cover the given area of the game
+    /// window (x, y, width, height in pixels).
+    Floating(i32, i32, i32, i32),
+}
+
+/// The options of a [`TextEntry`]
+#[derive(Clone, Debug)]
+pub struct TextEntryOptions<'a> {
+    pub keyboard: TextEntryKeyboard,
+    /// Shown above the text field of the full screen dialog
+    pub description: &'a str,
+    /// Text the full screen dialog is pre-filled with
+    pub existing_text: Option<&'a str>,
+    /// The maximum number of characters of the full screen dialog
+    pub max_characters: u32,
+    /// Hides the entered characters
+    pub password: bool,
+    /// Allows entering multiple lines
+    pub multiline: bool,
+}
+
+impl Default for TextEntryOptions<'_> {
+    fn default() -> Self {
+        TextEntryOptions {
+            keyboard: TextEntryKeyboard::FullScreen,
+            description: "",
+            existing_text: None,
+            max_characters: 256,
+            password: false,
+            multiline: false,
+        }
+    }
+}
+
+/// The outcome of a [`TextEntry`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TextEntryResult {
+    /// The user submitted the full screen dialog
+    Submitted(String),
+    /// The user cancelled the full screen dialog
+    Cancelled,
+    /// The floating keyboard was closed. The entered text was sent to the
+    /// game as regular key presses.
+    Dismissed,
+}
+
+/// A pending on-screen keyboard text entry, created with
+/// [`Utils::open_text_entry`].
+///
+/// The entry is a [`CallbackStateMachine`] that completes once
+/// [`GamepadTextInputDismissed`] or [`FloatingGamepadTextInputDismissed`]
+/// for its keyboard is forwarded to it. The result is returned once from
+/// [`poll`](CallbackStateMachine::poll) and stays readable with
+/// [`try_result`](Self::try_result). Dropping the entry does not close the
+/// keyboard.
+pub struct TextEntry {
+    utils: *mut sys::ISteamUtils,
+    keyboard: TextEntryKeyboard,
+    result: Option<TextEntryResult>,
+    reported: bool,
+}
+
+impl TextEntry {
+    /// Returns the result of the text entry once the keyboard has been
+    /// closed
+    pub fn try_result(&self) -> Option<TextEntryResult> {
+        self.result.clone()
+    }
+
+    /// Closes the keyboard
+    pub fn dismiss(&self) -> bool {
+        unsafe {
+            match self.keyboard {
+                TextEntryKeyboard::FullScreen => {
+                    sys::SteamAPI_ISteamUtils_DismissGamepadTextInput(self.utils)
+                }
+                TextEntryKeyboard::Floating(..) => {
+                    sys::SteamAPI_ISteamUtils_DismissFloatingGamepadTextInput(self.utils)
+                }
+            }
+        }
+    }
+
+    /// Applies a [`GamepadTextInputDismissed`], reading the submitted text
+    /// of a full screen entry
+    pub fn on_gamepad_text_input_dismissed(&mut self, dismissed: &GamepadTextInputDismissed) {
+        if self.result.is_some() || self.keyboard != TextEntryKeyboard::FullScreen {
+            return;
+        }
+        let text = dismissed
+            .submitted_text_len
+            .and_then(|len| unsafe { entered_gamepad_text(self.utils, len) });
+        self.result = Some(match text {
+            Some(text) => TextEntryResult::Submitted(text),
+            None => TextEntryResult::Cancelled,
+        });
+    }
+
+    /// Applies a [`FloatingGamepadTextInputDismissed`], completing a
+    /// floating entry
+    pub fn on_floating_gamepad_text_input_dismissed(&mut self) {
+        if self.result.is_none() && matches!(self.keyboard, TextEntryKeyboard::Floating(..)) {
+            self.result = Some(TextEntryResult::Dismissed);
+        }
+    }
+}
+
+impl CallbackStateMachine for TextEntry {
+    type Event = TextEntryResult;
+
+    fn handle_callback(&mut self, callback: &CallbackResult) {
+        match callback {
+            CallbackResult::GamepadTextInputDismissed(dismissed) => {
+                self.on_gamepad_text_input_dismissed(dismissed)
+            }
+            CallbackResult::FloatingGamepadTextInputDismissed(_) => {
+                self.on_floating_gamepad_text_input_dismissed()
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns the result once the keyboard has been closed
+    fn poll(&mut self) -> Vec<TextEntryResult> {
+        match &self.result {
+            Some(result) if !self.reported => {
+                self.reported = true;
+                vec![result.clone()]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Reads the text entered into the full screen text input dialog
+unsafe fn entered_gamepad_text(utils: *mut sys::ISteamUtils, len: u32) -> Option<String> {
+    // Leave room for the null terminator
+    let mut buf = vec![0u8; len as usize + 1];
+    if !sys::SteamAPI_ISteamUtils_GetEnteredGamepadTextInput(
+        utils,
+        buf.as_mut_ptr().cast(),
+        buf.len() as u32,
+    ) {
+        return None;
+    }
+    let text = CStr::from_bytes_until_nul(&buf).ok()?;
+    Some(text.to_string_lossy().into_owned())
+}
+
+impl Utils {
+    /// Returns the platform the game is running on, see [`SteamPlatform`]
+    pub fn platform(&self) -> SteamPlatform {
+        SteamPlatform {
+            steam_deck: self.is_steam_running_on_steam_deck(),
+            big_picture: self.is_steam_in_big_picture_mode(),
+            vr: self.is_steam_running_in_vr(),
+            battery: self.current_battery_power(),
+        }
+    }
+
+    /// Opens an on-screen keyboard for text entry.
+    ///
+    /// Returns `None` if the keyboard could not be shown, e.g. because
+    /// Steam is not running in Big Picture mode or on a Steam Deck.
+    ///
+    /// Steam only shows one keyboard at a time, so an entry that is
+    /// replaced by a newer one should be dropped, as it would otherwise
+    /// also complete with the result of the newer keyboard.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the description or existing text contain a null byte.
+    pub fn open_text_entry(&self, options: TextEntryOptions<'_>) -> Option<TextEntry> {
+        unsafe {
+            let shown = match options.keyboard {
+                TextEntryKeyboard::FullScreen => {
+                    let description = CString::new(options.description).unwrap();
+                    let existing_text = options.existing_text.map(|s| CString::new(s).unwrap());
+                    let mode = if options.password {
+                        GamepadTextInputMode::Password
+                    } else {
+                        GamepadTextInputMode::Normal
+                    };
+                    let line_mode = if options.multiline {
+                        GamepadTextInputLineMode::MultipleLines
+                    } else {
+                        GamepadTextInputLineMode::SingleLine
+                    };
+                    sys::SteamAPI_ISteamUtils_ShowGamepadTextInput(
+                        self.utils,
+                        mode.into(),
+                        line_mode.into(),
+                        description.as_ptr(),
+                        options.max_characters,
+                        existing_text
+                            .as_ref()
+                            .map_or(std::ptr::null(), |s| s.as_ptr()),
+                    )
+                }
+                TextEntryKeyboard::Floating(x, y, width, height) => {
+                    let mode = if options.multiline {
+                        FloatingGamepadTextInputMode::MultipleLines
+                    } else {
+                        FloatingGamepadTextInputMode::SingleLine
+                    };
+                    sys::SteamAPI_ISteamUtils_ShowFloatingGamepadTextInput(
+                        self.utils,
+                        mode.into(),
+                        x,
+                        y,
+                        width,
+                        height,
+                    )
+                }
+            };
+            let entry = TextEntry {
+                utils: self.utils,
+                keyboard: options.keyboard,
+                result: None,
+                reported: false,
+            };
+            shown.then_some(entry)
+        }
+    }
+
+    /// Closes the full screen text input dialog
+    pub fn dismiss_gamepad_text_input(&self) -> bool {
+        unsafe { sys::SteamAPI_ISteamUtils_DismissGamepadTextInput(self.utils) }
+    }
+
+    /// Closes the floating keyboard
+    pub fn dismiss_floating_gamepad_text_input(&self) -> bool {
+        unsafe { sys::SteamAPI_ISteamUtils_DismissFloatingGamepadTextInput(self.utils) }
+    }
+
+    /// Tells Steam that the game is a launcher, so the Steam Deck and Big
+    /// Picture show the desktop keyboard and do not expect gamepad input.
+    pub fn set_game_launcher_mode(&self, launcher_mode: bool) {
+        unsafe { sys::SteamAPI_ISteamUtils_SetGameLauncherMode(self.utils, launcher_mode) }
+    }
+
+    /// Checks if Steam is running in VR mode
+    pub fn is_steam_running_in_vr(&self) -> bool {
+        unsafe { sys::SteamAPI_ISteamUtils_IsSteamRunningInVR(self.utils) }
+    }
+
+    /// Checks if the game is streamed to the VR headset when Steam is
+    /// running in VR mode
+    pub fn is_vr_headset_streaming_enabled(&self) -> bool {
+        unsafe { sys::SteamAPI_ISteamUtils_IsVRHeadsetStreamingEnabled(self.utils) }
+    }
+
+    /// Sets whether the game is streamed to the VR headset when Steam is
+    /// running in VR mode. Games that support VR natively should disable
+    /// this.
+    pub fn set_vr_headset_streaming_enabled(&self, enabled: bool) {
+        unsafe { sys::SteamAPI_ISteamUtils_SetVRHeadsetStreamingEnabled(self.utils, enabled) }
+    }
+
+    /// Asks SteamVR to open the VR dashboard
+    pub fn start_vr_dashboard(&self) {
+        unsafe { sys::SteamAPI_ISteamUtils_StartVRDashboard(self.utils) }
+    }
+
+    /// Returns the battery level in percent, or `None` if the computer is
+    /// running on AC power
+    pub fn current_battery_power(&self) -> Option<u8> {
+        let power = unsafe { sys::SteamAPI_ISteamUtils_GetCurrentBatteryPower(self.utils) };
+        (power != 255).then_some(power)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(keyboard: TextEntryKeyboard) -> TextEntry {
+        TextEntry {
+            utils: std::ptr::null_mut(),
+            keyboard,
+            result: None,
+            reported: false,
+        }
+    }
+
+    #[test]
+    fn completes_once_for_its_keyboard() {
+        let mut floating = entry(TextEntryKeyboard::Floating(0, 0, 100, 100));
+        floating.handle_callback(&CallbackResult::GamepadTextInputDismissed(
+            GamepadTextInputDismissed {
+                submitted_text_len: None,
+            },
+        ));
+        assert_eq!(floating.try_result(), None);
+        floating.handle_callback(&CallbackResult::FloatingGamepadTextInputDismissed(
+            FloatingGamepadTextInputDismissed,
+        ));
+        assert_eq!(floating.poll(), vec![TextEntryResult::Dismissed]);
+        assert!(floating.poll().is_empty());
+        assert_eq!(floating.try_result(), Some(TextEntryResult::Dismissed));
+
+        let mut full_screen = entry(TextEntryKeyboard::FullScreen);
+        full_screen.handle_callback(&CallbackResult::FloatingGamepadTextInputDismissed(
+            FloatingGamepadTextInputDismissed,
+        ));
+        assert!(full_screen.poll().is_empty());
+        full_screen.handle_callback(&CallbackResult::GamepadTextInputDismissed(
+            GamepadTextInputDismissed {
+                submitted_text_len: None,
+            },
+        ));
+        assert_eq!(full_screen.poll(), vec![TextEntryResult::Cancelled]);
+    }
+}
@@ -0,0 +1,223 @@
+use super::*;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The protocols IPv6 connectivity can be checked for
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum IPv6ConnectivityProtocol {
+    Http,
+    Udp,
+}
+
+impl From<IPv6ConnectivityProtocol> for sys::ESteamIPv6ConnectivityProtocol {
+    fn from(protocol: IPv6ConnectivityProtocol) -> Self {
+        match protocol {
+            IPv6ConnectivityProtocol::Http => {
+                sys::ESteamIPv6ConnectivityProtocol::k_ESteamIPv6ConnectivityProtocol_HTTP
+            }
+            IPv6ConnectivityProtocol::Udp => {
+                sys::ESteamIPv6ConnectivityProtocol::k_ESteamIPv6ConnectivityProtocol_UDP
+            }
+        }
+    }
+}
+
+/// The result of an IPv6 connectivity check
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum IPv6ConnectivityState {
+    /// Steam has not checked connectivity yet
+    Unknown,
+    /// Steam could reach a server over IPv6
+    Good,
+    /// Steam failed to reach a server over IPv6
+    Bad,
+}
+
+impl From<sys::ESteamIPv6ConnectivityState> for IPv6ConnectivityState {
+    fn from(state: sys::ESteamIPv6ConnectivityState) -> Self {
+        match state {
+            sys::ESteamIPv6ConnectivityState::k_ESteamIPv6ConnectivityState_Good => {
+                IPv6ConnectivityState::Good
+            }
+            sys::ESteamIPv6ConnectivityState::k_ESteamIPv6ConnectivityState_Bad => {
+                IPv6ConnectivityState::Bad
+            }
+            _ => IPv6ConnectivityState::Unknown,
+        }
+    }
+}
+
+/// The result of [`Utils::check_file_signature`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum FileSignatureCheck {
+    InvalidSignature,
+    ValidSignature,
+    FileNotFound,
+    NoSignaturesFoundForThisApp,
+    NoSignaturesFoundForThisFile,
+}
+
+impl From<sys::ECheckFileSignature> for FileSignatureCheck {
+    fn from(check: sys::ECheckFileSignature) -> Self {
+        use sys::ECheckFileSignature::*;
+        match check {
+            k_ECheckFileSignatureValidSignature => FileSignatureCheck::ValidSignature,
+            k_ECheckFileSignatureFileNotFound => FileSignatureCheck::FileNotFound,
+            k_ECheckFileSignatureNoSignaturesFoundForThisApp => {
+                FileSignatureCheck::NoSignaturesFoundForThisApp
+            }
+            k_ECheckFileSignatureNoSignaturesFoundForThisFile => {
+                FileSignatureCheck::NoSignaturesFoundForThisFile
+            }
+            _ => FileSignatureCheck::InvalidSignature,
+        }
+    }
+}
+
+/// A snapshot of the environment the game is running in, for attaching
+/// to crash and support reports.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EnvironmentReport {
+    pub app_id: AppId,
+    pub universe: Universe,
+    pub ip_country: String,
+    pub ui_language: String,
+    pub steam_china_launcher: bool,
+    pub steam_deck: bool,
+    pub big_picture: bool,
+    pub vr: bool,
+    pub ipv6_http: IPv6ConnectivityState,
+    pub ipv6_udp: IPv6ConnectivityState,
+    pub seconds_since_app_active: u32,
+    pub seconds_since_computer_active: u32,
+    /// The number of IPC calls made since the last report was created
+    pub ipc_call_count: u32,
+    /// The time on the Steam servers, in seconds since the unix epoch
+    pub server_real_time: u32,
+}
+
+impl fmt::Display for EnvironmentReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "app id: {}", self.app_id.0)?;
+        writeln!(f, "universe: {:?}", self.universe)?;
+        writeln!(f, "ip country: {}", self.ip_country)?;
+        writeln!(f, "ui language: {}", self.ui_language)?;
+        writeln!(f, "steam china launcher: {}", self.steam_china_launcher)?;
+        writeln!(f, "steam deck: {}", self.steam_deck)?;
+        writeln!(f, "big picture: {}", self.big_picture)?;
+        writeln!(f, "vr: {}", self.vr)?;
+        writeln!(f, "ipv6 http: {:?}", self.ipv6_http)?;
+        writeln!(f, "ipv6 udp: {:?}", self.ipv6_udp)?;
+        writeln!(
+            f,
+            "seconds since app active: {}",
+            self.seconds_since_app_active
+        )?;
+        writeln!(
+            f,
+            "seconds since computer active: {}",
+            self.seconds_since_computer_active
+        )?;
+        writeln!(f, "ipc call count: {}", self.ipc_call_count)?;
+        writeln!(f, "server real time: {}", self.server_real_time)
+    }
+}
+
+impl Utils {
+    /// Returns the Steam universe the client is connected to
+    pub fn connected_universe(&self) -> Universe {
+        unsafe { sys::SteamAPI_ISteamUtils_GetConnectedUniverse(self.utils).into() }
+    }
+
+    /// Returns the number of seconds since the user last moved the mouse
+    /// or pressed a key in the game
+    pub fn seconds_since_app_active(&self) -> u32 {
+        unsafe { sys::SteamAPI_ISteamUtils_GetSecondsSinceAppActive(self.utils) }
+    }
+
+    /// Returns the number of seconds since the user last used the
+    /// computer
+    pub fn seconds_since_computer_active(&self) -> u32 {
+        unsafe { sys::SteamAPI_ISteamUtils_GetSecondsSinceComputerActive(self.utils) }
+    }
+
+    /// Returns the IPv6 connectivity of the user for the given protocol.
+    ///
+    /// Steam only checks IPv6 connectivity once it is needed, so this may
+    /// return [`IPv6ConnectivityState::Unknown`] for some time.
+    pub fn ipv6_connectivity_state(
+        &self,
+        protocol: IPv6ConnectivityProtocol,
+    ) -> IPv6ConnectivityState {
+        unsafe { sys::SteamAPI_ISteamUtils_GetIPv6ConnectivityState(self.utils, protocol.into()) }
+            .into()
+    }
+
+    /// Returns the number of IPC calls made since the last time this
+    /// function was called
+    pub fn ipc_call_count(&self) -> u32 {
+        unsafe { sys::SteamAPI_ISteamUtils_GetIPCCallCount(self.utils) }
+    }
+
+    /// Returns whether Steam was launched through the Steam China launcher
+    pub fn is_steam_china_launcher(&self) -> bool {
+        unsafe { sys::SteamAPI_ISteamUtils_IsSteamChinaLauncher(self.utils) }
+    }
+
+    /// Returns the current real time on the Steam servers
+    pub fn server_real_time(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(self.get_server_real_time().into())
+    }
+
+    /// Checks the signature of an executable file signed by Steam.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `file_name` contains a null byte.
+    pub fn check_file_signature<F>(&self, file_name: &str, cb: F)
+    where
+        F: FnOnce(SResult<FileSignatureCheck>) + 'static + Send,
+    {
+        let file_name = CString::new(file_name).unwrap();
+        unsafe {
+            let api_call =
+                sys::SteamAPI_ISteamUtils_CheckFileSignature(self.utils, file_name.as_ptr());
+            register_call_result::<sys::CheckFileSignature_t, _>(
+                &self._inner,
+                api_call,
+                move |v, io_error| {
+                    cb(if io_error {
+                        Err(SteamError::IOFailure)
+                    } else {
+                        Ok(v.m_eCheckFileSignature.into())
+                    })
+                },
+            );
+        }
+    }
+
+    /// Collects an [`EnvironmentReport`].
+    ///
+    /// This resets the count returned by [`ipc_call_count`](Self::ipc_call_count).
+    pub fn environment_report(&self) -> EnvironmentReport {
+        EnvironmentReport {
+            app_id: self.app_id(),
+            universe: self.connected_universe(),
+            ip_country: self.ip_country(),
+            ui_language: self.ui_language(),
+            steam_china_launcher: self.is_steam_china_launcher(),
+            steam_deck: self.is_steam_running_on_steam_deck(),
+            big_picture: self.is_steam_in_big_picture_mode(),
+            vr: self.is_steam_running_in_vr(),
+            ipv6_http: self.ipv6_connectivity_state(IPv6ConnectivityProtocol::Http),
+            ipv6_udp: self.ipv6_connectivity_state(IPv6ConnectivityProtocol::Udp),
+            seconds_since_app_active: self.seconds_since_app_active(),
+            seconds_since_computer_active: self.seconds_since_computer_active(),
+            ipc_call_count: self.ipc_call_count(),
+            server_real_time: self.get_server_real_time(),
+        }
+    }
+}
@@ -0,0 +1,1026 @@
+//! A client for the server-side Steam Web API.
+//!
+//! These endpoints are meant to be called by a game's backend with a
+//! publisher key and never from the game client, where the key could be
+//! extracted. They complete flows started through the client API, like
+//! verifying tickets from
+//! [`User::authentication_session_ticket_for_webapi`](crate::User::authentication_session_ticket_for_webapi)
+//! or finalizing a purchase after
+//! [`MicroTxnAuthorizationResponse`](crate::MicroTxnAuthorizationResponse).
+//!
+//! The crate does not include an HTTP client. Requests are sent through a
+//! [`WebApiTransport`], which can wrap any HTTP client or be a closure
+//! returning canned responses in tests.
+//!
+//! # Example
+//! ```no_run
+//! # use steamworks::*;
+//! # use steamworks::webapi::*;
+//! # fn http_send(_: &HttpRequest) -> Result<HttpResponse, TransportError> {
+//! #     Err("no http client in this example".into())
+//! # }
+//! let api = WebApi::new(http_send, "publisher key");
+//! let ownership = api
+//!     .check_app_ownership(SteamId::from_raw(76561197960287930), AppId(480))
+//!     .unwrap();
+//! println!("owns app: {}", ownership.owns_app);
+//! ```
+
+use crate::{AppId, PublishedFileId, SteamId, UGCQueryType};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
+use std::fmt::{self, Write};
+use std::net::{IpAddr, SocketAddr};
+use steamworks_sys as sys;
+use thiserror::Error;
+
+/// The host of the publisher-only Web API
+pub const PARTNER_BASE_URL: &str = "https://partner.steam-api.com";
+
+/// An error returned by a [`WebApiTransport`]
+pub type TransportError = Box<dyn std::error::Error + Send + Sync>;
+
+/// The HTTP method of a [`HttpRequest`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HttpMethod {
+    Get,
+    Post,
+}
+
+/// An HTTP request to the Web API
+///
+/// The publisher key is part of the URL or body, so it is redacted when
+/// the request is formatted with `Debug`.
+#[derive(Clone, PartialEq, Eq)]
+pub struct HttpRequest {
+    pub method: HttpMethod,
+    /// The full URL, including the query string for `GET` requests
+    pub url: String,
+    /// The `application/x-www-form-urlencoded` body of `POST` requests
+    pub body: Option<String>,
+}
+
+impl fmt::Debug for HttpRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HttpRequest")
+            .field("method", &self.method)
+            .field("url", &redact_key(&self.url))
+            .field("body", &self.body.as_deref().map(redact_key))
+            .finish()
+    }
+}
+
+/// Replaces the value of the `key` parameter of a URL or form body
+fn redact_key(params: &str) -> String {
+    let (prefix, query) = match params.split_once('?') {
+        Some((path, query)) => (&params[..=path.len()], query),
+        None => ("", params),
+    };
+    let query: Vec<_> = query
+        .split('&')
+        .map(|param| {
+            if param.starts_with("key=") {
+                "key=<redacted>"
+            } else {
+                param
+            }
+        })
+        .collect();
+    format!("{}{}", prefix, query.join("&"))
+}
+
+/// The HTTP response to a [`HttpRequest`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: Vec<u8>,
+}
+
+/// Sends requests to the Web API
+pub trait WebApiTransport {
+    /// Sends a request and waits for the response.
+    ///
+    /// Non-success status codes should be returned as a response and not
+    /// as an error.
+    fn send(&self, request: &HttpRequest) -> Result<HttpResponse, TransportError>;
+}
+
+impl<F> WebApiTransport for F
+where
+    F: Fn(&HttpRequest) -> Result<HttpResponse, TransportError>,
+{
+    fn send(&self, request: &HttpRequest) -> Result<HttpResponse, TransportError> {
+        self(request)
+    }
+}
+
+/// Errors from [`WebApi`]
+#[derive(Debug, Error)]
+pub enum WebApiError {
+    #[error("transport error: {0}")]
+    Transport(#[source] TransportError),
+    /// The request returned a non-success HTTP status, e.g. 403 for an
+    /// invalid key
+    #[error("http status {0}")]
+    Status(u16),
+    #[error("invalid response: {0}")]
+    Parse(#[from] serde_json::Error),
+    /// The request was processed but Steam returned an error
+    #[error("steam error {code}: {description}")]
+    Api { code: i32, description: String },
+}
+
+/// The result of [`WebApi::authenticate_user_ticket`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AuthenticatedTicket {
+    pub steam_id: SteamId,
+    /// The owner of the game, which differs from `steam_id` when the game
+    /// is borrowed through Family Sharing
+    pub owner_steam_id: SteamId,
+    pub vac_banned: bool,
+    pub publisher_banned: bool,
+}
+
+/// The result of [`WebApi::check_app_ownership`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AppOwnership {
+    pub owns_app: bool,
+    pub permanent: bool,
+    /// When the license was granted, as an ISO 8601 timestamp
+    pub timestamp: String,
+    pub owner_steam_id: SteamId,
+    pub site_license: bool,
+    pub timed_trial: bool,
+}
+
+/// Where a microtransaction was started from
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum UserSession {
+    /// The in-game overlay
+    Client,
+    /// A web browser, which is sent to the returned
+    /// [`InitTxnResponse::steam_url`]
+    Web,
+}
+
+/// An item of a microtransaction
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MicroTxnItem {
+    pub item_id: u32,
+    pub quantity: u16,
+    /// The total cost in the smallest unit of the currency, e.g. cents
+    pub amount: i64,
+    pub description: String,
+    pub category: Option<String>,
+}
+
+/// The parameters of [`WebApi::init_txn`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InitTxnRequest {
+    /// A unique id chosen by the backend
+    pub order_id: u64,
+    pub steam_id: SteamId,
+    pub app_id: AppId,
+    /// The ISO 639-1 language of the item descriptions
+    pub language: String,
+    /// The ISO 4217 currency of the item amounts
+    pub currency: String,
+    pub user_session: UserSession,
+    /// The IP address of the user, required for [`UserSession::Web`]
+    pub ip_address: Option<IpAddr>,
+    pub items: Vec<MicroTxnItem>,
+}
+
+/// The result of [`WebApi::init_txn`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InitTxnResponse {
+    pub order_id: u64,
+    pub trans_id: u64,
+    /// The URL to send the user to for [`UserSession::Web`]
+    pub steam_url: Option<String>,
+}
+
+/// The result of [`WebApi::finalize_txn`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FinalizeTxnResponse {
+    pub order_id: u64,
+    pub trans_id: u64,
+}
+
+/// The parameters of [`WebApi::query_files`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QueryFilesRequest {
+    pub app_id: AppId,
+    pub query_type: UGCQueryType,
+    /// The cursor returned by the previous page, `None` for the first page
+    pub cursor: Option<String>,
+    pub per_page: u32,
+    pub required_tags: Vec<String>,
+    pub excluded_tags: Vec<String>,
+    pub search_text: Option<String>,
+}
+
+impl QueryFilesRequest {
+    /// Creates a request for the first page of items of an app
+    pub fn new(app_id: AppId, query_type: UGCQueryType) -> QueryFilesRequest {
+        QueryFilesRequest {
+            app_id,
+            query_type,
+            cursor: None,
+            per_page: 50,
+            required_tags: Vec::new(),
+            excluded_tags: Vec::new(),
+            search_text: None,
+        }
+    }
+}
+
+/// The result of [`WebApi::query_files`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QueryFilesResponse {
+    /// The number of items matching the query across all pages
+    pub total: u32,
+    pub files: Vec<PublishedFileDetails>,
+    /// The cursor of the next page, if there is one
+    pub next_cursor: Option<String>,
+}
+
+/// A workshop item returned by [`WebApi::query_files`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PublishedFileDetails {
+    pub published_file_id: PublishedFileId,
+    pub creator: SteamId,
+    pub creator_app_id: AppId,
+    pub consumer_app_id: AppId,
+    pub title: String,
+    pub short_description: String,
+    pub file_size: u64,
+    pub preview_url: String,
+    pub time_created: u32,
+    pub time_updated: u32,
+    pub tags: Vec<String>,
+    pub metadata: String,
+    pub subscriptions: u32,
+    pub favorited: u32,
+}
+
+/// A game server returned by [`WebApi::get_servers_at_address`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ServerAtAddress {
+    pub addr: SocketAddr,
+    pub steam_id: SteamId,
+    pub app_id: AppId,
+    pub game_dir: String,
+    pub region: i32,
+    pub secure: bool,
+    pub lan: bool,
+    pub game_port: u16,
+    pub spec_port: u16,
+}
+
+/// A Web API client authenticated with a publisher key
+pub struct WebApi<T> {
+    transport: T,
+    key: String,
+    base_url: String,
+    microtxn_sandbox: bool,
+}
+
+impl<T: WebApiTransport> WebApi<T> {
+    /// Creates a client sending requests to [`PARTNER_BASE_URL`]
+    pub fn new(transport: T, key: impl Into<String>) -> WebApi<T> {
+        WebApi {
+            transport,
+            key: key.into(),
+            base_url: PARTNER_BASE_URL.to_owned(),
+            microtxn_sandbox: false,
+        }
+    }
+
+    /// Sends requests to another host, e.g. a local stub server
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        while self.base_url.ends_with('/') {
+            self.base_url.pop();
+        }
+        self
+    }
+
+    /// Sends microtransaction requests to `ISteamMicroTxnSandbox`, where
+    /// no money is charged
+    pub fn with_microtxn_sandbox(mut self, sandbox: bool) -> Self {
+        self.microtxn_sandbox = sandbox;
+        self
+    }
+
+    /// Returns the transport requests are sent through
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    /// Verifies a ticket from
+    /// [`User::authentication_session_ticket_for_webapi`](crate::User::authentication_session_ticket_for_webapi).
+    ///
+    /// `identity` must match the identity the ticket was created for.
+    pub fn authenticate_user_ticket(
+        &self,
+        app_id: AppId,
+        ticket: &[u8],
+        identity: Option<&str>,
+    ) -> Result<AuthenticatedTicket, WebApiError> {
+        let mut hex = String::with_capacity(ticket.len() * 2);
+        for byte in ticket {
+            write!(hex, "{:02X}", byte).unwrap();
+        }
+        let mut params = vec![("appid", app_id.0.to_string()), ("ticket", hex)];
+        if let Some(identity) = identity {
+            params.push(("identity", identity.to_owned()));
+        }
+        let raw: Envelope<RawAuthenticateUserTicket> = self.call(
+            HttpMethod::Get,
+            "ISteamUserAuth/AuthenticateUserTicket/v1",
+            &params,
+        )?;
+        let raw = raw.response;
+        match (raw.params, raw.error) {
+            (Some(params), _) => Ok(AuthenticatedTicket {
+                steam_id: SteamId::from_raw(params.steamid),
+                owner_steam_id: SteamId::from_raw(params.ownersteamid),
+                vac_banned: params.vacbanned,
+                publisher_banned: params.publisherbanned,
+            }),
+            (None, error) => Err(api_error(error)),
+        }
+    }
+
+    /// Checks whether a user owns an app, including through Family
+    /// Sharing
+    pub fn check_app_ownership(
+        &self,
+        steam_id: SteamId,
+        app_id: AppId,
+    ) -> Result<AppOwnership, WebApiError> {
+        let raw: RawAppOwnershipEnvelope = self.call(
+            HttpMethod::Get,
+            "ISteamUser/CheckAppOwnership/v4",
+            &[
+                ("steamid", steam_id.raw().to_string()),
+                ("appid", app_id.0.to_string()),
+            ],
+        )?;
+        let raw = raw.appownership;
+        Ok(AppOwnership {
+            owns_app: raw.ownsapp,
+            permanent: raw.permanent,
+            timestamp: raw.timestamp,
+            owner_steam_id: SteamId::from_raw(raw.ownersteamid),
+            site_license: raw.sitelicense,
+            timed_trial: raw.timedtrial,
+        })
+    }
+
+    /// Starts a microtransaction. Steam asks the user to authorize it and
+    /// sends
+    /// [`MicroTxnAuthorizationResponse`](crate::MicroTxnAuthorizationResponse)
+    /// to the game.
+    pub fn init_txn(&self, request: &InitTxnRequest) -> Result<InitTxnResponse, WebApiError> {
+        let mut params = vec![
+            ("orderid", request.order_id.to_string()),
+            ("steamid", request.steam_id.raw().to_string()),
+            ("appid", request.app_id.0.to_string()),
+            ("itemcount", request.items.len().to_string()),
+            ("language", request.language.clone()),
+            ("currency", request.currency.clone()),
+            (
+                "usersession",
+                match request.user_session {
+                    UserSession::Client => "client",
+                    UserSession::Web => "web",
+                }
+                .to_owned(),
+            ),
+        ];
+        if let Some(ip_address) = request.ip_address {
+            params.push(("ipaddress", ip_address.to_string()));
+        }
+        let mut item_params = Vec::new();
+        for (i, item) in request.items.iter().enumerate() {
+            item_params.push((format!("itemid[{}]", i), item.item_id.to_string()));
+            item_params.push((format!("qty[{}]", i), item.quantity.to_string()));
+            item_params.push((format!("amount[{}]", i), item.amount.to_string()));
+            item_params.push((format!("description[{}]", i), item.description.clone()));
+            if let Some(category) = &item.category {
+                item_params.push((format!("category[{}]", i), category.clone()));
+            }
+        }
+        params.extend(item_params.iter().map(|(k, v)| (k.as_str(), v.clone())));
+
+        let raw: RawTxn = self.call_microtxn("InitTxn/v3", &params)?;
+        Ok(InitTxnResponse {
+            order_id: raw.orderid,
+            trans_id: raw.transid,
+            steam_url: raw.steamurl,
+        })
+    }
+
+    /// Completes a microtransaction the user has authorized
+    pub fn finalize_txn(
+        &self,
+        app_id: AppId,
+        order_id: u64,
+    ) -> Result<FinalizeTxnResponse, WebApiError> {
+        let raw: RawTxn = self.call_microtxn(
+            "FinalizeTxn/v2",
+            &[
+                ("orderid", order_id.to_string()),
+                ("appid", app_id.0.to_string()),
+            ],
+        )?;
+        Ok(FinalizeTxnResponse {
+            order_id: raw.orderid,
+            trans_id: raw.transid,
+        })
+    }
+
+    /// Sets integer stats of a user. Only stats set to be writable by the
+    /// Web API can be set.
+    pub fn set_user_stats_for_game(
+        &self,
+        steam_id: SteamId,
+        app_id: AppId,
+        stats: &[(&str, i32)],
+    ) -> Result<(), WebApiError> {
+        let mut params = vec![
+            ("steamid".to_owned(), steam_id.raw().to_string()),
+            ("appid".to_owned(), app_id.0.to_string()),
+            ("count".to_owned(), stats.len().to_string()),
+        ];
+        for (i, (name, value)) in stats.iter().enumerate() {
+            params.push((format!("name[{}]", i), (*name).to_owned()));
+            params.push((format!("value[{}]", i), value.to_string()));
+        }
+        let params: Vec<_> = params
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.clone()))
+            .collect();
+        let raw: RawSetUserStats = self.call(
+            HttpMethod::Post,
+            "ISteamUserStats/SetUserStatsForGame/v1",
+            &params,
+        )?;
+        match raw.result {
+            Some(result) if result.result != 1 => Err(WebApiError::Api {
+                code: result.result,
+                description: result.error.unwrap_or_default(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Queries a page of workshop items
+    pub fn query_files(
+        &self,
+        request: &QueryFilesRequest,
+    ) -> Result<QueryFilesResponse, WebApiError> {
+        let query_type: sys::EUGCQuery = request.query_type.into();
+        let mut params = vec![
+            ("query_type".to_owned(), (query_type as u32).to_string()),
+            ("appid".to_owned(), request.app_id.0.to_string()),
+            ("creator_appid".to_owned(), request.app_id.0.to_string()),
+            (
+                "cursor".to_owned(),
+                request.cursor.clone().unwrap_or_else(|| "*".to_owned()),
+            ),
+            ("numperpage".to_owned(), request.per_page.to_string()),
+            ("return_tags".to_owned(), "true".to_owned()),
+            ("return_metadata".to_owned(), "true".to_owned()),
+            ("return_short_description".to_owned(), "true".to_owned()),
+        ];
+        for (i, tag) in request.required_tags.iter().enumerate() {
+            params.push((format!("requiredtags[{}]", i), tag.clone()));
+        }
+        for (i, tag) in request.excluded_tags.iter().enumerate() {
+            params.push((format!("excludedtags[{}]", i), tag.clone()));
+        }
+        if let Some(search_text) = &request.search_text {
+            params.push(("search_text".to_owned(), search_text.clone()));
+        }
+        let params: Vec<_> = params
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.clone()))
+            .collect();
+        let raw: Envelope<RawQueryFiles> = self.call(
+            HttpMethod::Get,
+            "IPublishedFileService/QueryFiles/v1",
+            &params,
+        )?;
+        let raw = raw.response;
+        Ok(QueryFilesResponse {
+            total: raw.total,
+            files: raw
+                .publishedfiledetails
+                .into_iter()
+                .map(|file| PublishedFileDetails {
+                    published_file_id: PublishedFileId(file.publishedfileid),
+                    creator: SteamId::from_raw(file.creator),
+                    creator_app_id: AppId(file.creator_appid),
+                    consumer_app_id: AppId(file.consumer_appid),
+                    title: file.title,
+                    short_description: file.short_description,
+                    file_size: file.file_size,
+                    preview_url: file.preview_url,
+                    time_created: file.time_created,
+                    time_updated: file.time_updated,
+                    tags: file.tags.into_iter().map(|tag| tag.tag).collect(),
+                    metadata: file.metadata,
+                    subscriptions: file.subscriptions,
+                    favorited: file.favorited,
+                })
+                .collect(),
+            // The last page returns the cursor it was requested with
+            next_cursor: raw
+                .next_cursor
+                .filter(|cursor| !cursor.is_empty() && Some(cursor) != request.cursor.as_ref()),
+        })
+    }
+
+    /// Returns the game servers running on an IP address
+    pub fn get_servers_at_address(
+        &self,
+        addr: IpAddr,
+    ) -> Result<Vec<ServerAtAddress>, WebApiError> {
+        let raw: Envelope<RawServersAtAddress> = self.call(
+            HttpMethod::Get,
+            "ISteamApps/GetServersAtAddress/v1",
+            &[("addr", addr.to_string())],
+        )?;
+        let raw = raw.response;
+        if !raw.success {
+            return Err(WebApiError::Api {
+                code: 0,
+                description: raw.message.unwrap_or_default(),
+            });
+        }
+        raw.servers
+            .into_iter()
+            .map(|server| {
+                let addr = server.addr.parse().map_err(|_| WebApiError::Api {
+                    code: 0,
+                    description: format!("invalid server address {:?}", server.addr),
+                })?;
+                Ok(ServerAtAddress {
+                    addr,
+                    steam_id: SteamId::from_raw(server.steamid),
+                    app_id: AppId(server.appid),
+                    game_dir: server.gamedir,
+                    region: server.region,
+                    secure: server.secure,
+                    lan: server.lan,
+                    game_port: server.gameport,
+                    spec_port: server.specport,
+                })
+            })
+            .collect()
+    }
+
+    fn call_microtxn<R: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: &[(&str, String)],
+    ) -> Result<R, WebApiError> {
+        let interface = if self.microtxn_sandbox {
+            "ISteamMicroTxnSandbox"
+        } else {
+            "ISteamMicroTxn"
+        };
+        let raw: Envelope<RawMicroTxn<R>> = self.call(
+            HttpMethod::Post,
+            &format!("{}/{}", interface, method),
+            params,
+        )?;
+        match raw.response {
+            RawMicroTxn {
+                result,
+                params: Some(params),
+                ..
+            } if result == "OK" => Ok(params),
+            RawMicroTxn { error, .. } => Err(api_error(error)),
+        }
+    }
+
+    fn call<R: DeserializeOwned>(
+        &self,
+        method: HttpMethod,
+        path: &str,
+        params: &[(&str, String)],
+    ) -> Result<R, WebApiError> {
+        let mut query = format!("key={}", encode(&self.key));
+        for (name, value) in params {
+            write!(query, "&{}={}", encode(name), encode(value)).unwrap();
+        }
+        let url = format!("{}/{}/", self.base_url, path);
+        let request = match method {
+            HttpMethod::Get => HttpRequest {
+                method,
+                url: format!("{}?{}", url, query),
+                body: None,
+            },
+            HttpMethod::Post => HttpRequest {
+                method,
+                url,
+                body: Some(query),
+            },
+        };
+        let response = self
+            .transport
+            .send(&request)
+            .map_err(WebApiError::Transport)?;
+        if !(200..300).contains(&response.status) {
+            return Err(WebApiError::Status(response.status));
+        }
+        Ok(serde_json::from_slice(&response.body)?)
+    }
+}
+
+/// Percent-encodes a form value
+fn encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => write!(encoded, "%{:02X}", byte).unwrap(),
+        }
+    }
+    encoded
+}
+
+fn api_error(error: Option<RawError>) -> WebApiError {
+    match error {
+        Some(error) => WebApiError::Api {
+            code: error.errorcode,
+            description: error.errordesc,
+        },
+        None => WebApiError::Api {
+            code: 0,
+            description: "unknown error".to_owned(),
+        },
+    }
+}
+
+/// Parses 64-bit ids, which the Web API returns as strings
+fn de_u64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Repr {
+        Number(u64),
+        String(String),
+    }
+    match Repr::deserialize(deserializer)? {
+        Repr::Number(n) => Ok(n),
+        Repr::String(s) => s.parse().map_err(serde::de::Error::custom),
+    }
+}
+
+#[derive(Deserialize)]
+struct Envelope<T> {
+    response: T,
+}
+
+#[derive(Deserialize)]
+struct RawError {
+    errorcode: i32,
+    #[serde(default)]
+    errordesc: String,
+}
+
+#[derive(Deserialize)]
+struct RawAuthenticateUserTicket {
+    params: Option<RawTicketParams>,
+    error: Option<RawError>,
+}
+
+#[derive(Deserialize)]
+struct RawTicketParams {
+    #[serde(deserialize_with = "de_u64")]
+    steamid: u64,
+    #[serde(deserialize_with = "de_u64")]
+    ownersteamid: u64,
+    #[serde(default)]
+    vacbanned: bool,
+    #[serde(default)]
+    publisherbanned: bool,
+}
+
+#[derive(Deserialize)]
+struct RawAppOwnershipEnvelope {
+    appownership: RawAppOwnership,
+}
+
+#[derive(Deserialize)]
+struct RawAppOwnership {
+    ownsapp: bool,
+    #[serde(default)]
+    permanent: bool,
+    #[serde(default)]
+    timestamp: String,
+    #[serde(default, deserialize_with = "de_u64")]
+    ownersteamid: u64,
+    #[serde(default)]
+    sitelicense: bool,
+    #[serde(default)]
+    timedtrial: bool,
+}
+
+#[derive(Deserialize)]
+struct RawMicroTxn<T> {
+    result: String,
+    params: Option<T>,
+    error: Option<RawError>,
+}
+
+#[derive(Deserialize)]
+struct RawTxn {
+    #[serde(deserialize_with = "de_u64")]
+    orderid: u64,
+    #[serde(deserialize_with = "de_u64")]
+    transid: u64,
+    steamurl: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RawSetUserStats {
+    result: Option<RawSetUserStatsResult>,
+}
+
+#[derive(Deserialize)]
+struct RawSetUserStatsResult {
+    result: i32,
+    error: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RawQueryFiles {
+    #[serde(default)]
+    total: u32,
+    #[serde(default)]
+    publishedfiledetails: Vec<RawPublishedFile>,
+    next_cursor: Option<String>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct RawPublishedFile {
+    #[serde(deserialize_with = "de_u64")]
+    publishedfileid: u64,
+    #[serde(deserialize_with = "de_u64")]
+    creator: u64,
+    creator_appid: u32,
+    consumer_appid: u32,
+    title: String,
+    short_description: String,
+    #[serde(deserialize_with = "de_u64")]
+    file_size: u64,
+    preview_url: String,
+    time_created: u32,
+    time_updated: u32,
+    tags: Vec<RawTag>,
+    metadata: String,
+    subscriptions: u32,
+    favorited: u32,
+}
+
+#[derive(Deserialize)]
+struct RawTag {
+    tag: String,
+}
+
+#[derive(Deserialize)]
+struct RawServersAtAddress {
+    success: bool,
+    #[serde(default)]
+    servers: Vec<RawServer>,
+    message: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RawServer {
+    addr: String,
+    #[serde(deserialize_with = "de_u64")]
+    steamid: u64,
+    appid: u32,
+    #[serde(default)]
+    gamedir: String,
+    #[serde(default)]
+    region: i32,
+    #[serde(default)]
+    secure: bool,
+    #[serde(default)]
+    lan: bool,
+    #[serde(default)]
+    gameport: u16,
+    #[serde(default)]
+    specport: u16,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    fn stub<'a>(
+        status: u16,
+        body: &'static str,
+        requests: &'a RefCell<Vec<HttpRequest>>,
+    ) -> impl Fn(&HttpRequest) -> Result<HttpResponse, TransportError> + 'a {
+        move |request| {
+            requests.borrow_mut().push(request.clone());
+            Ok(HttpResponse {
+                status,
+                body: body.as_bytes().to_vec(),
+            })
+        }
+    }
+
+    #[test]
+    fn authenticate_user_ticket() {
+        let requests = RefCell::new(Vec::new());
+        let api = WebApi::new(
+            stub(
+                200,
+                r#"{"response":{"params":{"result":"OK","steamid":"76561197960287930",
+                "ownersteamid":"76561197960287931","vacbanned":false,"publisherbanned":true}}}"#,
+                &requests,
+            ),
+            "k&y",
+        )
+        .with_base_url("http://localhost:8080/");
+        let ticket = api
+            .authenticate_user_ticket(AppId(480), &[0x14, 0xab], Some("my server"))
+            .unwrap();
+        assert_eq!(ticket.steam_id, SteamId::from_raw(76561197960287930));
+        assert_eq!(ticket.owner_steam_id, SteamId::from_raw(76561197960287931));
+        assert!(!ticket.vac_banned);
+        assert!(ticket.publisher_banned);
+
+        let requests = requests.borrow();
+        assert_eq!(requests[0].method, HttpMethod::Get);
+        assert_eq!(
+            requests[0].url,
+            "http://localhost:8080/ISteamUserAuth/AuthenticateUserTicket/v1/\
+             ?key=k%26y&appid=480&ticket=14AB&identity=my%20server"
+        );
+        let debug = format!("{:?}", requests[0]);
+        assert!(!debug.contains("k%26y"));
+        assert!(debug.contains("v1/?key=<redacted>&appid=480"));
+    }
+
+    #[test]
+    fn api_errors() {
+        let requests = RefCell::new(Vec::new());
+        let api = WebApi::new(
+            stub(
+                200,
+                r#"{"response":{"error":{"errorcode":101,"errordesc":"Invalid ticket"}}}"#,
+                &requests,
+            ),
+            "key",
+        );
+        match api.authenticate_user_ticket(AppId(480), &[1], None) {
+            Err(WebApiError::Api { code, description }) => {
+                assert_eq!(code, 101);
+                assert_eq!(description, "Invalid ticket");
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+
+        let api = WebApi::new(stub(403, "<html>Forbidden</html>", &requests), "key");
+        assert!(matches!(
+            api.check_app_ownership(SteamId::from_raw(1), AppId(480)),
+            Err(WebApiError::Status(403))
+        ));
+    }
+
+    #[test]
+    fn microtxn() {
+        let requests = RefCell::new(Vec::new());
+        let api = WebApi::new(
+            stub(
+                200,
+                r#"{"response":{"result":"OK","params":{"orderid":"42","transid":"1234"}}}"#,
+                &requests,
+            ),
+            "key",
+        )
+        .with_microtxn_sandbox(true);
+        let response = api
+            .init_txn(&InitTxnRequest {
+                order_id: 42,
+                steam_id: SteamId::from_raw(76561197960287930),
+                app_id: AppId(480),
+                language: "en".to_owned(),
+                currency: "USD".to_owned(),
+                user_session: UserSession::Client,
+                ip_address: None,
+                items: vec![MicroTxnItem {
+                    item_id: 7,
+                    quantity: 2,
+                    amount: 199,
+                    description: "Gem pack".to_owned(),
+                    category: None,
+                }],
+            })
+            .unwrap();
+        assert_eq!(
+            response,
+            InitTxnResponse {
+                order_id: 42,
+                trans_id: 1234,
+                steam_url: None,
+            }
+        );
+        let finalized = api.finalize_txn(AppId(480), 42).unwrap();
+        assert_eq!(finalized.trans_id, 1234);
+
+        let sent = requests.borrow();
+        assert_eq!(sent[0].method, HttpMethod::Post);
+        assert_eq!(
+            sent[0].url,
+            "https://partner.steam-api.com/ISteamMicroTxnSandbox/InitTxn/v3/"
+        );
+        assert_eq!(
+            sent[0].body.as_deref(),
+            Some(
+                "key=key&orderid=42&steamid=76561197960287930&appid=480&itemcount=1\
+                 &language=en&currency=USD&usersession=client&itemid%5B0%5D=7&qty%5B0%5D=2\
+                 &amount%5B0%5D=199&description%5B0%5D=Gem%20pack"
+            )
+        );
+        assert!(format!("{:?}", sent[0]).contains("Some(\"key=<redacted>&orderid=42"));
+        drop(sent);
+
+        let api = WebApi::new(
+            stub(
+                200,
+                r#"{"response":{"result":"Failure","error":{"errorcode":10,"errordesc":"Denied"}}}"#,
+                &requests,
+            ),
+            "key",
+        );
+        assert!(matches!(
+            api.finalize_txn(AppId(480), 42),
+            Err(WebApiError::Api { code: 10, .. })
+        ));
+    }
+
+    #[test]
+    fn query_files() {
+        let requests = RefCell::new(Vec::new());
+        let api = WebApi::new(
+            stub(
+                200,
+                r#"{"response":{"total":2,"next_cursor":"AoJ4","publishedfiledetails":[
+                {"result":1,"publishedfileid":"2000","creator":"76561197960287930",
+                "creator_appid":480,"consumer_appid":480,"title":"Map","file_size":"1024",
+                "time_created":10,"time_updated":20,"tags":[{"tag":"Maps","display_name":"Maps"}]}]}}"#,
+                &requests,
+            ),
+            "key",
+        );
+        let mut request = QueryFilesRequest::new(AppId(480), UGCQueryType::RankedByVote);
+        request.required_tags.push("Maps".to_owned());
+        let page = api.query_files(&request).unwrap();
+        assert_eq!(page.total, 2);
+        assert_eq!(page.next_cursor.as_deref(), Some("AoJ4"));
+        assert_eq!(page.files[0].published_file_id, PublishedFileId(2000));
+        assert_eq!(page.files[0].file_size, 1024);
+        assert_eq!(page.files[0].tags, ["Maps"]);
+        assert!(requests.borrow()[0].url.contains("cursor=%2A"));
+
+        // The last page echoes the cursor back
+        request.cursor = Some("AoJ4".to_owned());
+        assert_eq!(api.query_files(&request).unwrap().next_cursor, None);
+    }
+
+    #[test]
+    fn servers_at_address() {
+        let requests = RefCell::new(Vec::new());
+        let api = WebApi::new(
+            stub(
+                200,
+                r#"{"response":{"success":true,"servers":[{"addr":"10.0.0.1:27015","gmsindex":-1,
+                "steamid":"90071992547409920","appid":480,"gamedir":"spacewar","region":-1,
+                "secure":true,"lan":false,"gameport":27015,"specport":0}]}}"#,
+                &requests,
+            ),
+            "key",
+        );
+        let servers = api
+            .get_servers_at_address("10.0.0.1".parse().unwrap())
+            .unwrap();
+        assert_eq!(servers[0].addr, "10.0.0.1:27015".parse().unwrap());
+        assert_eq!(servers[0].steam_id, SteamId::from_raw(90071992547409920));
+        assert!(servers[0].secure);
+    }
+}